@@ -0,0 +1,286 @@
+#![deny(clippy::all, rust_2018_idioms)]
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{quote, ToTokens};
+use syn::{
+    ext::IdentExt, parse_macro_input, parse_quote, Data, DeriveInput, Fields, Generics, LitStr,
+    Path,
+};
+
+#[proc_macro_derive(FromDid, attributes(did))]
+pub fn derive_from_did(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_from_did(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+#[proc_macro_derive(ToDid, attributes(did))]
+pub fn derive_to_did(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_to_did(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Struct with its `#[did(...)]` attributes parsed.
+struct Object {
+    name: String,
+    fields: Vec<Field>,
+}
+
+struct Field {
+    ident: syn::Ident,
+    key: String,
+    default: Option<Default>,
+    flatten: bool,
+}
+
+enum Default {
+    Trait,
+    Path(Path),
+}
+
+fn parse_object(input: &DeriveInput) -> syn::Result<Object> {
+    let mut name = None;
+    for attr in input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("did"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("name") {
+                let lit = meta.value()?.parse::<LitStr>()?;
+                name = Some(identifier(lit.value(), &lit)?);
+                Ok(())
+            } else {
+                Err(meta.error("unknown `did` attribute, expected `name`"))
+            }
+        })?;
+    }
+    let name = match name {
+        Some(name) => name,
+        None => identifier(input.ident.unraw().to_string(), &input.ident)?,
+    };
+
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => fields.named.iter().collect(),
+            Fields::Unit => Vec::new(),
+            Fields::Unnamed(_) => {
+                return Err(syn::Error::new_spanned(
+                    input,
+                    "`Дід` objects can only be mapped to structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(syn::Error::new_spanned(
+                input,
+                "`Дід` objects can only be mapped to structs",
+            ))
+        }
+    };
+
+    let fields = fields
+        .into_iter()
+        .map(|field| {
+            let ident = field.ident.clone().expect("named field");
+            let mut parsed = Field {
+                key: ident.unraw().to_string(),
+                ident,
+                default: None,
+                flatten: false,
+            };
+            let mut renamed = false;
+            for attr in field
+                .attrs
+                .iter()
+                .filter(|attr| attr.path().is_ident("did"))
+            {
+                attr.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("rename") {
+                        let lit = meta.value()?.parse::<LitStr>()?;
+                        parsed.key = identifier(lit.value(), &lit)?;
+                        renamed = true;
+                    } else if meta.path.is_ident("default") {
+                        parsed.default = Some(if meta.input.peek(syn::Token![=]) {
+                            Default::Path(meta.value()?.parse::<LitStr>()?.parse()?)
+                        } else {
+                            Default::Trait
+                        });
+                    } else if meta.path.is_ident("flatten") {
+                        parsed.flatten = true;
+                    } else {
+                        return Err(meta.error(
+                            "unknown `did` attribute, expected `rename`, `default` or `flatten`",
+                        ));
+                    }
+                    Ok(())
+                })?;
+            }
+            // The keys of flattened fields are never written.
+            if !renamed && !parsed.flatten {
+                identifier(parsed.key.clone(), &parsed.ident)?;
+            }
+            if parsed.flatten && parsed.default.is_some() {
+                return Err(syn::Error::new_spanned(
+                    &parsed.ident,
+                    "`flatten` fields cannot have a `default`",
+                ));
+            }
+            Ok(parsed)
+        })
+        .collect::<syn::Result<_>>()?;
+
+    Ok(Object { name, fields })
+}
+
+/// Checks that `value` can be written as an object name or key without quotes, by the
+/// same rule as `mavka_did::writer`, which cannot be used from here.
+fn identifier(value: String, spanned: impl ToTokens) -> syn::Result<String> {
+    let rest = match ["пусто", "так", "ні"]
+        .iter()
+        .find_map(|keyword| value.strip_prefix(keyword))
+    {
+        Some("") => None,
+        Some(rest) => Some(rest),
+        None => {
+            let mut chars = value.chars();
+            match chars.next() {
+                Some(c) if c == '_' || is_alpha(c) => Some(chars.as_str()),
+                _ => None,
+            }
+        }
+    };
+    let valid = rest.is_some_and(|rest| {
+        let mut chars = rest.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                c if c.is_ascii_digit() || c == '_' || is_alpha(c) => {}
+                '\'' | 'ʼ' if chars.next().is_some_and(is_alpha) => {}
+                _ => return false,
+            }
+        }
+        true
+    });
+    if valid {
+        Ok(value)
+    } else {
+        Err(syn::Error::new_spanned(
+            spanned,
+            format!("`{value}` is not a `Дід` identifier"),
+        ))
+    }
+}
+
+fn is_alpha(c: char) -> bool {
+    c.is_ascii_alphabetic()
+        || matches!(c, 'а'..='я' | 'А'..='Я' | 'і' | 'І' | 'ї' | 'Ї' | 'є' | 'Є' | 'ґ' | 'Ґ')
+}
+
+fn add_bound(generics: &Generics, bound: Path) -> Generics {
+    let mut generics = generics.clone();
+    for param in generics.type_params_mut() {
+        param.bounds.push(parse_quote!(#bound));
+    }
+    generics
+}
+
+fn expand_from_did(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let object = parse_object(&input)?;
+    let ident = &input.ident;
+    let generics = add_bound(&input.generics, parse_quote!(::mavka_did::FromDid));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let name = &object.name;
+
+    let fields = object.fields.iter().map(|field| {
+        let ident = &field.ident;
+        let key = &field.key;
+        let value = if field.flatten {
+            quote!(::mavka_did::convert::FromDidEntries::from_entries(
+                entries, context
+            )?)
+        } else {
+            match &field.default {
+                None => quote!(::mavka_did::convert::entry(entries, #key, context)?),
+                Some(Default::Trait) => quote! {
+                    ::mavka_did::convert::entry_or_else(
+                        entries,
+                        #key,
+                        ::core::default::Default::default,
+                    )?
+                },
+                Some(Default::Path(path)) => {
+                    quote!(::mavka_did::convert::entry_or_else(entries, #key, #path)?)
+                }
+            }
+        };
+        quote!(#ident: #value)
+    });
+
+    Ok(quote! {
+        impl #impl_generics ::mavka_did::FromDid for #ident #ty_generics #where_clause {
+            fn from_did(
+                did: &::mavka_did::parser::ast::Did<'_>,
+            ) -> ::core::result::Result<Self, ::mavka_did::convert::Error> {
+                let object = ::mavka_did::convert::expect_object(did, #name)?;
+                ::mavka_did::convert::FromDidEntries::from_entries(&object.entries, object.context)
+            }
+        }
+
+        impl #impl_generics ::mavka_did::convert::FromDidEntries for #ident #ty_generics #where_clause {
+            #[allow(unused_variables)]
+            fn from_entries(
+                entries: &[::mavka_did::parser::ast::ObjectEntryNode<'_>],
+                context: ::mavka_did::parser::ast::NodeContext,
+            ) -> ::core::result::Result<Self, ::mavka_did::convert::Error> {
+                ::core::result::Result::Ok(Self { #(#fields,)* })
+            }
+        }
+    })
+}
+
+fn expand_to_did(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let object = parse_object(&input)?;
+    let ident = &input.ident;
+    let generics = add_bound(&input.generics, parse_quote!(::mavka_did::ToDid));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let name = &object.name;
+
+    let entries = object.fields.iter().map(|field| {
+        let ident = &field.ident;
+        let key = &field.key;
+        if field.flatten {
+            quote!(::mavka_did::convert::ToDidEntries::to_entries(&self.#ident, entries);)
+        } else {
+            quote! {
+                entries.push(::mavka_did::convert::object_entry(
+                    #key,
+                    ::mavka_did::ToDid::to_did(&self.#ident),
+                ));
+            }
+        }
+    });
+
+    Ok(quote! {
+        impl #impl_generics ::mavka_did::ToDid for #ident #ty_generics #where_clause {
+            fn to_did(&self) -> ::mavka_did::parser::ast::Did<'_> {
+                let mut entries = ::std::vec::Vec::new();
+                ::mavka_did::convert::ToDidEntries::to_entries(self, &mut entries);
+                ::mavka_did::convert::object(#name, entries)
+            }
+        }
+
+        impl #impl_generics ::mavka_did::convert::ToDidEntries for #ident #ty_generics #where_clause {
+            #[allow(unused_variables)]
+            fn to_entries<'did>(
+                &'did self,
+                entries: &mut ::std::vec::Vec<::mavka_did::parser::ast::ObjectEntryNode<'did>>,
+            ) {
+                #(#entries)*
+            }
+        }
+    })
+}
@@ -0,0 +1,26 @@
+use mavka_did::parser::cst::{Element, Node, TokenKind};
+
+fn print_comments(node: &Node<'_>) {
+    for element in &node.children {
+        match element {
+            Element::Node(node) => print_comments(node),
+            Element::Token(token)
+                if matches!(token.kind, TokenKind::LineComment | TokenKind::BlockComment) =>
+            {
+                println!(
+                    "{}:{} {}",
+                    token.context.line, token.context.column, token.text
+                );
+            }
+            Element::Token(_) => {}
+        }
+    }
+}
+
+fn main() {
+    let input = ";; Налаштування\nЛюдина(\n  імʼя=\"Давид\", ;; повне ім'я\n  вік=0,\n)\n";
+    let document = mavka_did::parser::parse_cst(input).unwrap();
+    assert_eq!(document.to_string(), input);
+    print_comments(document.root());
+    println!("{:?}", document.to_did());
+}
@@ -0,0 +1,52 @@
+use mavka_did::{FromDid, ToDid};
+
+#[derive(Debug, FromDid, ToDid)]
+#[did(name = "Людина")]
+struct Person {
+    #[did(rename = "імʼя")]
+    name: String,
+    #[did(rename = "прізвище")]
+    surname: Option<String>,
+    #[did(rename = "вік", default)]
+    age: u32,
+    #[did(flatten)]
+    body: Body,
+    #[did(rename = "зацікавлення")]
+    interests: Vec<String>,
+}
+
+#[derive(Debug, FromDid, ToDid)]
+struct Body {
+    #[did(rename = "параметри")]
+    parameters: Parameters,
+}
+
+#[derive(Debug, FromDid, ToDid)]
+#[did(name = "Параметри")]
+struct Parameters {
+    #[did(rename = "висота")]
+    height: u32,
+    #[did(rename = "вага")]
+    weight: f64,
+}
+
+fn main() {
+    let input = r#"Людина(
+  імʼя="Давид",
+  параметри=Параметри(
+    висота=175,
+    вага=69.5
+  ),
+  зацікавлення=["творення", "життя"]
+)"#;
+    let did = mavka_did::parser::parse_did(input).unwrap();
+    let person = Person::from_did(&did).unwrap();
+    println!("{:#?}", person);
+    println!("{}", mavka_did::writer::to_string_pretty(&person.to_did()));
+
+    let did = mavka_did::parser::parse_did(
+        r#"Людина(імʼя="Давид", параметри=Параметри(висота=-1, вага=0), зацікавлення=[])"#,
+    )
+    .unwrap();
+    println!("{}", Person::from_did(&did).unwrap_err());
+}
@@ -0,0 +1,21 @@
+use mavka_did::{edit::Key, parser};
+
+fn main() {
+    let input = r#";; Налаштування людини
+Людина(
+  імʼя="Давид",
+  параметри=(висота=175, вага=69), ;; у сантиметрах і кілограмах
+  зацікавлення=["творення", "життя"],
+)
+"#;
+    let mut document = parser::parse_cst(input).unwrap();
+    document
+        .set(&[Key::from("параметри"), Key::from("висота")], "180")
+        .unwrap();
+    document
+        .push(&[Key::from("зацікавлення")], r#""мова""#)
+        .unwrap();
+    document.remove(&[Key::from("імʼя")]).unwrap();
+    document.set(&[Key::from("вік")], "0").unwrap();
+    print!("{document}");
+}
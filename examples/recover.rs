@@ -0,0 +1,8 @@
+fn main() {
+    let input = "Людина(\n  імʼя=\"Давид\" прізвище=\"Когут\",\n  вік=,\n  зацікавлення=[\"творення\" \"життя\"],\n)\n";
+    let (did, errors) = mavka_did::parser::parse_recovering(input);
+    for error in &errors {
+        println!("{error}");
+    }
+    println!("{}", mavka_did::writer::to_string_pretty(&did));
+}
@@ -0,0 +1,6 @@
+fn main() {
+    let input = r#"Людина(імʼя="Давид", параметри=(висота=175, "вага в кг"=69), зацікавлення=["творення", "життя"])"#;
+    let did = mavka_did::parser::parse(input).unwrap();
+    println!("{}", mavka_did::writer::to_string(&did));
+    println!("{}", mavka_did::writer::to_string_pretty(&did));
+}
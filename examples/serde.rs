@@ -0,0 +1,33 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize, Serialize)]
+struct Людина {
+    імʼя: String,
+    прізвище: String,
+    вік: u8,
+    параметри: HashMap<String, f64>,
+    зацікавлення: Vec<String>,
+    друг: Option<Box<Людина>>,
+}
+
+fn main() {
+    let input = r#"Людина(
+  імʼя="Давид",
+  прізвище="Когут",
+  вік=0,
+  параметри=(
+    висота=175,
+    вага=69.5
+  ),
+  зацікавлення=["творення", "життя"],
+  друг=пусто
+)"#;
+    let людина: Людина = mavka_did::from_str(input).unwrap();
+    println!("{:#?}", людина);
+    println!("{}", mavka_did::to_string_pretty(&людина).unwrap());
+
+    let error = mavka_did::from_str::<Людина>(r#"Людина(імʼя="Давид", вік=-1)"#);
+    println!("{}", error.unwrap_err());
+}
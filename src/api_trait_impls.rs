@@ -4,26 +4,43 @@ use wai_bindgen_rust::Handle;
 
 use crate::{
     api::{
-        AstNode, DictionaryEntryKey, DictionaryEntryNode, DictionaryNode, EmptyNode, ListNode,
-        LogicalNode, NodeContext, NumberNode, ObjectEntryNode, ObjectNode, ParseError,
-        ParseErrorExpectation, TextNode,
+        AstNode, ByteRange, DictionaryEntryKey, DictionaryEntryNode, DictionaryNode,
+        DuplicateKeyWarning, EmptyNode, EscapeErrorKind, Language, ListNode, LogicalNode,
+        NodeContext, NumberNode, ObjectEntryNode, ObjectNode, ParseError, ParseErrorExpectation,
+        ParseErrorReason, ParseWarning, TextNode,
     },
-    BoxedAstNode,
+    diagnostic,
+    parser::{
+        ast,
+        escape::{self, EscapeError},
+        number::{self, number_accessors, Decimal},
+        DuplicateKeys, ParseOptions, Warning,
+    },
+    BoxedAstNode, ErrorKind, Reason,
 };
 
 impl Default for NodeContext {
     fn default() -> Self {
-        Self {
-            line: 1,
-            column: 1,
-            index: 0,
-        }
+        ast::NodeContext::default().into()
     }
 }
 
 impl PartialEq for NodeContext {
     fn eq(&self, other: &Self) -> bool {
-        self.line == other.line && self.column == other.column && self.index == other.index
+        self.line == other.line
+            && self.column == other.column
+            && self.index == other.index
+            && self.byte_column == other.byte_column
+            && self.utf16_column == other.utf16_column
+            && self.char_index == other.char_index
+            && self.utf16_index == other.utf16_index
+            && self.end_line == other.end_line
+            && self.end_column == other.end_column
+            && self.end_byte_column == other.end_byte_column
+            && self.end_utf16_column == other.end_utf16_column
+            && self.end_index == other.end_index
+            && self.end_char_index == other.end_char_index
+            && self.end_utf16_index == other.end_utf16_index
     }
 }
 
@@ -97,18 +114,35 @@ impl PartialEq for NumberNode {
     }
 }
 
+impl NumberNode {
+    /// Fills `radix` and `normalized` in from the `value`.
+    pub fn new(value: String, context: NodeContext) -> Self {
+        let radix = number::radix(&value) as u8;
+        let normalized = value
+            .parse::<Decimal>()
+            .ok()
+            .map(|decimal| decimal.to_string());
+        Self {
+            value,
+            radix,
+            normalized,
+            context,
+        }
+    }
+
+    number_accessors!();
+}
+
 impl Neg for NumberNode {
     type Output = Self;
 
     fn neg(self) -> Self::Output {
-        Self {
-            value: if self.value.starts_with('-') {
-                self.value[1..].to_owned()
-            } else {
-                format!("-{}", self.value)
-            },
-            context: self.context,
-        }
+        let value = match self.value.strip_prefix(['-', '+']) {
+            Some(unsigned) if self.value.starts_with('-') => unsigned.to_owned(),
+            Some(unsigned) => format!("-{unsigned}"),
+            None => format!("-{}", self.value),
+        };
+        Self::new(value, self.context)
     }
 }
 
@@ -241,42 +275,16 @@ impl PartialEq for ObjectEntryNode {
 
 impl fmt::Display for ParseErrorExpectation {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match *self {
-            Self::EmptyNode => write!(f, "Очікувався вузол `пусто`"),
-            Self::LogicalNode => write!(f, "Очікувався логічний вузол (`так` або `ні`)"),
-            Self::NumberNode => write!(f, "Очікувався числовий вузол"),
-            Self::TextNode => write!(
-                f,
-                "Очікувався текстовий вузол. Явні перенесення рядків не дозволені"
-            ),
-            Self::ListNode => write!(f, "Очікувався список"),
-            Self::DictionaryNode => write!(f, "Очікувався словник"),
-            Self::DictionaryEntryNode => write!(f, "Очікувався запис словника"),
-            Self::DictionaryEntryKey => write!(
-                f,
-                "Очікувався ключ запису словника (ідентифікатор, текст або число)"
-            ),
-            Self::ObjectNode => write!(f, "Очікувався об'єкт"),
-            Self::ObjectEntryNode => write!(f, "Очікувався запис об'єкта"),
-            Self::AstNode => write!(f, "Очікувався вузол формату `Дід`"),
-            Self::Identifier => write!(
-                f,
-                "Очікувався ідентифікатор запису (має починатися з літери або `_`)"
-            ),
-            Self::EqualsSign => write!(f, "Очікувався знак рівності `=`"),
-            Self::LeftParenthesis => write!(f, "Очікувався ліва кругла дужка `(`"),
-            Self::RightParenthesis => write!(f, "Очікувався права кругла дужка `)`"),
-            Self::LeftBracket => write!(f, "Очікувався ліва квадратна дужка `[`"),
-            Self::RightBracket => write!(f, "Очікувався права квадратна дужка `]`"),
-            Self::EntryValue => write!(f, "Очікувалася значення запису"),
-            Self::Eof => write!(f, "Очікувався кінець файлу"),
-        }
+        ErrorKind::from(*self).fmt(f)
     }
 }
 
 impl fmt::Display for ParseError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         writeln!(f, "Вид помилки: {}", self.expectation)?;
+        for expectation in self.alternatives.iter().skip(1) {
+            writeln!(f, "або: {expectation}")?;
+        }
         writeln!(
             f,
             "Рядок: {}, Стовпчик: {}, Індекс: {}",
@@ -291,13 +299,162 @@ impl std::error::Error for ParseError {}
 impl PartialEq for ParseError {
     fn eq(&self, other: &Self) -> bool {
         self.expectation == other.expectation
+            && self.alternatives == other.alternatives
             && self.line == other.line
             && self.column == other.column
             && self.index == other.index
+            && self.context == other.context
             && self.info == other.info
     }
 }
 
+impl ParseError {
+    /// The WAI form of `err` from parsing `input`.
+    pub(crate) fn new(input: &str, err: crate::Error) -> Self {
+        ParseError {
+            expectation: err.kind.into(),
+            alternatives: err.expected.iter().map(|&kind| kind.into()).collect(),
+            line: err.line as u64,
+            column: err.column as u64,
+            index: err.start as u64,
+            context: err.context(input).into(),
+            reason: err.reason.clone().map(Into::into),
+            info: err.to_string(),
+        }
+    }
+
+    /// The native form of the error, which `input` has.
+    pub(crate) fn into_error(self, input: &str) -> crate::Error {
+        let start = usize::try_from(self.index).map_or(input.len(), |index| index.min(input.len()));
+        let end = usize::try_from(self.context.end_index)
+            .map_or(input.len(), |end| end.clamp(start, input.len()));
+        crate::Error {
+            kind: self.expectation.into(),
+            expected: self.alternatives.into_iter().map(Into::into).collect(),
+            start,
+            end,
+            line: self.line as usize,
+            column: self.column as usize,
+            found: input
+                .get(start..end)
+                .filter(|found| !found.is_empty())
+                .map(str::to_owned),
+            reason: self.reason.map(Into::into),
+        }
+    }
+}
+
+impl From<Reason> for ParseErrorReason {
+    fn from(reason: Reason) -> Self {
+        match reason {
+            Reason::Escape(kind) => Self::Escape(kind.into()),
+            Reason::UnterminatedComment => Self::UnterminatedComment,
+            Reason::TooDeep(max) => Self::TooDeep(max as u64),
+            Reason::InputTooLong(max) => Self::InputTooLong(max as u64),
+            Reason::TooManyNodes(max) => Self::TooManyNodes(max as u64),
+            Reason::TextTooLong(max) => Self::TextTooLong(max as u64),
+            Reason::TooManyEntries(max) => Self::TooManyEntries(max as u64),
+            Reason::DuplicateKey { first } => Self::DuplicateKey(ByteRange {
+                start: first.start as u64,
+                end: first.end as u64,
+            }),
+        }
+    }
+}
+
+impl From<ParseErrorReason> for Reason {
+    fn from(reason: ParseErrorReason) -> Self {
+        // Limits past the address space are the same as none.
+        let limit = |max: u64| usize::try_from(max).unwrap_or(usize::MAX);
+        match reason {
+            ParseErrorReason::Escape(kind) => Self::Escape(kind.into()),
+            ParseErrorReason::UnterminatedComment => Self::UnterminatedComment,
+            ParseErrorReason::TooDeep(max) => Self::TooDeep(limit(max)),
+            ParseErrorReason::InputTooLong(max) => Self::InputTooLong(limit(max)),
+            ParseErrorReason::TooManyNodes(max) => Self::TooManyNodes(limit(max)),
+            ParseErrorReason::TextTooLong(max) => Self::TextTooLong(limit(max)),
+            ParseErrorReason::TooManyEntries(max) => Self::TooManyEntries(limit(max)),
+            ParseErrorReason::DuplicateKey(first) => Self::DuplicateKey {
+                first: limit(first.start)..limit(first.end),
+            },
+        }
+    }
+}
+
+impl From<escape::EscapeErrorKind> for EscapeErrorKind {
+    fn from(kind: escape::EscapeErrorKind) -> Self {
+        match kind {
+            escape::EscapeErrorKind::UnknownEscape(c) => Self::UnknownEscape(c),
+            escape::EscapeErrorKind::TrailingBackslash => Self::TrailingBackslash,
+            escape::EscapeErrorKind::InvalidUnicodeEscape => Self::InvalidUnicodeEscape,
+            escape::EscapeErrorKind::UnpairedSurrogate(unit) => Self::UnpairedSurrogate(unit),
+        }
+    }
+}
+
+impl From<EscapeErrorKind> for escape::EscapeErrorKind {
+    fn from(kind: EscapeErrorKind) -> Self {
+        match kind {
+            EscapeErrorKind::UnknownEscape(c) => Self::UnknownEscape(c),
+            EscapeErrorKind::TrailingBackslash => Self::TrailingBackslash,
+            EscapeErrorKind::InvalidUnicodeEscape => Self::InvalidUnicodeEscape,
+            EscapeErrorKind::UnpairedSurrogate(unit) => Self::UnpairedSurrogate(unit),
+        }
+    }
+}
+
+impl From<Language> for diagnostic::Language {
+    fn from(language: Language) -> Self {
+        match language {
+            Language::Ukrainian => Self::Ukrainian,
+            Language::English => Self::English,
+        }
+    }
+}
+
+macro_rules! map_error_kinds {
+    ($($variant:ident),* $(,)?) => {
+        impl From<ErrorKind> for ParseErrorExpectation {
+            fn from(kind: ErrorKind) -> Self {
+                match kind {
+                    $(ErrorKind::$variant => Self::$variant,)*
+                }
+            }
+        }
+
+        impl From<ParseErrorExpectation> for ErrorKind {
+            fn from(expectation: ParseErrorExpectation) -> Self {
+                match expectation {
+                    $(ParseErrorExpectation::$variant => Self::$variant,)*
+                }
+            }
+        }
+    };
+}
+
+map_error_kinds!(
+    EmptyNode,
+    LogicalNode,
+    NumberNode,
+    TextNode,
+    ListNode,
+    DictionaryNode,
+    DictionaryEntryNode,
+    DictionaryEntryKey,
+    ObjectNode,
+    ObjectEntryNode,
+    AstNode,
+    Identifier,
+    EqualsSign,
+    LeftParenthesis,
+    RightParenthesis,
+    LeftBracket,
+    RightBracket,
+    Comma,
+    EntryValue,
+    Eof,
+);
+
 impl<T: Into<AstNode>> From<T> for BoxedAstNode {
     #[inline]
     fn from(value: T) -> Self {
@@ -317,3 +474,168 @@ impl AsRef<AstNode> for Handle<BoxedAstNode> {
         &self.0
     }
 }
+
+impl From<ast::NodeContext> for NodeContext {
+    #[inline]
+    fn from(value: ast::NodeContext) -> Self {
+        Self {
+            line: value.line,
+            column: value.column,
+            byte_column: value.byte_column,
+            utf16_column: value.utf16_column,
+            index: value.index,
+            char_index: value.char_index,
+            utf16_index: value.utf16_index,
+            end_line: value.end_line,
+            end_column: value.end_column,
+            end_byte_column: value.end_byte_column,
+            end_utf16_column: value.end_utf16_column,
+            end_index: value.end_index,
+            end_char_index: value.end_char_index,
+            end_utf16_index: value.end_utf16_index,
+        }
+    }
+}
+
+impl From<NodeContext> for ast::NodeContext {
+    #[inline]
+    fn from(value: NodeContext) -> Self {
+        Self {
+            line: value.line,
+            column: value.column,
+            byte_column: value.byte_column,
+            utf16_column: value.utf16_column,
+            index: value.index,
+            char_index: value.char_index,
+            utf16_index: value.utf16_index,
+            end_line: value.end_line,
+            end_column: value.end_column,
+            end_byte_column: value.end_byte_column,
+            end_utf16_column: value.end_utf16_column,
+            end_index: value.end_index,
+            end_char_index: value.end_char_index,
+            end_utf16_index: value.end_utf16_index,
+        }
+    }
+}
+
+impl From<crate::api::ParseOptions> for ParseOptions {
+    fn from(options: crate::api::ParseOptions) -> Self {
+        // Limits past the address space are the same as none.
+        let limit = |limit: Option<u64>, default: usize| {
+            limit.map_or(default, |limit| {
+                usize::try_from(limit).unwrap_or(usize::MAX)
+            })
+        };
+        let defaults = Self::default();
+        Self {
+            max_depth: limit(options.max_depth, defaults.max_depth),
+            max_input_len: limit(options.max_input_length, defaults.max_input_len),
+            max_nodes: limit(options.max_nodes, defaults.max_nodes),
+            max_text_len: limit(options.max_text_length, defaults.max_text_len),
+            max_entries: limit(options.max_entries, defaults.max_entries),
+            duplicate_keys: match options.duplicate_keys {
+                None | Some(crate::api::DuplicateKeys::Allow) => DuplicateKeys::Allow,
+                Some(crate::api::DuplicateKeys::Reject) => DuplicateKeys::Reject,
+                Some(crate::api::DuplicateKeys::LastWins) => DuplicateKeys::LastWins,
+            },
+        }
+    }
+}
+
+impl From<Warning> for ParseWarning {
+    fn from(warning: Warning) -> Self {
+        match warning {
+            Warning::DuplicateKey { first, key } => Self::DuplicateKey(DuplicateKeyWarning {
+                first: first.into(),
+                key: key.into(),
+            }),
+        }
+    }
+}
+
+/// Placeholders of recovered trees become empty nodes, since the WAI tree has no errors.
+impl TryFrom<ast::Did<'_>> for AstNode {
+    type Error = EscapeError;
+
+    fn try_from(value: ast::Did<'_>) -> Result<Self, Self::Error> {
+        Ok(match value {
+            ast::Did::Empty(node) => Self::Empty(EmptyNode {
+                context: node.context.into(),
+            }),
+            ast::Did::Error(node) => Self::Empty(EmptyNode {
+                context: node.context.into(),
+            }),
+            ast::Did::Logical(node) => Self::Logical(LogicalNode {
+                value: node.value,
+                context: node.context.into(),
+            }),
+            ast::Did::Number(node) => Self::Number(node.into()),
+            ast::Did::Text(node) => Self::Text(node.try_into()?),
+            ast::Did::Dictionary(node) => Self::Dictionary(DictionaryNode {
+                entries: node
+                    .entries
+                    .into_iter()
+                    .map(|entry| {
+                        Ok(DictionaryEntryNode {
+                            key: match entry.key {
+                                ast::DictionaryEntryKey::Number(key) => {
+                                    DictionaryEntryKey::Number(key.into())
+                                }
+                                ast::DictionaryEntryKey::Text(key) => {
+                                    DictionaryEntryKey::Text(key.try_into()?)
+                                }
+                            },
+                            value: AstNode::try_from(entry.value)?.into(),
+                            context: entry.context.into(),
+                        })
+                    })
+                    .collect::<Result<_, _>>()?,
+                context: node.context.into(),
+            }),
+            ast::Did::Object(node) => Self::Object(ObjectNode {
+                name: node.name.try_into()?,
+                entries: node
+                    .entries
+                    .into_iter()
+                    .map(|entry| {
+                        Ok(ObjectEntryNode {
+                            key: entry.key.try_into()?,
+                            value: AstNode::try_from(entry.value)?.into(),
+                            context: entry.context.into(),
+                        })
+                    })
+                    .collect::<Result<_, _>>()?,
+                context: node.context.into(),
+            }),
+            ast::Did::List(node) => Self::List(ListNode {
+                entries: node
+                    .entries
+                    .into_iter()
+                    .map(|entry| AstNode::try_from(entry).map(Into::into))
+                    .collect::<Result<_, _>>()?,
+                context: node.context.into(),
+            }),
+        })
+    }
+}
+
+impl From<ast::NumberNode<'_>> for NumberNode {
+    #[inline]
+    fn from(value: ast::NumberNode<'_>) -> Self {
+        Self::new(value.value.into_owned(), value.context.into())
+    }
+}
+
+impl TryFrom<ast::TextNode<'_>> for TextNode {
+    type Error = EscapeError;
+
+    #[inline]
+    fn try_from(value: ast::TextNode<'_>) -> Result<Self, Self::Error> {
+        let context = value.context.into();
+        Ok(Self {
+            value: value.into_unescaped()?.into_owned(),
+            context,
+        })
+    }
+}
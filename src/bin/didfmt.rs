@@ -0,0 +1,131 @@
+//! Formatter of `Дід` files.
+//!
+//! Without files, reads the standard input and writes the result to the standard output.
+//! Otherwise rewrites every file in place.
+
+use std::{
+    env, fs,
+    io::{self, Read},
+    process::ExitCode,
+};
+
+use mavka_did::{
+    diagnostic::{self, Language},
+    writer::{self, FormatError, Style, TrailingComma},
+};
+
+const USAGE: &str = "\
+Використання: didfmt [ОПЦІЇ] [ФАЙЛИ...]
+
+Опції:
+  --indent <N>              ширина відступу (типово 2)
+  --hard-tabs               відступи табуляціями
+  --tab-width <N>           ширина табуляції при перевірці ширини рядка (типово 4)
+  --max-width <N>           максимальна ширина рядка (типово 80)
+  --trailing-comma <ВИД>    always, never або vertical (типово never)
+  --spaces-around-equals    пробіли навколо `=`
+  --check                   лише перевірити, чи файли відформатовані
+  -h, --help                показати цю довідку";
+
+fn main() -> ExitCode {
+    let mut style = Style::default();
+    let mut check = false;
+    let mut files = Vec::new();
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--indent" => match number(args.next()) {
+                Some(n) => style.indent_width = n,
+                None => return usage_error("--indent очікує невід'ємне ціле число"),
+            },
+            "--hard-tabs" => style.hard_tabs = true,
+            "--tab-width" => match number(args.next()) {
+                Some(n) => style.tab_width = n,
+                None => return usage_error("--tab-width очікує невід'ємне ціле число"),
+            },
+            "--max-width" => match number(args.next()) {
+                Some(n) => style.max_width = n,
+                None => return usage_error("--max-width очікує невід'ємне ціле число"),
+            },
+            "--trailing-comma" => {
+                style.trailing_comma = match args.next().as_deref() {
+                    Some("always") => TrailingComma::Always,
+                    Some("never") => TrailingComma::Never,
+                    Some("vertical") => TrailingComma::Vertical,
+                    _ => return usage_error("--trailing-comma очікує always, never або vertical"),
+                }
+            }
+            "--spaces-around-equals" => style.spaces_around_equals = true,
+            "--check" => check = true,
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                return ExitCode::SUCCESS;
+            }
+            arg if arg.starts_with('-') => {
+                return usage_error(&format!("невідома опція `{arg}`"));
+            }
+            _ => files.push(arg),
+        }
+    }
+
+    if files.is_empty() {
+        let mut input = String::new();
+        if let Err(err) = io::stdin().read_to_string(&mut input) {
+            eprintln!("<stdin>: {err}");
+            return ExitCode::FAILURE;
+        }
+        return match writer::format(&input, &style) {
+            Ok(output) if check && output != input => {
+                eprintln!("<stdin>: не відформатовано");
+                ExitCode::FAILURE
+            }
+            Ok(_) if check => ExitCode::SUCCESS,
+            Ok(output) => {
+                print!("{output}");
+                ExitCode::SUCCESS
+            }
+            Err(err) => {
+                eprint!("<stdin>: {}", describe(&input, &err));
+                ExitCode::FAILURE
+            }
+        };
+    }
+
+    let mut status = ExitCode::SUCCESS;
+    for file in files {
+        let result = fs::read_to_string(&file)
+            .map_err(|err| format!("{err}\n"))
+            .and_then(|input| {
+                let output =
+                    writer::format(&input, &style).map_err(|err| describe(&input, &err))?;
+                if output == input {
+                    Ok(())
+                } else if check {
+                    Err("не відформатовано\n".to_owned())
+                } else {
+                    fs::write(&file, output).map_err(|err| format!("{err}\n"))
+                }
+            });
+        if let Err(err) = result {
+            eprint!("{file}: {err}");
+            status = ExitCode::FAILURE;
+        }
+    }
+    status
+}
+
+/// The parse error rendered with the source, with a line break at the end.
+fn describe(input: &str, err: &FormatError) -> String {
+    let FormatError::Parse(err) = err;
+    diagnostic::render(input, err, Language::Ukrainian)
+}
+
+fn number(arg: Option<String>) -> Option<usize> {
+    arg?.parse().ok()
+}
+
+fn usage_error(message: &str) -> ExitCode {
+    eprintln!("{message}\n\n{USAGE}");
+    ExitCode::from(2)
+}
@@ -0,0 +1,488 @@
+//! Conversion between Rust types and `Дід` nodes without serde.
+//!
+//! [`FromDid`] and [`ToDid`] can be derived for structs with named fields (the `derive`
+//! feature). A struct corresponds to an object with the same name:
+//!
+//! ```ignore
+//! #[derive(FromDid, ToDid)]
+//! #[did(name = "Людина")]
+//! struct Person {
+//!     #[did(rename = "імʼя")]
+//!     name: String,
+//!     #[did(default)]
+//!     вік: u32,
+//!     #[did(flatten)]
+//!     extra: Extra,
+//! }
+//! ```
+
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashMap},
+    fmt,
+};
+
+use crate::{
+    parser::{
+        ast::{
+            DictionaryEntryKey, DictionaryEntryNode, DictionaryNode, Did, EmptyNode, ListNode,
+            LogicalNode, NodeContext, NumberNode, ObjectEntryNode, ObjectNode, TextNode,
+        },
+        escape::{EscapeError, EscapeErrorKind},
+        number::Decimal,
+    },
+    writer,
+};
+
+/// A type that can be read from a `Дід` node.
+pub trait FromDid: Sized {
+    fn from_did(did: &Did<'_>) -> Result<Self, Error>;
+
+    /// Value to use when an object entry of this type is missing.
+    ///
+    /// Only `Option` has one: missing entries become `None`.
+    fn missing() -> Option<Self> {
+        None
+    }
+}
+
+/// A type that can be written as a `Дід` node.
+pub trait ToDid {
+    fn to_did(&self) -> Did<'_>;
+}
+
+/// A type that is read from the entries of an object.
+///
+/// Derived together with [`FromDid`]; needed for `#[did(flatten)]` fields.
+pub trait FromDidEntries: Sized {
+    fn from_entries(entries: &[ObjectEntryNode<'_>], context: NodeContext) -> Result<Self, Error>;
+}
+
+/// A type that is written as the entries of an object.
+///
+/// Derived together with [`ToDid`]; needed for `#[did(flatten)]` fields.
+pub trait ToDidEntries {
+    fn to_entries<'a>(&'a self, entries: &mut Vec<ObjectEntryNode<'a>>);
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Error {
+    pub kind: ErrorKind,
+    /// Position of the node that caused the error, boxed to keep results small.
+    pub context: Box<NodeContext>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// The node has a different type.
+    InvalidType { expected: &'static str },
+    /// The node has the right type, but its value does not fit.
+    InvalidValue { expected: &'static str },
+    /// The object has a different name.
+    InvalidName {
+        expected: &'static str,
+        found: String,
+    },
+    /// The object has no entry with this key.
+    MissingEntry { key: &'static str },
+    /// The text has a bad escape sequence.
+    InvalidEscape(EscapeErrorKind),
+}
+
+impl Error {
+    fn new(kind: ErrorKind, context: NodeContext) -> Self {
+        Self {
+            kind,
+            context: Box::new(context),
+        }
+    }
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidType { expected } => write!(f, "Очікувався {expected}"),
+            Self::InvalidValue { expected } => write!(f, "Очікувалося значення типу {expected}"),
+            Self::InvalidName { expected, found } => {
+                write!(f, "Очікувався об'єкт `{expected}`, знайдено `{found}`")
+            }
+            Self::MissingEntry { key } => write!(f, "Відсутній запис `{key}`"),
+            Self::InvalidEscape(kind) => kind.fmt(f),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} (рядок: {}, стовпчик: {})",
+            self.kind, self.context.line, self.context.column
+        )
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<EscapeError> for Error {
+    fn from(err: EscapeError) -> Self {
+        Self::new(ErrorKind::InvalidEscape(err.kind), err.context)
+    }
+}
+
+fn invalid_type(did: &Did<'_>, expected: &'static str) -> Error {
+    Error::new(ErrorKind::InvalidType { expected }, did.context())
+}
+
+/// Checks that `did` is an object named `name`.
+#[doc(hidden)]
+pub fn expect_object<'a, 'inp>(
+    did: &'a Did<'inp>,
+    name: &'static str,
+) -> Result<&'a ObjectNode<'inp>, Error> {
+    match did {
+        Did::Object(object) if object.name.value == name => Ok(object),
+        Did::Object(object) => Err(Error::new(
+            ErrorKind::InvalidName {
+                expected: name,
+                found: object.name.value.clone().into_owned(),
+            },
+            object.name.context,
+        )),
+        _ => Err(invalid_type(did, "об'єкт")),
+    }
+}
+
+/// Reads the entry `key`, falling back to [`FromDid::missing`].
+#[doc(hidden)]
+pub fn entry<T: FromDid>(
+    entries: &[ObjectEntryNode<'_>],
+    key: &'static str,
+    context: NodeContext,
+) -> Result<T, Error> {
+    match find_entry(entries, key) {
+        Some(value) => T::from_did(value),
+        None => T::missing().ok_or(Error::new(ErrorKind::MissingEntry { key }, context)),
+    }
+}
+
+/// Reads the entry `key`, falling back to `default`.
+#[doc(hidden)]
+pub fn entry_or_else<T: FromDid>(
+    entries: &[ObjectEntryNode<'_>],
+    key: &'static str,
+    default: impl FnOnce() -> T,
+) -> Result<T, Error> {
+    find_entry(entries, key).map_or_else(|| Ok(default()), T::from_did)
+}
+
+fn find_entry<'a, 'inp>(entries: &'a [ObjectEntryNode<'inp>], key: &str) -> Option<&'a Did<'inp>> {
+    entries
+        .iter()
+        .rev()
+        .find(|entry| entry.key.value == key)
+        .map(|entry| &entry.value)
+}
+
+#[doc(hidden)]
+pub fn object<'a>(name: &'static str, entries: Vec<ObjectEntryNode<'a>>) -> Did<'a> {
+    Did::Object(ObjectNode {
+        name: TextNode {
+            value: name.into(),
+            context: NodeContext::default(),
+        },
+        entries,
+        context: NodeContext::default(),
+    })
+}
+
+#[doc(hidden)]
+pub fn object_entry<'a>(key: &'static str, value: Did<'a>) -> ObjectEntryNode<'a> {
+    ObjectEntryNode {
+        key: TextNode {
+            value: key.into(),
+            context: NodeContext::default(),
+        },
+        value,
+        context: NodeContext::default(),
+    }
+}
+
+fn empty<'a>() -> Did<'a> {
+    Did::Empty(EmptyNode {
+        context: NodeContext::default(),
+    })
+}
+
+fn number<'a>(value: impl ToString) -> Did<'a> {
+    Did::Number(NumberNode {
+        value: Cow::Owned(value.to_string()),
+        context: NodeContext::default(),
+    })
+}
+
+fn text(value: &str) -> TextNode<'_> {
+    TextNode {
+        value: writer::escape(value),
+        context: NodeContext::default(),
+    }
+}
+
+impl FromDid for () {
+    fn from_did(did: &Did<'_>) -> Result<Self, Error> {
+        match did {
+            Did::Empty(_) => Ok(()),
+            _ => Err(invalid_type(did, "вузол `пусто`")),
+        }
+    }
+}
+
+impl ToDid for () {
+    fn to_did(&self) -> Did<'_> {
+        empty()
+    }
+}
+
+impl FromDid for bool {
+    fn from_did(did: &Did<'_>) -> Result<Self, Error> {
+        match did {
+            Did::Logical(node) => Ok(node.value),
+            _ => Err(invalid_type(did, "логічний вузол")),
+        }
+    }
+}
+
+impl ToDid for bool {
+    fn to_did(&self) -> Did<'_> {
+        Did::Logical(LogicalNode {
+            value: *self,
+            context: NodeContext::default(),
+        })
+    }
+}
+
+macro_rules! number_impls {
+    ($($ty:ty),*) => {
+        $(
+            impl FromDid for $ty {
+                fn from_did(did: &Did<'_>) -> Result<Self, Error> {
+                    match did {
+                        Did::Number(node) => node.parse().map_err(|err| {
+                            Error::new(
+                                ErrorKind::InvalidValue { expected: stringify!($ty) },
+                                err.context,
+                            )
+                        }),
+                        _ => Err(invalid_type(did, "числовий вузол")),
+                    }
+                }
+            }
+
+            impl ToDid for $ty {
+                fn to_did(&self) -> Did<'_> {
+                    number(self)
+                }
+            }
+        )*
+    };
+}
+
+number_impls!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
+macro_rules! float_impls {
+    ($($ty:ty),*) => {
+        $(
+            impl FromDid for $ty {
+                fn from_did(did: &Did<'_>) -> Result<Self, Error> {
+                    match did {
+                        Did::Number(node) => node.parse().map_err(|err| {
+                            Error::new(
+                                ErrorKind::InvalidValue { expected: stringify!($ty) },
+                                err.context,
+                            )
+                        }),
+                        _ => Err(invalid_type(did, "числовий вузол")),
+                    }
+                }
+            }
+
+            /// Infinities and `NaN` have no `Дід` representation and are written as `пусто`.
+            impl ToDid for $ty {
+                fn to_did(&self) -> Did<'_> {
+                    if self.is_finite() {
+                        number(self)
+                    } else {
+                        empty()
+                    }
+                }
+            }
+        )*
+    };
+}
+
+float_impls!(f32, f64);
+
+impl FromDid for Decimal {
+    fn from_did(did: &Did<'_>) -> Result<Self, Error> {
+        match did {
+            Did::Number(node) => node.as_decimal().map_err(|err| {
+                Error::new(
+                    ErrorKind::InvalidValue {
+                        expected: "Decimal",
+                    },
+                    err.context,
+                )
+            }),
+            _ => Err(invalid_type(did, "числовий вузол")),
+        }
+    }
+}
+
+impl ToDid for Decimal {
+    fn to_did(&self) -> Did<'_> {
+        number(self)
+    }
+}
+
+impl FromDid for String {
+    fn from_did(did: &Did<'_>) -> Result<Self, Error> {
+        match did {
+            Did::Text(node) => Ok(node.unescape()?.into_owned()),
+            _ => Err(invalid_type(did, "текстовий вузол")),
+        }
+    }
+}
+
+impl ToDid for String {
+    fn to_did(&self) -> Did<'_> {
+        Did::Text(text(self))
+    }
+}
+
+impl ToDid for str {
+    fn to_did(&self) -> Did<'_> {
+        Did::Text(text(self))
+    }
+}
+
+impl<T: FromDid> FromDid for Option<T> {
+    fn from_did(did: &Did<'_>) -> Result<Self, Error> {
+        match did {
+            Did::Empty(_) => Ok(None),
+            _ => T::from_did(did).map(Some),
+        }
+    }
+
+    fn missing() -> Option<Self> {
+        Some(None)
+    }
+}
+
+impl<T: ToDid> ToDid for Option<T> {
+    fn to_did(&self) -> Did<'_> {
+        match self {
+            Some(value) => value.to_did(),
+            None => empty(),
+        }
+    }
+}
+
+impl<T: FromDid> FromDid for Box<T> {
+    fn from_did(did: &Did<'_>) -> Result<Self, Error> {
+        T::from_did(did).map(Box::new)
+    }
+
+    fn missing() -> Option<Self> {
+        T::missing().map(Box::new)
+    }
+}
+
+impl<T: ToDid + ?Sized> ToDid for Box<T> {
+    fn to_did(&self) -> Did<'_> {
+        (**self).to_did()
+    }
+}
+
+impl<T: FromDid> FromDid for Vec<T> {
+    fn from_did(did: &Did<'_>) -> Result<Self, Error> {
+        match did {
+            Did::List(node) => node.entries.iter().map(T::from_did).collect(),
+            _ => Err(invalid_type(did, "список")),
+        }
+    }
+}
+
+impl<T: ToDid> ToDid for Vec<T> {
+    fn to_did(&self) -> Did<'_> {
+        self.as_slice().to_did()
+    }
+}
+
+impl<T: ToDid> ToDid for [T] {
+    fn to_did(&self) -> Did<'_> {
+        Did::List(ListNode {
+            entries: self.iter().map(T::to_did).collect(),
+            context: NodeContext::default(),
+        })
+    }
+}
+
+/// Entries of a dictionary or of an object, with their keys decoded.
+type MapEntries<'a> =
+    Box<dyn Iterator<Item = (Result<Cow<'a, str>, EscapeError>, &'a Did<'a>)> + 'a>;
+
+/// Reads the entries of a dictionary or of an object as text keys and values.
+fn map_entries<'a, T: FromDid>(
+    did: &'a Did<'_>,
+) -> Result<impl Iterator<Item = Result<(String, T), Error>> + 'a, Error> {
+    let entries: MapEntries<'a> = match did {
+        Did::Dictionary(node) => Box::new(node.entries.iter().map(|entry| match &entry.key {
+            DictionaryEntryKey::Number(key) => (Ok(Cow::Borrowed(&*key.value)), &entry.value),
+            DictionaryEntryKey::Text(key) => (key.unescape(), &entry.value),
+        })),
+        Did::Object(node) => Box::new(
+            node.entries
+                .iter()
+                .map(|entry| (entry.key.unescape(), &entry.value)),
+        ),
+        _ => return Err(invalid_type(did, "словник")),
+    };
+    Ok(entries.map(|(key, value)| Ok((key?.into_owned(), T::from_did(value)?))))
+}
+
+fn dictionary<'a>(entries: impl Iterator<Item = (&'a String, Did<'a>)>) -> Did<'a> {
+    Did::Dictionary(DictionaryNode {
+        entries: entries
+            .map(|(key, value)| DictionaryEntryNode {
+                key: DictionaryEntryKey::Text(text(key)),
+                value,
+                context: NodeContext::default(),
+            })
+            .collect(),
+        context: NodeContext::default(),
+    })
+}
+
+impl<T: FromDid> FromDid for BTreeMap<String, T> {
+    fn from_did(did: &Did<'_>) -> Result<Self, Error> {
+        map_entries(did)?.collect()
+    }
+}
+
+impl<T: ToDid> ToDid for BTreeMap<String, T> {
+    fn to_did(&self) -> Did<'_> {
+        dictionary(self.iter().map(|(key, value)| (key, value.to_did())))
+    }
+}
+
+impl<T: FromDid, S: std::hash::BuildHasher + Default> FromDid for HashMap<String, T, S> {
+    fn from_did(did: &Did<'_>) -> Result<Self, Error> {
+        map_entries(did)?.collect()
+    }
+}
+
+impl<T: ToDid, S> ToDid for HashMap<String, T, S> {
+    fn to_did(&self) -> Did<'_> {
+        dictionary(self.iter().map(|(key, value)| (key, value.to_did())))
+    }
+}
@@ -0,0 +1,674 @@
+//! Deserializing `Дід` into types that implement [`serde::Deserialize`].
+
+use std::{borrow::Cow, fmt, slice};
+
+use serde::{
+    de::{
+        self, DeserializeSeed, EnumAccess, IntoDeserializer, MapAccess, SeqAccess, VariantAccess,
+        Visitor,
+    },
+    forward_to_deserialize_any, Deserialize,
+};
+
+use crate::parser::{
+    self,
+    ast::{
+        DictionaryEntryKey, DictionaryEntryNode, Did, NodeContext, ObjectEntryNode, ObjectNode,
+        TextNode,
+    },
+    escape::{self, EscapeError},
+    number::FromNumber,
+};
+
+/// Key of the single entry of an object that holds the content of a newtype or tuple variant.
+pub(crate) const VARIANT_VALUE_KEY: &str = "значення";
+
+/// Parses `input` and deserializes the document into `T`.
+pub fn from_str<'de, T: Deserialize<'de>>(input: &'de str) -> Result<T, Error> {
+    let did = parser::parse_did(input).map_err(Error::Parse)?;
+    from_did(&did)
+}
+
+/// Deserializes an already parsed document into `T`.
+pub fn from_did<'de, T: Deserialize<'de>>(did: &Did<'de>) -> Result<T, Error> {
+    T::deserialize(Deserializer(did))
+}
+
+#[derive(Debug)]
+pub enum Error {
+    Parse(crate::Error),
+    Message {
+        message: String,
+        /// Position of the node that caused the error, boxed to keep results small.
+        context: Option<Box<NodeContext>>,
+    },
+}
+
+impl Error {
+    fn at(self, context: NodeContext) -> Self {
+        match self {
+            Self::Message {
+                message,
+                context: None,
+            } => Self::Message {
+                message,
+                context: Some(Box::new(context)),
+            },
+            err => err,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Parse(err) => err.fmt(f),
+            Self::Message {
+                message,
+                context: Some(context),
+            } => write!(
+                f,
+                "{message} (рядок: {}, стовпчик: {})",
+                context.line, context.column
+            ),
+            Self::Message {
+                message,
+                context: None,
+            } => f.write_str(message),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Parse(err) => Some(err),
+            Self::Message { .. } => None,
+        }
+    }
+}
+
+impl From<EscapeError> for Error {
+    fn from(err: EscapeError) -> Self {
+        Self::Message {
+            message: err.kind.to_string(),
+            context: Some(Box::new(err.context)),
+        }
+    }
+}
+
+impl de::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Self::Message {
+            message: msg.to_string(),
+            context: None,
+        }
+    }
+}
+
+/// Deserializer that walks a parsed [`Did`] tree.
+#[derive(Clone, Copy)]
+pub struct Deserializer<'a, 'de>(pub &'a Did<'de>);
+
+impl<'a, 'de> Deserializer<'a, 'de> {
+    fn invalid_type(self, exp: &dyn de::Expected) -> Error {
+        let unexpected = match self.0 {
+            Did::Empty(_) => de::Unexpected::Unit,
+            Did::Logical(node) => de::Unexpected::Bool(node.value),
+            Did::Number(node) => de::Unexpected::Other(&node.value),
+            Did::Text(node) => de::Unexpected::Str(&node.value),
+            Did::Dictionary(_) => de::Unexpected::Map,
+            Did::Object(_) => de::Unexpected::StructVariant,
+            Did::List(_) => de::Unexpected::Seq,
+            Did::Error(_) => de::Unexpected::Other("помилковий вузол"),
+        };
+        de::Error::invalid_type(unexpected, exp)
+    }
+
+    fn number<T: FromNumber>(self, exp: &dyn de::Expected) -> Result<T, Error> {
+        match self.0 {
+            Did::Number(node) => parse_number(&node.value, exp),
+            _ => Err(self.invalid_type(exp)),
+        }
+    }
+}
+
+macro_rules! deserialize_number {
+    ($($method:ident => $visit:ident: $ty:ty,)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+                let value: $ty = self.number(&visitor).map_err(|err| err.at(self.0.context()))?;
+                visitor.$visit(value)
+            }
+        )*
+    };
+}
+
+impl<'a, 'de> de::Deserializer<'de> for Deserializer<'a, 'de> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let result = match self.0 {
+            Did::Empty(_) => visitor.visit_unit(),
+            Did::Logical(node) => visitor.visit_bool(node.value),
+            Did::Number(node) => visit_number(&node.value, visitor),
+            Did::Text(node) => visit_str(unescape(&node.value, node.context)?, visitor),
+            Did::Dictionary(node) => visitor.visit_map(DictionaryAccess::new(&node.entries)),
+            Did::Object(node) => visitor.visit_map(ObjectAccess::new(&node.entries)),
+            Did::List(node) => visitor.visit_seq(ListAccess(node.entries.iter())),
+            Did::Error(_) => Err(self.invalid_type(&visitor)),
+        };
+        result.map_err(|err| err.at(self.0.context()))
+    }
+
+    deserialize_number! {
+        deserialize_i8 => visit_i8: i8,
+        deserialize_i16 => visit_i16: i16,
+        deserialize_i32 => visit_i32: i32,
+        deserialize_i64 => visit_i64: i64,
+        deserialize_i128 => visit_i128: i128,
+        deserialize_u8 => visit_u8: u8,
+        deserialize_u16 => visit_u16: u16,
+        deserialize_u32 => visit_u32: u32,
+        deserialize_u64 => visit_u64: u64,
+        deserialize_u128 => visit_u128: u128,
+        deserialize_f32 => visit_f32: f32,
+        deserialize_f64 => visit_f64: f64,
+    }
+
+    fn deserialize_char<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.0 {
+            Did::Text(node) => {
+                let value = unescape(&node.value, node.context)?;
+                let mut chars = value.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => visitor.visit_char(c),
+                    _ => visit_str(value, visitor),
+                }
+            }
+            _ => Err(self.invalid_type(&visitor)),
+        }
+        .map_err(|err| err.at(self.0.context()))
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.0 {
+            Did::Empty(_) => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.0 {
+            Did::Empty(_) => visitor.visit_unit(),
+            _ => Err(self.invalid_type(&visitor).at(self.0.context())),
+        }
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        match self.0 {
+            Did::Object(node) if node.entries.is_empty() => visitor.visit_unit(),
+            _ => self.deserialize_unit(visitor),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.0 {
+            Did::List(node) => visitor.visit_seq(ListAccess(node.entries.iter())),
+            _ => Err(self.invalid_type(&visitor)),
+        }
+        .map_err(|err| err.at(self.0.context()))
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.0 {
+            Did::Dictionary(node) => visitor.visit_map(DictionaryAccess::new(&node.entries)),
+            Did::Object(node) => visitor.visit_map(ObjectAccess::new(&node.entries)),
+            _ => Err(self.invalid_type(&visitor)),
+        }
+        .map_err(|err| err.at(self.0.context()))
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.deserialize_map(visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        match self.0 {
+            Did::Text(node) => {
+                let value = unescape(&node.value, node.context)?;
+                visitor.visit_enum(value.as_ref().into_deserializer())
+            }
+            Did::Object(node) => visitor.visit_enum(ObjectVariant(node)),
+            Did::Dictionary(node) if node.entries.len() == 1 => {
+                visitor.visit_enum(DictionaryVariant(&node.entries[0]))
+            }
+            _ => Err(self.invalid_type(&visitor)),
+        }
+        .map_err(|err| err.at(self.0.context()))
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+
+    forward_to_deserialize_any! {
+        bool str string bytes byte_buf identifier
+    }
+}
+
+/// Decodes the escape sequences of a text, keeping the borrow of the input when possible.
+fn unescape<'de>(value: &Cow<'de, str>, context: NodeContext) -> Result<Cow<'de, str>, Error> {
+    let unescaped = match value {
+        Cow::Borrowed(raw) => escape::unescape(raw, context)?,
+        Cow::Owned(raw) => Cow::Owned(escape::unescape(raw, context)?.into_owned()),
+    };
+    Ok(unescaped)
+}
+
+fn visit_str<'de, V: Visitor<'de>>(value: Cow<'de, str>, visitor: V) -> Result<V::Value, Error> {
+    match value {
+        Cow::Borrowed(value) => visitor.visit_borrowed_str(value),
+        Cow::Owned(value) => visitor.visit_string(value),
+    }
+}
+
+fn visit_number<'de, V: Visitor<'de>>(value: &str, visitor: V) -> Result<V::Value, Error> {
+    if let Ok(value) = i64::from_number(value) {
+        visitor.visit_i64(value)
+    } else if let Ok(value) = u64::from_number(value) {
+        visitor.visit_u64(value)
+    } else if let Ok(value) = i128::from_number(value) {
+        visitor.visit_i128(value)
+    } else {
+        let value = parse_number(value, &visitor)?;
+        visitor.visit_f64(value)
+    }
+}
+
+fn parse_number<T: FromNumber>(value: &str, exp: &dyn de::Expected) -> Result<T, Error> {
+    T::from_number(value).map_err(|_| de::Error::invalid_value(de::Unexpected::Other(value), exp))
+}
+
+struct ListAccess<'a, 'de>(slice::Iter<'a, Did<'de>>);
+
+impl<'a, 'de> SeqAccess<'de> for ListAccess<'a, 'de> {
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Error> {
+        self.0
+            .next()
+            .map(|did| seed.deserialize(Deserializer(did)))
+            .transpose()
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.0.len())
+    }
+}
+
+struct DictionaryAccess<'a, 'de> {
+    entries: slice::Iter<'a, DictionaryEntryNode<'de>>,
+    value: Option<&'a Did<'de>>,
+}
+
+impl<'a, 'de> DictionaryAccess<'a, 'de> {
+    fn new(entries: &'a [DictionaryEntryNode<'de>]) -> Self {
+        Self {
+            entries: entries.iter(),
+            value: None,
+        }
+    }
+}
+
+impl<'a, 'de> MapAccess<'de> for DictionaryAccess<'a, 'de> {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Error> {
+        let Some(entry) = self.entries.next() else {
+            return Ok(None);
+        };
+        self.value = Some(&entry.value);
+        seed.deserialize(KeyDeserializer::from(&entry.key))
+            .map(Some)
+            .map_err(|err| err.at(entry.context))
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+        let value = self
+            .value
+            .take()
+            .expect("next_value_seed called before next_key_seed");
+        seed.deserialize(Deserializer(value))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.entries.len())
+    }
+}
+
+struct ObjectAccess<'a, 'de> {
+    entries: slice::Iter<'a, ObjectEntryNode<'de>>,
+    value: Option<&'a Did<'de>>,
+}
+
+impl<'a, 'de> ObjectAccess<'a, 'de> {
+    fn new(entries: &'a [ObjectEntryNode<'de>]) -> Self {
+        Self {
+            entries: entries.iter(),
+            value: None,
+        }
+    }
+}
+
+impl<'a, 'de> MapAccess<'de> for ObjectAccess<'a, 'de> {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Error> {
+        let Some(entry) = self.entries.next() else {
+            return Ok(None);
+        };
+        self.value = Some(&entry.value);
+        seed.deserialize(KeyDeserializer::from(&entry.key))
+            .map(Some)
+            .map_err(|err| err.at(entry.context))
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+        let value = self
+            .value
+            .take()
+            .expect("next_value_seed called before next_key_seed");
+        seed.deserialize(Deserializer(value))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.entries.len())
+    }
+}
+
+/// Deserializer of dictionary and object keys.
+///
+/// Both number and text keys can be read as strings or as numbers.
+struct KeyDeserializer<'a, 'de> {
+    value: &'a Cow<'de, str>,
+    is_number: bool,
+    context: NodeContext,
+}
+
+impl<'a, 'de> KeyDeserializer<'a, 'de> {
+    fn text(&self) -> Result<Cow<'de, str>, Error> {
+        if self.is_number {
+            Ok(self.value.clone())
+        } else {
+            unescape(self.value, self.context)
+        }
+    }
+}
+
+impl<'a, 'de> From<&'a DictionaryEntryKey<'de>> for KeyDeserializer<'a, 'de> {
+    fn from(key: &'a DictionaryEntryKey<'de>) -> Self {
+        match key {
+            DictionaryEntryKey::Number(node) => Self {
+                value: &node.value,
+                is_number: true,
+                context: node.context,
+            },
+            DictionaryEntryKey::Text(node) => node.into(),
+        }
+    }
+}
+
+impl<'a, 'de> From<&'a TextNode<'de>> for KeyDeserializer<'a, 'de> {
+    fn from(node: &'a TextNode<'de>) -> Self {
+        Self {
+            value: &node.value,
+            is_number: false,
+            context: node.context,
+        }
+    }
+}
+
+macro_rules! deserialize_key_number {
+    ($($method:ident => $visit:ident: $ty:ty,)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+                let value: $ty = parse_number(self.value, &visitor)?;
+                visitor.$visit(value)
+            }
+        )*
+    };
+}
+
+impl<'a, 'de> de::Deserializer<'de> for KeyDeserializer<'a, 'de> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        if self.is_number {
+            visit_number(self.value, visitor)
+        } else {
+            visit_str(self.text()?, visitor)
+        }
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visit_str(self.text()?, visitor)
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_identifier<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_enum(self.text()?.as_ref().into_deserializer())
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    deserialize_key_number! {
+        deserialize_i8 => visit_i8: i8,
+        deserialize_i16 => visit_i16: i16,
+        deserialize_i32 => visit_i32: i32,
+        deserialize_i64 => visit_i64: i64,
+        deserialize_i128 => visit_i128: i128,
+        deserialize_u8 => visit_u8: u8,
+        deserialize_u16 => visit_u16: u16,
+        deserialize_u32 => visit_u32: u32,
+        deserialize_u64 => visit_u64: u64,
+        deserialize_u128 => visit_u128: u128,
+        deserialize_f32 => visit_f32: f32,
+        deserialize_f64 => visit_f64: f64,
+    }
+
+    forward_to_deserialize_any! {
+        bool char bytes byte_buf option unit unit_struct seq tuple
+        tuple_struct map struct ignored_any
+    }
+}
+
+/// Enum variant written as an object named after the variant: `Коло(радіус=1)`.
+struct ObjectVariant<'a, 'de>(&'a ObjectNode<'de>);
+
+impl<'a, 'de> EnumAccess<'de> for ObjectVariant<'a, 'de> {
+    type Error = Error;
+    type Variant = Self;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Self), Error> {
+        let variant = seed.deserialize(KeyDeserializer::from(&self.0.name))?;
+        Ok((variant, self))
+    }
+}
+
+impl<'a, 'de> ObjectVariant<'a, 'de> {
+    /// The value of the only `значення` entry, if the object has exactly that.
+    fn content(&self) -> Option<&'a Did<'de>> {
+        match self.0.entries.as_slice() {
+            [entry] if entry.key.value == VARIANT_VALUE_KEY => Some(&entry.value),
+            _ => None,
+        }
+    }
+}
+
+impl<'a, 'de> VariantAccess<'de> for ObjectVariant<'a, 'de> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Error> {
+        match self.0.entries.first() {
+            None => Ok(()),
+            Some(entry) => {
+                let err: Error =
+                    de::Error::invalid_length(self.0.entries.len(), &"об'єкт без записів");
+                Err(err.at(entry.context))
+            }
+        }
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, Error> {
+        match self.content() {
+            Some(value) => seed.deserialize(Deserializer(value)),
+            None => seed.deserialize(ObjectContent(self.0)),
+        }
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, Error> {
+        match self.content() {
+            Some(value) => de::Deserializer::deserialize_seq(Deserializer(value), visitor),
+            None => Err(de::Error::invalid_type(
+                de::Unexpected::StructVariant,
+                &visitor,
+            )),
+        }
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_map(ObjectAccess::new(&self.0.entries))
+    }
+}
+
+/// Entries of an object, deserialized regardless of its name.
+struct ObjectContent<'a, 'de>(&'a ObjectNode<'de>);
+
+impl<'a, 'de> de::Deserializer<'de> for ObjectContent<'a, 'de> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor
+            .visit_map(ObjectAccess::new(&self.0.entries))
+            .map_err(|err| err.at(self.0.context))
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+/// Enum variant written as a dictionary with a single entry: `(Коло=1)`.
+struct DictionaryVariant<'a, 'de>(&'a DictionaryEntryNode<'de>);
+
+impl<'a, 'de> EnumAccess<'de> for DictionaryVariant<'a, 'de> {
+    type Error = Error;
+    type Variant = Deserializer<'a, 'de>;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, Deserializer<'a, 'de>), Error> {
+        let variant = seed
+            .deserialize(KeyDeserializer::from(&self.0.key))
+            .map_err(|err| err.at(self.0.context))?;
+        Ok((variant, Deserializer(&self.0.value)))
+    }
+}
+
+impl<'a, 'de> VariantAccess<'de> for Deserializer<'a, 'de> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Error> {
+        de::Deserializer::deserialize_unit(self, de::IgnoredAny).map(drop)
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, Error> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, Error> {
+        de::Deserializer::deserialize_seq(self, visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        de::Deserializer::deserialize_map(self, visitor)
+    }
+}
@@ -0,0 +1,297 @@
+//! Rendering parse errors together with a snippet of the input.
+//!
+//! ```
+//! use mavka_did::diagnostic::{render, Language};
+//!
+//! let input = "[1 2]";
+//! let error = mavka_did::parser::parse_did(input).unwrap_err();
+//! assert_eq!(
+//!     render(input, &error, Language::Ukrainian),
+//!     "\
+//! помилка: Очікувалася права квадратна дужка `]` або кома `,`
+//!  --> рядок 1, стовпчик 4
+//!   |
+//! 1 | [1 2]
+//!   | - список почався тут
+//!   |    ^ знайдено `2`
+//!   |
+//!   = порада: записи розділяються комами
+//! "
+//! );
+//! ```
+
+use std::fmt::{self, Write};
+
+use crate::{
+    line_index::{Encoding, LineIndex},
+    parser, Error, ErrorKind, Reason,
+};
+
+/// Language of the messages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Language {
+    #[default]
+    Ukrainian,
+    English,
+}
+
+/// Renders `error` of parsing `input` with the offending line of the source underlined.
+///
+/// When a bracket is expected, the start of the list, dictionary or object that it would
+/// close is labeled too.
+pub fn render(input: &str, error: &Error, language: Language) -> String {
+    let mut out = String::new();
+    write(&mut out, input, error, language).expect("writing to a String never fails");
+    out
+}
+
+/// Writes the rendering of `error` into `out`, see [`render`].
+pub fn write(out: &mut dyn Write, input: &str, error: &Error, language: Language) -> fmt::Result {
+    let primary = Label {
+        start: error.start,
+        len: error.found.as_deref().map_or(1, |found| {
+            found
+                .lines()
+                .next()
+                .map_or(1, |line| line.chars().count().max(1))
+        }),
+        marker: '^',
+        text: (error.reason.is_none()).then(|| error.found_message(language)),
+    };
+    let closes = error
+        .expected
+        .iter()
+        .any(|kind| matches!(kind, ErrorKind::RightParenthesis | ErrorKind::RightBracket));
+    let secondary = if let Some(Reason::DuplicateKey { first }) = &error.reason {
+        Some(Label {
+            start: first.start,
+            len: input[first.clone()].chars().count().max(1),
+            marker: '-',
+            text: Some(
+                match language {
+                    Language::Ukrainian => "уперше використано тут",
+                    Language::English => "first used here",
+                }
+                .to_owned(),
+            ),
+        })
+    } else {
+        closes
+            .then(|| opening(&input[..error.start]))
+            .flatten()
+            .map(|(start, len, opened)| Label {
+                start,
+                len,
+                marker: '-',
+                text: Some(opened.started(language).to_owned()),
+            })
+    };
+
+    let labels: Vec<_> = secondary.into_iter().chain([primary]).collect();
+    let line_index = LineIndex::new(input);
+    let lines: Vec<_> = labels
+        .iter()
+        .map(|label| line_of(&line_index, label.start))
+        .collect();
+    let width = lines
+        .iter()
+        .map(|(number, ..)| number.to_string().len())
+        .max()
+        .unwrap_or(1);
+    let gutter = " ".repeat(width);
+
+    let (error_word, hint_word) = match language {
+        Language::Ukrainian => ("помилка", "порада"),
+        Language::English => ("error", "hint"),
+    };
+    writeln!(out, "{error_word}: {}", error.message(language))?;
+    match language {
+        Language::Ukrainian => writeln!(
+            out,
+            "{gutter}--> рядок {}, стовпчик {}",
+            error.line, error.column
+        ),
+        Language::English => writeln!(
+            out,
+            "{gutter}--> line {}, column {}",
+            error.line, error.column
+        ),
+    }?;
+    writeln!(out, "{gutter} |")?;
+
+    let mut previous = None;
+    for (label, &(number, line_start, line)) in labels.iter().zip(&lines) {
+        if previous != Some(number) {
+            if previous.is_some_and(|previous| number > previous + 1) {
+                writeln!(out, "{gutter} ...")?;
+            }
+            writeln!(out, "{number:>width$} | {line}")?;
+            previous = Some(number);
+        }
+        // Tabs are kept, so that the marker lines up with the source.
+        let indent: String = input[line_start..label.start]
+            .chars()
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+        let markers = label.marker.to_string().repeat(label.len);
+        match &label.text {
+            Some(text) => writeln!(out, "{gutter} | {indent}{markers} {text}")?,
+            None => writeln!(out, "{gutter} | {indent}{markers}")?,
+        }
+    }
+
+    if let Some(hint) = hint(error, language) {
+        writeln!(out, "{gutter} |")?;
+        writeln!(out, "{gutter} = {hint_word}: {hint}")?;
+    }
+    Ok(())
+}
+
+struct Label {
+    start: usize,
+    /// Number of markers, in characters.
+    len: usize,
+    marker: char,
+    text: Option<String>,
+}
+
+/// Number, byte index of the start and text without the line break of the line at `index`.
+fn line_of<'inp>(lines: &LineIndex<'inp>, index: usize) -> (usize, usize, &'inp str) {
+    let (number, _) = lines
+        .line_col(index, Encoding::Utf8)
+        .expect("labels are in the input");
+    let start = lines
+        .offset(number, 1, Encoding::Utf8)
+        .expect("the line exists");
+    (number, start, lines.line(number).expect("the line exists"))
+}
+
+#[derive(Clone, Copy)]
+enum Opened {
+    List,
+    Dictionary,
+    Object,
+}
+
+impl Opened {
+    fn started(self, language: Language) -> &'static str {
+        match (self, language) {
+            (Self::List, Language::Ukrainian) => "список почався тут",
+            (Self::Dictionary, Language::Ukrainian) => "словник почався тут",
+            (Self::Object, Language::Ukrainian) => "об'єкт почався тут",
+            (Self::List, Language::English) => "the list starts here",
+            (Self::Dictionary, Language::English) => "the dictionary starts here",
+            (Self::Object, Language::English) => "the object starts here",
+        }
+    }
+}
+
+/// Byte index and length in characters of the start of the innermost list, dictionary
+/// or object that is not closed in `before`, as the recovering parser finds it.
+fn opening(before: &str) -> Option<(usize, usize, Opened)> {
+    let start = parser::unclosed(before)?;
+    let rest = &before[start..];
+    Some(match rest.chars().next()? {
+        '[' => (start, 1, Opened::List),
+        '(' => (start, 1, Opened::Dictionary),
+        _ => {
+            // The name of the object, which is an identifier.
+            let name = rest
+                .find(|c: char| c == '(' || c == ';' || c.is_whitespace())
+                .map_or(rest, |end| &rest[..end]);
+            (start, name.chars().count(), Opened::Object)
+        }
+    })
+}
+
+fn hint(error: &Error, language: Language) -> Option<&'static str> {
+    let ukrainian = language == Language::Ukrainian;
+    if let Some(reason) = &error.reason {
+        return Some(match (reason, ukrainian) {
+            (Reason::Escape(_), true) => {
+                "дозволені escape-послідовності: `\\\"`, `\\\\`, `\\b`, `\\f`, `\\n`, `\\r`, `\\t` і `\\uXXXX`"
+            }
+            (Reason::Escape(_), false) => {
+                "the allowed escape sequences are `\\\"`, `\\\\`, `\\b`, `\\f`, `\\n`, `\\r`, `\\t` and `\\uXXXX`"
+            }
+            (Reason::UnterminatedComment, true) => "блоковий коментар закривається `;;;`",
+            (Reason::UnterminatedComment, false) => "a block comment is closed with `;;;`",
+            (Reason::TooDeep(_), true) => {
+                "глибину обмежено, щоб розбір не переповнив стек, див. `ParseOptions::max_depth`"
+            }
+            (Reason::TooDeep(_), false) => {
+                "the depth is limited to keep parsing from overflowing the stack, see `ParseOptions::max_depth`"
+            }
+            (
+                Reason::InputTooLong(_)
+                | Reason::TooManyNodes(_)
+                | Reason::TextTooLong(_)
+                | Reason::TooManyEntries(_),
+                true,
+            ) => "обмеження задаються в `ParseOptions`",
+            (
+                Reason::InputTooLong(_)
+                | Reason::TooManyNodes(_)
+                | Reason::TextTooLong(_)
+                | Reason::TooManyEntries(_),
+                false,
+            ) => "the limits are set in `ParseOptions`",
+            (Reason::DuplicateKey { .. }, true) => {
+                "ключі порівнюються за значенням: `1` і `1.0` однакові, як і `а` і `\"а\"`"
+            }
+            (Reason::DuplicateKey { .. }, false) => {
+                "keys are compared by value: `1` and `1.0` are the same, as are `а` and `\"а\"`"
+            }
+        });
+    }
+    if error
+        .found
+        .as_deref()
+        .is_some_and(|found| found.starts_with('"') && (found.len() == 1 || !found.ends_with('"')))
+    {
+        return Some(if ukrainian {
+            "текст має закінчитися `\"` на тому ж рядку, перенесення рядка записується як `\\n`"
+        } else {
+            "a text must end with `\"` on the same line, a line break is written as `\\n`"
+        });
+    }
+    let hint = match (error.kind, ukrainian) {
+        (ErrorKind::Comma | ErrorKind::RightParenthesis | ErrorKind::RightBracket, _)
+            if !error.expected.contains(&ErrorKind::Comma) =>
+        {
+            return None;
+        }
+        (ErrorKind::Comma | ErrorKind::RightParenthesis | ErrorKind::RightBracket, true) => {
+            "записи розділяються комами"
+        }
+        (ErrorKind::Comma | ErrorKind::RightParenthesis | ErrorKind::RightBracket, false) => {
+            "entries are separated with commas"
+        }
+        (ErrorKind::EqualsSign, true) => "запис має вигляд `ключ=значення`",
+        (ErrorKind::EqualsSign, false) => "an entry looks like `key=value`",
+        (ErrorKind::Identifier, true) => {
+            "ключі об'єкта пишуться без лапок, для інших ключів використовуйте словник"
+        }
+        (ErrorKind::Identifier, false) => {
+            "keys of an object are written without quotes, use a dictionary for other keys"
+        }
+        (ErrorKind::DictionaryEntryKey, true) => {
+            "ключем словника може бути ідентифікатор, текст або число"
+        }
+        (ErrorKind::DictionaryEntryKey, false) => {
+            "a key of a dictionary can be an identifier, a text or a number"
+        }
+        (ErrorKind::LeftParenthesis, true) => "об'єкт записується як `Назва(ключ=значення)`",
+        (ErrorKind::LeftParenthesis, false) => "an object is written as `Name(key=value)`",
+        (ErrorKind::AstNode | ErrorKind::EntryValue, true) => {
+            "значенням може бути `пусто`, `так`, `ні`, число, текст, список, словник або об'єкт"
+        }
+        (ErrorKind::AstNode | ErrorKind::EntryValue, false) => {
+            "a value can be `пусто`, `так`, `ні`, a number, a text, a list, a dictionary or an object"
+        }
+        (ErrorKind::Eof, true) => "документ містить лише одне значення",
+        (ErrorKind::Eof, false) => "a document holds a single value",
+        _ => return None,
+    };
+    Some(hint)
+}
@@ -0,0 +1,413 @@
+//! Editing `Дід` documents while keeping their formatting and comments.
+//!
+//! ```
+//! use mavka_did::{edit::Key, parser};
+//!
+//! let input = "Людина(\n  параметри=(висота=175, вага=69), ;; у сантиметрах\n)\n";
+//! let mut document = parser::parse_cst(input).unwrap();
+//! document
+//!     .set(&[Key::from("параметри"), Key::from("висота")], "180")
+//!     .unwrap();
+//! assert_eq!(
+//!     document.to_string(),
+//!     "Людина(\n  параметри=(висота=180, вага=69), ;; у сантиметрах\n)\n"
+//! );
+//! ```
+//!
+//! Inserted nodes have contexts relative to their own text, so the edited document has to
+//! be parsed again for exact positions.
+
+use std::{borrow::Cow, fmt};
+
+use crate::{
+    parser::{
+        self,
+        cst::{Document, Element, Node, NodeKind, Token, TokenKind},
+    },
+    path::{self, Container, EntryKey},
+    writer, Error,
+};
+
+pub use crate::path::Key;
+
+#[derive(Debug)]
+pub enum EditError {
+    /// Nothing is found at the first `depth + 1` keys of the path.
+    NotFound { depth: usize },
+    /// The value at the path is not a list.
+    NotAList,
+    /// The new value is not a valid `Дід` value.
+    InvalidValue(Error),
+    /// An object entry key that is not an identifier.
+    InvalidKey(String),
+}
+
+impl fmt::Display for EditError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotFound { depth } => write!(f, "Не знайдено значення за ключем №{}", depth + 1),
+            Self::NotAList => write!(f, "Значення не є списком"),
+            Self::InvalidValue(err) => write!(f, "Некоректне значення: {err}"),
+            Self::InvalidKey(key) => {
+                write!(f, "Ключ запису об'єкта `{key}` не є ідентифікатором")
+            }
+        }
+    }
+}
+
+impl std::error::Error for EditError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::InvalidValue(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl<'inp> Document<'inp> {
+    /// The node of the value at `path`.
+    pub fn get(&self, path: &[Key<'_>]) -> Option<&Node<'inp>> {
+        let mut node = self.value();
+        for key in path {
+            let position = position(node, *key)?;
+            node = match &node.children[position] {
+                Element::Node(child) => value_of(child),
+                Element::Token(_) => unreachable!("positions point at nodes"),
+            };
+        }
+        Some(node)
+    }
+
+    /// Replaces the value at `path` with `value`, written as `Дід` source text.
+    ///
+    /// If the last key names an entry that does not exist yet, it is added to the end
+    /// of its dictionary or object.
+    pub fn set(&mut self, path: &[Key<'_>], value: &str) -> Result<(), EditError> {
+        let value = parse_value(value)?;
+        let Some((last, parent_path)) = path.split_last() else {
+            *self.value_mut() = value;
+            return Ok(());
+        };
+        let parent = navigate(self.value_mut(), parent_path)?;
+        match position(parent, *last) {
+            Some(position) => {
+                *value_of_mut(&mut parent.children[position]) = value;
+                Ok(())
+            }
+            None => match (*last, parent.kind) {
+                (Key::Name(key), NodeKind::Dictionary | NodeKind::Object) => {
+                    let entry = new_entry(parent, key, value)?;
+                    append(parent, entry);
+                    Ok(())
+                }
+                _ => Err(EditError::NotFound {
+                    depth: parent_path.len(),
+                }),
+            },
+        }
+    }
+
+    /// Adds `value`, written as `Дід` source text, to the end of the list at `path`.
+    pub fn push(&mut self, path: &[Key<'_>], value: &str) -> Result<(), EditError> {
+        let value = parse_value(value)?;
+        let list = navigate(self.value_mut(), path)?;
+        if list.kind != NodeKind::List {
+            return Err(EditError::NotAList);
+        }
+        append(list, value);
+        Ok(())
+    }
+
+    /// Removes the entry or the list element at `path` together with its comma.
+    pub fn remove(&mut self, path: &[Key<'_>]) -> Result<(), EditError> {
+        let Some((last, parent_path)) = path.split_last() else {
+            return Err(EditError::NotFound { depth: 0 });
+        };
+        let parent = navigate(self.value_mut(), parent_path)?;
+        let position = position(parent, *last).ok_or(EditError::NotFound {
+            depth: parent_path.len(),
+        })?;
+        remove(&mut parent.children, position);
+        Ok(())
+    }
+
+    fn value_mut(&mut self) -> &mut Node<'inp> {
+        self.root_mut()
+            .children
+            .iter_mut()
+            .find_map(|element| match element {
+                Element::Node(node) => Some(node),
+                Element::Token(_) => None,
+            })
+            .expect("a document has a value")
+    }
+}
+
+fn parse_value(value: &str) -> Result<Node<'static>, EditError> {
+    let document = parser::parse_cst(value).map_err(EditError::InvalidValue)?;
+    Ok(document.value().clone().into_owned())
+}
+
+fn navigate<'a, 'inp>(
+    mut node: &'a mut Node<'inp>,
+    path: &[Key<'_>],
+) -> Result<&'a mut Node<'inp>, EditError> {
+    for (depth, key) in path.iter().enumerate() {
+        let position = position(node, *key).ok_or(EditError::NotFound { depth })?;
+        node = value_of_mut(&mut node.children[position]);
+    }
+    Ok(node)
+}
+
+/// Index in `node.children` of the entry or the list element at `key`.
+fn position(node: &Node<'_>, key: Key<'_>) -> Option<usize> {
+    let entries: Vec<_> = node
+        .children
+        .iter()
+        .enumerate()
+        .filter_map(|(position, element)| match element {
+            Element::Node(child) => Some((position, child)),
+            Element::Token(_) => None,
+        })
+        .collect();
+    let container = match (node.kind, key) {
+        (NodeKind::Dictionary, _) => Container::Dictionary,
+        (NodeKind::Object, _) => Container::Object,
+        (NodeKind::List, Key::Index(index)) => {
+            return entries.get(index).map(|&(position, _)| position)
+        }
+        _ => return None,
+    };
+    let entry = path::position(container, &entries, |(_, entry)| entry_key(entry), key)?;
+    Some(entries[entry].0)
+}
+
+fn entry_key<'a>(entry: &'a Node<'_>) -> EntryKey<'a> {
+    let key = entry.token().expect("an entry has a key");
+    match key.kind {
+        TokenKind::Number => EntryKey::Number(&key.text),
+        TokenKind::Text => EntryKey::Escaped(&key.text[1..key.text.len() - 1], key.context),
+        _ => EntryKey::Name(&key.text),
+    }
+}
+
+/// The value of an entry, or the node itself for list elements.
+fn value_of<'a, 'inp>(node: &'a Node<'inp>) -> &'a Node<'inp> {
+    match node.kind {
+        NodeKind::DictionaryEntry | NodeKind::ObjectEntry => {
+            node.nodes().next().expect("an entry has a value")
+        }
+        _ => node,
+    }
+}
+
+fn value_of_mut<'a, 'inp>(element: &'a mut Element<'inp>) -> &'a mut Node<'inp> {
+    let Element::Node(node) = element else {
+        unreachable!("positions point at nodes");
+    };
+    if !matches!(node.kind, NodeKind::DictionaryEntry | NodeKind::ObjectEntry) {
+        return node;
+    }
+    node.children
+        .iter_mut()
+        .find_map(|element| match element {
+            Element::Node(value) => Some(value),
+            Element::Token(_) => None,
+        })
+        .expect("an entry has a value")
+}
+
+/// Builds an entry of `container`, spelling `=` with the same spaces as its last entry.
+///
+/// The key is a number if it is written as one and all keys of the dictionary are numbers.
+fn new_entry<'inp>(
+    container: &Node<'inp>,
+    key: &str,
+    value: Node<'inp>,
+) -> Result<Node<'inp>, EditError> {
+    let number_keys = container.kind == NodeKind::Dictionary
+        && container.nodes().next().is_some()
+        && container
+            .nodes()
+            .all(|entry| matches!(entry.token(), Some(key) if key.kind == TokenKind::Number));
+    let key = if number_keys && is_number(key) {
+        token(TokenKind::Number, key.to_owned())
+    } else if writer::is_identifier(key) {
+        token(TokenKind::Identifier, key.to_owned())
+    } else if container.kind == NodeKind::Dictionary {
+        token(TokenKind::Text, format!("\"{}\"", writer::escape(key)))
+    } else {
+        return Err(EditError::InvalidKey(key.to_owned()));
+    };
+
+    // Comments and line breaks around `=` belong to that entry only.
+    let is_space = |element: &&Element<'_>| match element {
+        Element::Token(token) => token.kind == TokenKind::Whitespace && !token.text.contains('\n'),
+        Element::Node(_) => false,
+    };
+    let equals = container
+        .nodes()
+        .last()
+        .map(|entry| {
+            let children = &entry.children;
+            let equals = children
+                .iter()
+                .position(|element| is_token(element, TokenKind::EqualsSign))
+                .expect("an entry has `=`");
+            let before = children[..equals].last().filter(is_space);
+            let after = children.get(equals + 1).filter(is_space);
+            before
+                .into_iter()
+                .chain([&children[equals]])
+                .chain(after)
+                .cloned()
+                .collect()
+        })
+        .unwrap_or_else(|| vec![Element::Token(token(TokenKind::EqualsSign, "=".to_owned()))]);
+
+    let mut children = vec![Element::Token(key)];
+    children.extend(equals);
+    children.push(Element::Node(value));
+    Ok(Node {
+        kind: match container.kind {
+            NodeKind::Object => NodeKind::ObjectEntry,
+            _ => NodeKind::DictionaryEntry,
+        },
+        children,
+        context: Default::default(),
+    })
+}
+
+/// Whether `text` is a single number token.
+fn is_number(text: &str) -> bool {
+    parser::parse_cst(text).is_ok_and(|document| {
+        let value = document.value();
+        value.kind == NodeKind::Number && value.to_string() == text
+    })
+}
+
+fn token<'inp>(kind: TokenKind, text: String) -> Token<'inp> {
+    Token {
+        kind,
+        text: Cow::Owned(text),
+        context: Default::default(),
+    }
+}
+
+fn is_token(element: &Element<'_>, kind: TokenKind) -> bool {
+    matches!(element, Element::Token(token) if token.kind == kind)
+}
+
+/// Adds `element` after the last entry of `container`, separated and indented
+/// like the entries before it. A trailing comma is kept if there was one.
+fn append<'inp>(container: &mut Node<'inp>, element: Node<'inp>) {
+    let children = &mut container.children;
+    let Some(last) = children
+        .iter()
+        .rposition(|element| matches!(element, Element::Node(_)))
+    else {
+        let open = children
+            .iter()
+            .position(|element| {
+                is_token(element, TokenKind::LeftParenthesis)
+                    || is_token(element, TokenKind::LeftBracket)
+            })
+            .expect("a container has an opening bracket");
+        children.insert(open + 1, Element::Node(element));
+        return;
+    };
+
+    let separator = match &children[last - 1] {
+        Element::Token(token) if token.kind == TokenKind::Whitespace => token.text.to_string(),
+        element if is_token(element, TokenKind::Comma) => String::new(),
+        _ => " ".to_owned(),
+    };
+    let trailing_comma = children[last + 1..]
+        .iter()
+        .position(|element| is_token(element, TokenKind::Comma))
+        .map(|offset| last + 1 + offset);
+
+    let at = match trailing_comma {
+        Some(comma) => comma + 1,
+        None => {
+            children.insert(
+                last + 1,
+                Element::Token(token(TokenKind::Comma, ",".to_owned())),
+            );
+            last + 2
+        }
+    };
+    // Comments on the line of the last entry stay with it.
+    let at = skip_line_comments(children, at);
+
+    let after_line_comment = is_token(&children[at - 1], TokenKind::LineComment);
+    let separator = if after_line_comment && !separator.contains('\n') {
+        "\n".to_owned()
+    } else {
+        separator
+    };
+    let mut inserted = Vec::with_capacity(3);
+    if !separator.is_empty() {
+        inserted.push(Element::Token(token(TokenKind::Whitespace, separator)));
+    }
+    inserted.push(Element::Node(element));
+    if trailing_comma.is_some() {
+        inserted.push(Element::Token(token(TokenKind::Comma, ",".to_owned())));
+    }
+    children.splice(at..at, inserted);
+}
+
+/// Index after the comments that follow `at` on the same line, or `at` if there are none.
+fn skip_line_comments(children: &[Element<'_>], at: usize) -> usize {
+    let mut end = at;
+    for (offset, element) in children[at..].iter().enumerate() {
+        let Element::Token(token) = element else {
+            break;
+        };
+        match token.kind {
+            TokenKind::Whitespace | TokenKind::BlockComment if token.text.contains('\n') => break,
+            TokenKind::Whitespace => {}
+            TokenKind::BlockComment => end = at + offset + 1,
+            TokenKind::LineComment => return at + offset + 1,
+            _ => break,
+        }
+    }
+    end
+}
+
+/// Removes the node at `position` with its comma, the whitespace that led to it
+/// and the comments on its line.
+fn remove(children: &mut Vec<Element<'_>>, position: usize) {
+    let is_whitespace = |element: &Element<'_>| is_token(element, TokenKind::Whitespace);
+    let is_inline_whitespace = |element: &Element<'_>| matches!(element, Element::Token(token) if token.kind == TokenKind::Whitespace && !token.text.contains('\n'));
+    let comma = children[position + 1..]
+        .iter()
+        .take_while(|element| matches!(element, Element::Token(token) if token.kind.is_trivia() || token.kind == TokenKind::Comma))
+        .position(|element| is_token(element, TokenKind::Comma))
+        .map(|offset| position + 1 + offset);
+
+    let mut start = position;
+    let end = match comma {
+        Some(comma) => {
+            let end = skip_line_comments(children, comma + 1);
+            if is_whitespace(&children[start - 1]) {
+                start -= 1;
+                end
+            } else if children.get(end).is_some_and(is_inline_whitespace) {
+                end + 1
+            } else {
+                end
+            }
+        }
+        None => {
+            if is_whitespace(&children[start - 1]) {
+                start -= 1;
+            }
+            if is_token(&children[start - 1], TokenKind::Comma) {
+                start -= 1;
+            }
+            skip_line_comments(children, position + 1)
+        }
+    };
+    children.drain(start..end);
+}
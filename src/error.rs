@@ -0,0 +1,476 @@
+//! Errors of parsing the `Дід` format.
+
+use std::{fmt, ops::Range};
+
+#[cfg(feature = "wasm")]
+use crate::parser::escape::EscapeError;
+use crate::{
+    diagnostic::Language,
+    line_index::LineIndex,
+    parser::{ast::NodeContext, escape::EscapeErrorKind, Rule},
+};
+
+/// An error of parsing `Дід` source text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Error {
+    /// The first of the expected alternatives.
+    pub kind: ErrorKind,
+    /// Everything that could stand at the position of the error, `kind` included.
+    pub expected: Vec<ErrorKind>,
+    /// Byte index of the start of the offending text.
+    pub start: usize,
+    /// Byte index right after the offending text.
+    pub end: usize,
+    /// Line of `start`, starting from 1.
+    pub line: usize,
+    /// Column of `start` in characters, starting from 1.
+    pub column: usize,
+    /// The offending text, or `None` at the end of the input.
+    pub found: Option<String>,
+    /// Why the found text is invalid, when it has the expected kind but is still wrong.
+    pub reason: Option<Reason>,
+}
+
+/// What the parser expected to find.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    EmptyNode,
+    LogicalNode,
+    NumberNode,
+    TextNode,
+    ListNode,
+    DictionaryNode,
+    DictionaryEntryNode,
+    DictionaryEntryKey,
+    ObjectNode,
+    ObjectEntryNode,
+    /// Any node.
+    AstNode,
+    Identifier,
+    EqualsSign,
+    LeftParenthesis,
+    RightParenthesis,
+    LeftBracket,
+    RightBracket,
+    Comma,
+    /// Any node after the `=` of an entry.
+    EntryValue,
+    Eof,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Reason {
+    /// An invalid escape sequence in a text.
+    Escape(EscapeErrorKind),
+    /// A block comment without the closing `;;;`.
+    UnterminatedComment,
+    /// A list, dictionary or object nested deeper than the limit, which is given.
+    TooDeep(usize),
+    /// The input is longer than the limit in bytes, which is given.
+    InputTooLong(usize),
+    /// The document has more values than the limit, which is given.
+    TooManyNodes(usize),
+    /// A text is longer than the limit in bytes, which is given.
+    TextTooLong(usize),
+    /// A list, dictionary or object has more entries than the limit, which is given.
+    TooManyEntries(usize),
+    /// The key is the same as the one of an earlier entry, which is at the byte range `first`.
+    DuplicateKey { first: Range<usize> },
+}
+
+impl ErrorKind {
+    /// The verb agreeing with the noun, and the noun of what was expected.
+    pub(crate) fn words(self, language: Language) -> (&'static str, &'static str) {
+        match language {
+            Language::Ukrainian => match self {
+                Self::EmptyNode => ("Очікувався", "вузол `пусто`"),
+                Self::LogicalNode => ("Очікувався", "логічний вузол (`так` або `ні`)"),
+                Self::NumberNode => ("Очікувався", "числовий вузол"),
+                Self::TextNode => (
+                    "Очікувався",
+                    "текстовий вузол (явні перенесення рядків не дозволені)",
+                ),
+                Self::ListNode => ("Очікувався", "список"),
+                Self::DictionaryNode => ("Очікувався", "словник"),
+                Self::DictionaryEntryNode => ("Очікувався", "запис словника"),
+                Self::DictionaryEntryKey => (
+                    "Очікувався",
+                    "ключ запису словника (ідентифікатор, текст або число)",
+                ),
+                Self::ObjectNode => ("Очікувався", "об'єкт"),
+                Self::ObjectEntryNode => ("Очікувався", "запис об'єкта"),
+                Self::AstNode => ("Очікувався", "вузол формату `Дід`"),
+                Self::Identifier => (
+                    "Очікувався",
+                    "ідентифікатор запису (має починатися з літери або `_`)",
+                ),
+                Self::EqualsSign => ("Очікувався", "знак рівності `=`"),
+                Self::LeftParenthesis => ("Очікувалася", "ліва кругла дужка `(`"),
+                Self::RightParenthesis => ("Очікувалася", "права кругла дужка `)`"),
+                Self::LeftBracket => ("Очікувалася", "ліва квадратна дужка `[`"),
+                Self::RightBracket => ("Очікувалася", "права квадратна дужка `]`"),
+                Self::Comma => ("Очікувалася", "кома `,`"),
+                Self::EntryValue => ("Очікувалося", "значення запису"),
+                Self::Eof => ("Очікувався", "кінець файлу"),
+            },
+            Language::English => (
+                "Expected",
+                match self {
+                    Self::EmptyNode => "the `пусто` node",
+                    Self::LogicalNode => "a logical node (`так` or `ні`)",
+                    Self::NumberNode => "a number node",
+                    Self::TextNode => "a text node (explicit line breaks are not allowed)",
+                    Self::ListNode => "a list",
+                    Self::DictionaryNode => "a dictionary",
+                    Self::DictionaryEntryNode => "a dictionary entry",
+                    Self::DictionaryEntryKey => {
+                        "a dictionary entry key (an identifier, a text or a number)"
+                    }
+                    Self::ObjectNode => "an object",
+                    Self::ObjectEntryNode => "an object entry",
+                    Self::AstNode => "a `Дід` node",
+                    Self::Identifier => "an entry identifier (it must start with a letter or `_`)",
+                    Self::EqualsSign => "an equals sign `=`",
+                    Self::LeftParenthesis => "a left parenthesis `(`",
+                    Self::RightParenthesis => "a right parenthesis `)`",
+                    Self::LeftBracket => "a left square bracket `[`",
+                    Self::RightBracket => "a right square bracket `]`",
+                    Self::Comma => "a comma `,`",
+                    Self::EntryValue => "an entry value",
+                    Self::Eof => "the end of the file",
+                },
+            ),
+        }
+    }
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (verb, noun) = self.words(Language::Ukrainian);
+        write!(f, "{verb} {noun}")
+    }
+}
+
+impl Reason {
+    pub(crate) fn message(&self, language: Language) -> String {
+        match (self, language) {
+            (Self::Escape(kind), Language::Ukrainian) => kind.to_string(),
+            (Self::Escape(kind), Language::English) => match kind {
+                EscapeErrorKind::UnknownEscape(c) => format!("Unknown escape sequence `\\{c}`"),
+                EscapeErrorKind::TrailingBackslash => "Unfinished escape sequence `\\`".to_owned(),
+                EscapeErrorKind::InvalidUnicodeEscape => {
+                    "Expected four hexadecimal digits after `\\u`".to_owned()
+                }
+                EscapeErrorKind::UnpairedSurrogate(unit) => {
+                    format!("Surrogate `\\u{unit:04X}` has no pair")
+                }
+            },
+            (Self::UnterminatedComment, Language::Ukrainian) => {
+                "Незавершений блоковий коментар".to_owned()
+            }
+            (Self::UnterminatedComment, Language::English) => {
+                "Unterminated block comment".to_owned()
+            }
+            (Self::TooDeep(max_depth), Language::Ukrainian) => {
+                format!("Вкладеність глибша за дозволену (найбільша глибина: {max_depth})")
+            }
+            (Self::TooDeep(max_depth), Language::English) => {
+                format!("Nesting is deeper than allowed (the maximum depth is {max_depth})")
+            }
+            (Self::InputTooLong(max), Language::Ukrainian) => {
+                format!("Вхідний текст довший за дозволений (найбільша довжина в байтах: {max})")
+            }
+            (Self::InputTooLong(max), Language::English) => {
+                format!("The input is longer than allowed (the maximum length is {max} bytes)")
+            }
+            (Self::TooManyNodes(max), Language::Ukrainian) => {
+                format!("Документ містить забагато значень (найбільша кількість: {max})")
+            }
+            (Self::TooManyNodes(max), Language::English) => {
+                format!("The document has too many values (the maximum is {max})")
+            }
+            (Self::TextTooLong(max), Language::Ukrainian) => {
+                format!("Текст довший за дозволений (найбільша довжина в байтах: {max})")
+            }
+            (Self::TextTooLong(max), Language::English) => {
+                format!("The text is longer than allowed (the maximum length is {max} bytes)")
+            }
+            (Self::TooManyEntries(max), Language::Ukrainian) => {
+                format!("Забагато записів (найбільша кількість: {max})")
+            }
+            (Self::TooManyEntries(max), Language::English) => {
+                format!("Too many entries (the maximum is {max})")
+            }
+            (Self::DuplicateKey { .. }, Language::Ukrainian) => "Ключ повторюється".to_owned(),
+            (Self::DuplicateKey { .. }, Language::English) => "Duplicate key".to_owned(),
+        }
+    }
+}
+
+impl fmt::Display for Reason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message(Language::Ukrainian))
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message(Language::Ukrainian))?;
+        if self.reason.is_none() {
+            write!(f, ", {}", self.found_message(Language::Ukrainian))?;
+        }
+        write!(f, " (рядок: {}, стовпчик: {})", self.line, self.column)
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl Error {
+    /// Range of the offending text in `input`, which is the parsed text.
+    pub fn context(&self, input: &str) -> NodeContext {
+        NodeContext::at(input, self.start).spanning(&input[self.start..self.end])
+    }
+
+    /// The reason, or what was expected.
+    pub(crate) fn message(&self, language: Language) -> String {
+        if let Some(reason) = &self.reason {
+            return reason.message(language);
+        }
+        let (verb, noun) = self.kind.words(language);
+        let mut message = format!("{verb} {noun}");
+        for kind in self.expected.iter().filter(|&&kind| kind != self.kind) {
+            message += match language {
+                Language::Ukrainian => " або ",
+                Language::English => " or ",
+            };
+            message += kind.words(language).1;
+        }
+        message
+    }
+
+    pub(crate) fn found_message(&self, language: Language) -> String {
+        match (&self.found, language) {
+            (Some(found), Language::Ukrainian) => format!("знайдено `{found}`"),
+            (Some(found), Language::English) => format!("found `{found}`"),
+            (None, Language::Ukrainian) => "знайдено кінець файлу".to_owned(),
+            (None, Language::English) => "found the end of the file".to_owned(),
+        }
+    }
+
+    /// Translates an error of the grammar, naming everything it expected at the failed position.
+    pub(crate) fn from_pest(lines: &LineIndex<'_>, err: pest::error::Error<Rule>) -> Self {
+        let start = match err.location {
+            pest::error::InputLocation::Pos(pos) => pos,
+            pest::error::InputLocation::Span((start, _)) => start,
+        };
+        let expected = match err.variant {
+            pest::error::ErrorVariant::ParsingError { positives, .. } => {
+                expectations(&positives, &lines.input()[..start])
+            }
+            // The grammar raises no custom errors.
+            pest::error::ErrorVariant::CustomError { .. } => Vec::new(),
+        };
+        // The grammar fails at the start of a block comment that is never closed.
+        let rest = &lines.input()[start..];
+        if rest
+            .strip_prefix(";;;")
+            .is_some_and(|comment| !comment.contains(";;;"))
+        {
+            return Self::new(
+                lines,
+                start,
+                rest.len(),
+                Vec::new(),
+                Some(Reason::UnterminatedComment),
+            );
+        }
+        Self::expected(lines, start, expected)
+    }
+
+    /// An invalid escape sequence in a text node.
+    #[cfg(feature = "wasm")]
+    pub(crate) fn from_escape(lines: &LineIndex<'_>, err: EscapeError) -> Self {
+        Self::escape(lines, err.context.index as usize, err.kind)
+    }
+
+    /// An invalid escape sequence at `start`.
+    pub(crate) fn escape(lines: &LineIndex<'_>, start: usize, kind: EscapeErrorKind) -> Self {
+        Self::new(
+            lines,
+            start,
+            escape_len(&lines.input()[start..]),
+            vec![ErrorKind::TextNode],
+            Some(Reason::Escape(kind)),
+        )
+    }
+
+    /// A limit of parsing is exceeded at `start`.
+    pub(crate) fn limit(lines: &LineIndex<'_>, start: usize, reason: Reason) -> Self {
+        Self::new(
+            lines,
+            start,
+            token_len(&lines.input()[start..]),
+            Vec::new(),
+            Some(reason),
+        )
+    }
+
+    /// The `key` repeats the key at `first`.
+    pub(crate) fn duplicate_key(
+        lines: &LineIndex<'_>,
+        first: NodeContext,
+        key: NodeContext,
+    ) -> Self {
+        let range = key.range();
+        Self::new(
+            lines,
+            range.start,
+            range.len(),
+            Vec::new(),
+            Some(Reason::DuplicateKey {
+                first: first.range(),
+            }),
+        )
+    }
+
+    /// None of `expected` is found at `start`.
+    pub(crate) fn expected(lines: &LineIndex<'_>, start: usize, expected: Vec<ErrorKind>) -> Self {
+        Self::new(
+            lines,
+            start,
+            token_len(&lines.input()[start..]),
+            expected,
+            None,
+        )
+    }
+
+    pub(crate) fn new(
+        lines: &LineIndex<'_>,
+        start: usize,
+        len: usize,
+        expected: Vec<ErrorKind>,
+        reason: Option<Reason>,
+    ) -> Self {
+        let end = start + len;
+        let context = lines
+            .context(start)
+            .expect("the start is at a character of the input");
+        Self {
+            kind: expected.first().copied().unwrap_or(ErrorKind::AstNode),
+            expected,
+            start,
+            end,
+            line: context.line as usize,
+            column: context.column as usize,
+            found: (len > 0).then(|| lines.input()[start..end].to_owned()),
+            reason,
+        }
+    }
+}
+
+/// Rules that can start a node, as pest reports them when no node was found.
+const NODE_START: [Rule; 7] = [
+    Rule::empty,
+    Rule::logical,
+    Rule::number,
+    Rule::text,
+    Rule::ident,
+    Rule::left_paren,
+    Rule::left_bracket,
+];
+
+/// Rules that can start a key of a dictionary entry.
+const DICTIONARY_KEY_START: [Rule; 3] = [Rule::number, Rule::ident, Rule::text];
+
+/// Maps the rules pest expected to error kinds, merging the ones that together mean
+/// "any node" or "any dictionary key". `before` is the input up to the failed position.
+fn expectations(positives: &[Rule], before: &str) -> Vec<ErrorKind> {
+    let (group, kind) = if NODE_START.iter().all(|rule| positives.contains(rule)) {
+        let kind = if before.trim_end().ends_with('=') {
+            ErrorKind::EntryValue
+        } else {
+            ErrorKind::AstNode
+        };
+        (&NODE_START[..], kind)
+    } else if DICTIONARY_KEY_START
+        .iter()
+        .all(|rule| positives.contains(rule))
+    {
+        (&DICTIONARY_KEY_START[..], ErrorKind::DictionaryEntryKey)
+    } else {
+        (&[][..], ErrorKind::AstNode)
+    };
+
+    let mut expected = Vec::with_capacity(positives.len());
+    for rule in positives {
+        let kind = if group.contains(rule) {
+            kind
+        } else {
+            match rule {
+                Rule::empty => ErrorKind::EmptyNode,
+                Rule::logical => ErrorKind::LogicalNode,
+                Rule::number => ErrorKind::NumberNode,
+                Rule::text => ErrorKind::TextNode,
+                Rule::list => ErrorKind::ListNode,
+                Rule::dict => ErrorKind::DictionaryNode,
+                Rule::dict_entry => ErrorKind::DictionaryEntryNode,
+                Rule::object => ErrorKind::ObjectNode,
+                Rule::object_entry => ErrorKind::ObjectEntryNode,
+                Rule::ident => ErrorKind::Identifier,
+                Rule::equals => ErrorKind::EqualsSign,
+                Rule::left_paren => ErrorKind::LeftParenthesis,
+                Rule::right_paren => ErrorKind::RightParenthesis,
+                Rule::left_bracket => ErrorKind::LeftBracket,
+                Rule::right_bracket => ErrorKind::RightBracket,
+                Rule::comma => ErrorKind::Comma,
+                Rule::EOI => ErrorKind::Eof,
+                _ => continue,
+            }
+        };
+        if !expected.contains(&kind) {
+            expected.push(kind);
+        }
+    }
+    expected
+}
+
+/// Byte length of the token that starts `rest`, roughly as the grammar would split it.
+pub(crate) fn token_len(rest: &str) -> usize {
+    let Some(first) = rest.chars().next() else {
+        return 0;
+    };
+    match first {
+        '"' => {
+            // Up to the closing quote, or to the end of the line if there is none.
+            let mut escaped = false;
+            rest.char_indices()
+                .skip(1)
+                .find(|&(_, c)| {
+                    let end = !escaped && matches!(c, '"' | '\n' | '\r');
+                    escaped = !escaped && c == '\\';
+                    end
+                })
+                .map_or(rest.len(), |(i, c)| if c == '"' { i + 1 } else { i })
+        }
+        ';' if rest.starts_with(";;") => rest.find(['\n', '\r']).unwrap_or(rest.len()),
+        '(' | ')' | '[' | ']' | ',' | '=' | ';' => 1,
+        c if c.is_whitespace() => c.len_utf8(),
+        _ => rest
+            .find(|c: char| c.is_whitespace() || "()[],=;\"".contains(c))
+            .unwrap_or(rest.len()),
+    }
+}
+
+/// Byte length of the escape sequence that starts `rest` with a backslash.
+fn escape_len(rest: &str) -> usize {
+    let mut chars = rest.char_indices().skip(1);
+    match chars.next() {
+        Some((_, 'u')) => chars
+            .take(4)
+            .take_while(|(_, c)| c.is_ascii_hexdigit())
+            .last()
+            .map_or(2, |(i, _)| i + 1),
+        Some((i, c)) => i + c.len_utf8(),
+        None => rest.len(),
+    }
+}
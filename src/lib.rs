@@ -5,17 +5,53 @@ wai_bindgen_rust::export!("api.wai");
 
 #[cfg(feature = "wasm")]
 mod api_trait_impls;
+pub mod convert;
+#[cfg(feature = "serde")]
+pub mod de;
+pub mod diagnostic;
+pub mod edit;
+mod error;
+pub mod line_index;
 #[cfg(feature = "wasm")]
 pub mod node;
 pub mod parser;
+pub mod path;
+pub mod query;
+#[cfg(feature = "serde")]
+pub mod ser;
+pub mod visit;
+pub mod writer;
 
 #[cfg(feature = "wasm")]
 use std::fmt::Debug;
 
+pub use convert::{FromDid, ToDid};
+#[cfg(feature = "serde")]
+pub use de::{from_did, from_str};
+pub use error::{Error, ErrorKind, Reason};
+/// Object names and keys are written without quotes, so names and keys that are not
+/// identifiers are rejected:
+///
+/// ```compile_fail
+/// #[derive(mavka_did::ToDid)]
+/// struct Person {
+///     #[did(rename = "повне імʼя")]
+///     name: String,
+/// }
+/// ```
+///
+/// ```compile_fail
+/// #[derive(mavka_did::ToDid)]
+/// #[did(name = "так")]
+/// struct Yes;
+/// ```
+#[cfg(feature = "derive")]
+pub use mavka_did_derive::{FromDid, ToDid};
+#[cfg(feature = "serde")]
+pub use ser::{to_did, to_string, to_string_pretty};
+
 #[cfg(feature = "wasm")]
-use node::{DictionaryEntryNode, ObjectEntryNode, ParseErrorExpectation};
-#[cfg(feature = "wasm")]
-use pest::error::{InputLocation, LineColLocation};
+use node::{DictionaryEntryNode, ObjectEntryNode};
 #[cfg(feature = "wasm")]
 use wai_bindgen_rust::Handle;
 
@@ -36,31 +72,40 @@ impl api::BoxedAstNode for BoxedAstNode {
     fn get(&self) -> AstNode {
         self.0.clone()
     }
+
+    fn pointer(&self, pointer: String) -> Option<AstNode> {
+        self.0.pointer(&pointer).cloned()
+    }
 }
 
 #[cfg(feature = "wasm")]
 impl api::Api for Api {
     #[inline]
     fn parse(input: String) -> Result<AstNode, ParseError> {
-        parser::parse(&input).map_err(|err| {
-            let (line, column) = match err.line_col {
-                LineColLocation::Pos((line, col)) => (line as u64, col as u64),
-                LineColLocation::Span((line, col), _) => (line as u64, col as u64),
-            };
-            let index = match err.location {
-                InputLocation::Pos(pos) => pos as u64,
-                InputLocation::Span((start, _)) => start as u64,
-            };
-            ParseError {
-                expectation: ParseErrorExpectation::AstNode,
-                line,
-                column,
-                index,
-                info: err.to_string(),
-            }
+        parser::parse(&input).map_err(|err| ParseError::new(&input, err))
+    }
+
+    fn parse_with(input: String, options: api::ParseOptions) -> Result<AstNode, ParseError> {
+        parser::parse_with(&input, &options.into()).map_err(|err| ParseError::new(&input, err))
+    }
+
+    fn parse_with_warnings(
+        input: String,
+        options: api::ParseOptions,
+    ) -> Result<api::ParsedDocument, ParseError> {
+        let (root, warnings) = parser::parse_with_warnings(&input, &options.into())
+            .map_err(|err| ParseError::new(&input, err))?;
+        Ok(api::ParsedDocument {
+            root,
+            warnings: warnings.into_iter().map(Into::into).collect(),
         })
     }
 
+    fn render_error(input: String, error: ParseError, language: api::Language) -> String {
+        let error = error.into_error(&input);
+        diagnostic::render(&input, &error, language.into())
+    }
+
     fn display(root: AstNode, pretty: bool) -> String {
         let root = DisplayableAst::from(&root);
         if pretty {
@@ -69,6 +114,31 @@ impl api::Api for Api {
             format!("{:?}", root)
         }
     }
+
+    fn stringify(root: AstNode, pretty: bool) -> String {
+        if pretty {
+            writer::to_string_pretty(&root)
+        } else {
+            writer::to_string(&root)
+        }
+    }
+
+    fn pointer(root: AstNode, pointer: String) -> Option<AstNode> {
+        root.pointer(&pointer).cloned()
+    }
+
+    fn get_path(root: AstNode, path: Vec<api::PathKey>) -> Option<AstNode> {
+        let path: Vec<_> = path
+            .iter()
+            .map(|key| match key {
+                api::PathKey::Name(name) => path::Key::Name(name),
+                api::PathKey::Index(index) => {
+                    path::Key::Index(usize::try_from(*index).unwrap_or(usize::MAX))
+                }
+            })
+            .collect();
+        root.get_path(&path).cloned()
+    }
 }
 
 #[cfg(feature = "wasm")]
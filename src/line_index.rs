@@ -0,0 +1,207 @@
+//! Converting offsets in the input to lines and columns and back.
+//!
+//! Lines end with `\n`, `\r\n` or a lone `\r`.
+//!
+//! ```
+//! use mavka_did::line_index::{Encoding, LineIndex};
+//!
+//! let index = LineIndex::new("[\r\n  \"ї\", 1]");
+//! assert_eq!(index.line_col(10, Encoding::Utf8), Some((2, 8)));
+//! assert_eq!(index.line_col(10, Encoding::Utf16), Some((2, 7)));
+//! assert_eq!(index.offset(2, 7, Encoding::Utf16), Some(10));
+//! assert_eq!(index.encode(10, Encoding::Utf16), Some(9));
+//! ```
+
+use crate::parser::ast::NodeContext;
+
+/// Units in which offsets and columns are measured.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Encoding {
+    /// UTF-8 bytes, which index Rust strings.
+    #[default]
+    Utf8,
+    /// UTF-16 code units, which index JavaScript strings.
+    Utf16,
+    /// Unicode scalar values, which are Rust [`char`]s.
+    Utf32,
+}
+
+impl Encoding {
+    fn len(self, c: char) -> usize {
+        match self {
+            Self::Utf8 => c.len_utf8(),
+            Self::Utf16 => c.len_utf16(),
+            Self::Utf32 => 1,
+        }
+    }
+
+    fn measure(self, text: &str) -> usize {
+        match self {
+            Self::Utf8 => text.len(),
+            Self::Utf16 => text.chars().map(char::len_utf16).sum(),
+            Self::Utf32 => text.chars().count(),
+        }
+    }
+}
+
+/// Starts of the lines of an input, built once to convert many positions in it.
+#[derive(Debug, Clone)]
+pub struct LineIndex<'inp> {
+    input: &'inp str,
+    lines: Vec<LineStart>,
+    /// Length of the input in every encoding.
+    len: LineStart,
+}
+
+/// Offset in every encoding.
+#[derive(Debug, Clone, Copy)]
+struct LineStart {
+    utf8: usize,
+    utf16: usize,
+    utf32: usize,
+}
+
+impl LineStart {
+    fn get(self, encoding: Encoding) -> usize {
+        match encoding {
+            Encoding::Utf8 => self.utf8,
+            Encoding::Utf16 => self.utf16,
+            Encoding::Utf32 => self.utf32,
+        }
+    }
+}
+
+impl<'inp> LineIndex<'inp> {
+    pub fn new(input: &'inp str) -> Self {
+        let mut lines = vec![LineStart {
+            utf8: 0,
+            utf16: 0,
+            utf32: 0,
+        }];
+        let mut utf16 = 0;
+        let mut chars = input.char_indices().enumerate().peekable();
+        while let Some((utf32, (utf8, c))) = chars.next() {
+            utf16 += c.len_utf16();
+            let ends_line = match c {
+                '\n' => true,
+                '\r' => !matches!(chars.peek(), Some((_, (_, '\n')))),
+                _ => false,
+            };
+            if ends_line {
+                lines.push(LineStart {
+                    utf8: utf8 + 1,
+                    utf16,
+                    utf32: utf32 + 1,
+                });
+            }
+        }
+        let len = LineStart {
+            utf8: input.len(),
+            utf16,
+            utf32: input.chars().count(),
+        };
+        Self { input, lines, len }
+    }
+
+    /// The indexed input.
+    pub fn input(&self) -> &'inp str {
+        self.input
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.lines.len()
+    }
+
+    /// Text of the `line`, starting from 1, without its line break.
+    pub fn line(&self, line: usize) -> Option<&'inp str> {
+        let start = self.lines.get(line.checked_sub(1)?)?.utf8;
+        let end = self
+            .lines
+            .get(line)
+            .map_or(self.input.len(), |next| next.utf8);
+        Some(self.input[start..end].trim_end_matches(['\n', '\r']))
+    }
+
+    /// Line and column, both starting from 1, of the byte `offset`, with the column
+    /// measured in `encoding`.
+    ///
+    /// Returns `None` if `offset` is past the end of the input or inside a character.
+    pub fn line_col(&self, offset: usize, encoding: Encoding) -> Option<(usize, usize)> {
+        let (line, start) = self.line_at(offset, Encoding::Utf8)?;
+        let before = self.input.get(start.utf8..offset)?;
+        Some((line, encoding.measure(before) + 1))
+    }
+
+    /// Byte offset of the `column` of the `line`, both starting from 1, with the column
+    /// measured in `encoding`.
+    ///
+    /// The column right after the last character of the line is its line break.
+    /// Returns `None` for a position outside of the input or inside a character.
+    pub fn offset(&self, line: usize, column: usize, encoding: Encoding) -> Option<usize> {
+        let start = self.lines.get(line.checked_sub(1)?)?.utf8;
+        let text = self.line(line)?;
+        advance(start, text, column.checked_sub(1)?, encoding)
+    }
+
+    /// The byte `offset` measured in `encoding`.
+    ///
+    /// Returns `None` if `offset` is past the end of the input or inside a character.
+    pub fn encode(&self, offset: usize, encoding: Encoding) -> Option<usize> {
+        let (_, start) = self.line_at(offset, Encoding::Utf8)?;
+        let before = self.input.get(start.utf8..offset)?;
+        Some(start.get(encoding) + encoding.measure(before))
+    }
+
+    /// Byte offset of the `offset` measured in `encoding`.
+    ///
+    /// Returns `None` if `offset` is past the end of the input or inside a character.
+    pub fn decode(&self, offset: usize, encoding: Encoding) -> Option<usize> {
+        let (line, start) = self.line_at(offset, encoding)?;
+        let end = self
+            .lines
+            .get(line)
+            .map_or(self.input.len(), |next| next.utf8);
+        let rest = offset - start.get(encoding);
+        advance(start.utf8, &self.input[start.utf8..end], rest, encoding)
+    }
+
+    /// An empty range at the byte `offset`, see [`line_col`](Self::line_col).
+    pub fn context(&self, offset: usize) -> Option<NodeContext> {
+        let (line, start) = self.line_at(offset, Encoding::Utf8)?;
+        let before = self.input.get(start.utf8..offset)?;
+        let context = NodeContext {
+            line: line as u64,
+            column: Encoding::Utf32.measure(before) as u64 + 1,
+            byte_column: before.len() as u64 + 1,
+            utf16_column: Encoding::Utf16.measure(before) as u64 + 1,
+            index: offset as u64,
+            char_index: (start.utf32 + Encoding::Utf32.measure(before)) as u64,
+            utf16_index: (start.utf16 + Encoding::Utf16.measure(before)) as u64,
+            ..NodeContext::default()
+        };
+        Some(context.until(context))
+    }
+
+    /// Number, starting from 1, and start of the line with the `offset` in `encoding`.
+    fn line_at(&self, offset: usize, encoding: Encoding) -> Option<(usize, LineStart)> {
+        if offset > self.len.get(encoding) {
+            return None;
+        }
+        let line = self
+            .lines
+            .partition_point(|start| start.get(encoding) <= offset);
+        Some((line, self.lines[line - 1]))
+    }
+}
+
+/// Byte offset after `count` units of `encoding` of `text`, which starts at the byte `start`.
+fn advance(start: usize, text: &str, count: usize, encoding: Encoding) -> Option<usize> {
+    let mut units = 0;
+    for (index, c) in text.char_indices() {
+        if units == count {
+            return Some(start + index);
+        }
+        units += encoding.len(c);
+    }
+    (units == count).then_some(start + text.len())
+}
@@ -3,6 +3,10 @@ pub use crate::api::{
     LogicalNode, NodeContext, NumberNode, ObjectEntryNode, ObjectNode, ParseError,
     ParseErrorExpectation, TextNode,
 };
+use crate::{
+    api::DictionaryEntryKey,
+    path::{self, Container, EntryKey, Key},
+};
 
 pub trait EqIgnoreContext {
     fn eq_ignore_context(&self, other: &Self) -> bool;
@@ -89,4 +93,56 @@ impl AstNode {
             _ => panic!("Expected a list node"),
         }
     }
+
+    /// The value at `path`, see [`Did::get_path`](crate::parser::ast::Did::get_path).
+    ///
+    /// Nested values are shared through handles, so there is no mutable variant.
+    pub fn get_path(&self, path: &[Key<'_>]) -> Option<&AstNode> {
+        path.iter().try_fold(self, |node, &key| node.child(key))
+    }
+
+    /// The value at `pointer`, see [`Did::pointer`](crate::parser::ast::Did::pointer).
+    pub fn pointer(&self, pointer: &str) -> Option<&AstNode> {
+        path::segments(pointer)?
+            .iter()
+            .try_fold(self, |node, segment| {
+                let key = match node {
+                    AstNode::List(_) => Key::Index(path::index(segment)?),
+                    _ => Key::Name(segment),
+                };
+                node.child(key)
+            })
+    }
+
+    fn child(&self, key: Key<'_>) -> Option<&AstNode> {
+        let value = match self {
+            AstNode::Dictionary(node) => {
+                let position = path::position(
+                    Container::Dictionary,
+                    &node.entries,
+                    |entry| match &entry.key {
+                        DictionaryEntryKey::Number(key) => EntryKey::Number(&key.value),
+                        DictionaryEntryKey::Text(key) => EntryKey::Name(&key.value),
+                    },
+                    key,
+                )?;
+                &node.entries[position].value
+            }
+            AstNode::Object(node) => {
+                let position = path::position(
+                    Container::Object,
+                    &node.entries,
+                    |entry| EntryKey::Name(&entry.key.value),
+                    key,
+                )?;
+                &node.entries[position].value
+            }
+            AstNode::List(node) => match key {
+                Key::Index(index) => node.entries.get(index)?,
+                Key::Name(_) => return None,
+            },
+            _ => return None,
+        };
+        Some(value.as_ref())
+    }
 }
@@ -1,13 +1,163 @@
+use std::{borrow::Cow, ops::Range};
+
 use derive_more::{IsVariant, Unwrap};
 
-#[derive(Debug, Clone, Copy)]
+use super::{
+    escape::{unescape, EscapeError},
+    number::number_accessors,
+};
+use crate::line_index::LineIndex;
+
+/// Where a node is in the input.
+///
+/// Offsets and columns are given in UTF-8 bytes, Unicode scalar values and UTF-16 code
+/// units. Lines and columns start from 1, offsets from 0.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct NodeContext {
     pub line: u64,
+    /// Column in Unicode scalar values.
     pub column: u64,
+    /// Column in UTF-8 bytes.
+    pub byte_column: u64,
+    /// Column in UTF-16 code units.
+    pub utf16_column: u64,
+    /// Offset in UTF-8 bytes.
     pub index: u64,
+    /// Offset in Unicode scalar values.
+    pub char_index: u64,
+    /// Offset in UTF-16 code units.
+    pub utf16_index: u64,
+    /// Line of the end of the node.
+    pub end_line: u64,
+    /// Column right after the last character of the node, in Unicode scalar values.
+    pub end_column: u64,
+    /// Column right after the last character of the node, in UTF-8 bytes.
+    pub end_byte_column: u64,
+    /// Column right after the last character of the node, in UTF-16 code units.
+    pub end_utf16_column: u64,
+    /// Offset right after the node, in UTF-8 bytes.
+    pub end_index: u64,
+    /// Offset right after the node, in Unicode scalar values.
+    pub end_char_index: u64,
+    /// Offset right after the node, in UTF-16 code units.
+    pub end_utf16_index: u64,
+}
+
+impl NodeContext {
+    const START: Self = Self {
+        line: 1,
+        column: 1,
+        byte_column: 1,
+        utf16_column: 1,
+        index: 0,
+        char_index: 0,
+        utf16_index: 0,
+        end_line: 1,
+        end_column: 1,
+        end_byte_column: 1,
+        end_utf16_column: 1,
+        end_index: 0,
+        end_char_index: 0,
+        end_utf16_index: 0,
+    };
+
+    /// An empty range at the byte `index` of `input`.
+    ///
+    /// Use a [`LineIndex`] for many positions in one input.
+    ///
+    /// # Panics
+    ///
+    /// If `index` is past the end of `input` or inside a character.
+    pub fn at(input: &str, index: usize) -> Self {
+        LineIndex::new(input)
+            .context(index)
+            .expect("the index is at a character of the input")
+    }
+
+    /// An empty range at the end of `self`.
+    pub const fn end(self) -> Self {
+        Self {
+            line: self.end_line,
+            column: self.end_column,
+            byte_column: self.end_byte_column,
+            utf16_column: self.end_utf16_column,
+            index: self.end_index,
+            char_index: self.end_char_index,
+            utf16_index: self.end_utf16_index,
+            ..self
+        }
+    }
+
+    /// The start of `self` with the start of `end` as the end.
+    pub(crate) const fn until(self, end: NodeContext) -> Self {
+        Self {
+            end_line: end.line,
+            end_column: end.column,
+            end_byte_column: end.byte_column,
+            end_utf16_column: end.utf16_column,
+            end_index: end.index,
+            end_char_index: end.char_index,
+            end_utf16_index: end.utf16_index,
+            ..self
+        }
+    }
+
+    /// The start of `self` with the end of `last` as the end.
+    pub(crate) const fn through(self, last: NodeContext) -> Self {
+        self.until(last.end())
+    }
+
+    /// The start of `self` with the end right after `text` that starts there.
+    pub(crate) fn spanning(self, text: &str) -> Self {
+        let mut end = self;
+        end.advance(text);
+        self.until(end)
+    }
+
+    /// Moves the start of `self` over `text` that starts there.
+    ///
+    /// Lines end with `\n`, `\r\n` or a lone `\r`, so `text` must not end between
+    /// the `\r` and the `\n` of a line break.
+    pub(crate) fn advance(&mut self, text: &str) {
+        let mut chars = text.chars().peekable();
+        while let Some(c) = chars.next() {
+            if c == '\n' || (c == '\r' && chars.peek() != Some(&'\n')) {
+                self.line += 1;
+                self.column = 1;
+                self.byte_column = 1;
+                self.utf16_column = 1;
+            } else {
+                self.column += 1;
+                self.byte_column += c.len_utf8() as u64;
+                self.utf16_column += c.len_utf16() as u64;
+            }
+            self.char_index += 1;
+            self.utf16_index += c.len_utf16() as u64;
+        }
+        self.index += text.len() as u64;
+    }
+
+    /// Byte range of the node in the input.
+    pub fn range(&self) -> Range<usize> {
+        self.index as usize..self.end_index as usize
+    }
+}
+
+impl Default for NodeContext {
+    fn default() -> Self {
+        Self::START
+    }
 }
 
+/// Syntax tree that does not borrow the input, see [`Did::into_owned`].
+pub type OwnedDid = Did<'static>;
+
+/// A value of a `Дід` document.
+///
+/// New kinds of nodes may be added, as [`Did::Error`] was in 0.4.0, so matches on it
+/// outside of this crate need a wildcard arm.
 #[derive(Debug, Clone, IsVariant, Unwrap)]
+#[non_exhaustive]
 pub enum Did<'inp> {
     Empty(EmptyNode),
     Logical(LogicalNode),
@@ -16,6 +166,180 @@ pub enum Did<'inp> {
     Dictionary(DictionaryNode<'inp>),
     Object(ObjectNode<'inp>),
     List(ListNode<'inp>),
+    /// Only in trees from [`parse_recovering`](super::parse_recovering).
+    Error(ErrorNode<'inp>),
+}
+
+impl Did<'_> {
+    pub const fn context(&self) -> NodeContext {
+        match self {
+            Did::Empty(node) => node.context,
+            Did::Logical(node) => node.context,
+            Did::Number(node) => node.context,
+            Did::Text(node) => node.context,
+            Did::Dictionary(node) => node.context,
+            Did::Object(node) => node.context,
+            Did::List(node) => node.context,
+            Did::Error(node) => node.context,
+        }
+    }
+
+    /// Copies every borrowed number and text, so the tree no longer depends on the input.
+    pub fn into_owned(self) -> Did<'static> {
+        // Lists, dictionaries and objects being copied, innermost last, so that deep
+        // nesting does not take stack.
+        let mut frames: Vec<OwnedFrame<'_>> = Vec::new();
+        let mut value = self;
+        loop {
+            let mut owned = match value {
+                Did::Empty(node) => Some(Did::Empty(node)),
+                Did::Logical(node) => Some(Did::Logical(node)),
+                Did::Number(node) => Some(Did::Number(node.into_owned())),
+                Did::Text(node) => Some(Did::Text(node.into_owned())),
+                Did::Error(node) => Some(Did::Error(node.into_owned())),
+                Did::Dictionary(node) => {
+                    frames.push(OwnedFrame::Dictionary {
+                        node: DictionaryNode {
+                            entries: Vec::new(),
+                            context: node.context,
+                        },
+                        entries: node.entries.into_iter(),
+                        key: None,
+                    });
+                    None
+                }
+                Did::Object(node) => {
+                    frames.push(OwnedFrame::Object {
+                        node: ObjectNode {
+                            name: node.name.into_owned(),
+                            entries: Vec::new(),
+                            context: node.context,
+                        },
+                        entries: node.entries.into_iter(),
+                        key: None,
+                    });
+                    None
+                }
+                Did::List(node) => {
+                    frames.push(OwnedFrame::List {
+                        node: ListNode {
+                            entries: Vec::new(),
+                            context: node.context,
+                        },
+                        entries: node.entries.into_iter(),
+                    });
+                    None
+                }
+            };
+            loop {
+                let Some(frame) = frames.last_mut() else {
+                    return owned.expect("the root is copied last");
+                };
+                if let Some(owned) = owned.take() {
+                    frame.push(owned);
+                }
+                match frame.next() {
+                    Some(next) => {
+                        value = next;
+                        break;
+                    }
+                    None => owned = frames.pop().map(OwnedFrame::finish),
+                }
+            }
+        }
+    }
+
+    /// Drops the tree without recursion.
+    ///
+    /// Dropping a `Did` as usual recurses once per level of nesting, which is fine for
+    /// trees parsed within [`ParseOptions::max_depth`]. Deeper trees, such as ones built
+    /// by hand or parsed with a large limit, can overflow the stack unless dropped by this.
+    ///
+    /// [`ParseOptions::max_depth`]: super::ParseOptions::max_depth
+    pub fn drop_deep(self) {
+        let mut stack = vec![self];
+        while let Some(value) = stack.pop() {
+            match value {
+                Did::Dictionary(node) => {
+                    stack.extend(node.entries.into_iter().map(|entry| entry.value))
+                }
+                Did::Object(node) => {
+                    stack.extend(node.entries.into_iter().map(|entry| entry.value))
+                }
+                Did::List(node) => stack.extend(node.entries),
+                _ => {}
+            }
+        }
+    }
+}
+
+/// A list, dictionary or object that [`Did::into_owned`] is copying.
+enum OwnedFrame<'inp> {
+    Dictionary {
+        node: DictionaryNode<'static>,
+        entries: std::vec::IntoIter<DictionaryEntryNode<'inp>>,
+        /// The key and context of the entry whose value is being copied.
+        key: Option<(DictionaryEntryKey<'static>, NodeContext)>,
+    },
+    Object {
+        node: ObjectNode<'static>,
+        entries: std::vec::IntoIter<ObjectEntryNode<'inp>>,
+        key: Option<(TextNode<'static>, NodeContext)>,
+    },
+    List {
+        node: ListNode<'static>,
+        entries: std::vec::IntoIter<Did<'inp>>,
+    },
+}
+
+impl<'inp> OwnedFrame<'inp> {
+    /// The next value to copy, remembering the key of its entry.
+    fn next(&mut self) -> Option<Did<'inp>> {
+        match self {
+            OwnedFrame::Dictionary { entries, key, .. } => {
+                let entry = entries.next()?;
+                *key = Some((entry.key.into_owned(), entry.context));
+                Some(entry.value)
+            }
+            OwnedFrame::Object { entries, key, .. } => {
+                let entry = entries.next()?;
+                *key = Some((entry.key.into_owned(), entry.context));
+                Some(entry.value)
+            }
+            OwnedFrame::List { entries, .. } => entries.next(),
+        }
+    }
+
+    /// Adds the copied value of the current entry.
+    fn push(&mut self, value: Did<'static>) {
+        match self {
+            OwnedFrame::Dictionary { node, key, .. } => {
+                let (key, context) = key.take().expect("an entry is being copied");
+                node.entries.push(DictionaryEntryNode {
+                    key,
+                    value,
+                    context,
+                });
+            }
+            OwnedFrame::Object { node, key, .. } => {
+                let (key, context) = key.take().expect("an entry is being copied");
+                node.entries.push(ObjectEntryNode {
+                    key,
+                    value,
+                    context,
+                });
+            }
+            OwnedFrame::List { node, .. } => node.entries.push(value),
+        }
+    }
+
+    fn finish(self) -> Did<'static> {
+        match self {
+            OwnedFrame::Dictionary { node, .. } => Did::Dictionary(node),
+            OwnedFrame::Object { node, .. } => Did::Object(node),
+            OwnedFrame::List { node, .. } => Did::List(node),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -31,22 +355,74 @@ pub struct LogicalNode {
 
 #[derive(Debug, Clone)]
 pub struct NumberNode<'inp> {
-    pub value: &'inp str,
+    pub value: Cow<'inp, str>,
     pub context: NodeContext,
 }
 
+impl NumberNode<'_> {
+    pub fn into_owned(self) -> NumberNode<'static> {
+        NumberNode {
+            value: Cow::Owned(self.value.into_owned()),
+            context: self.context,
+        }
+    }
+
+    number_accessors!();
+}
+
 #[derive(Debug, Clone)]
 pub struct TextNode<'inp> {
-    pub value: &'inp str,
+    /// Text as written between the quotes, see [`TextNode::unescape`].
+    pub value: Cow<'inp, str>,
     pub context: NodeContext,
 }
 
+impl<'inp> TextNode<'inp> {
+    pub fn into_owned(self) -> TextNode<'static> {
+        TextNode {
+            value: Cow::Owned(self.value.into_owned()),
+            context: self.context,
+        }
+    }
+
+    /// Text with its escape sequences replaced by the characters they stand for.
+    ///
+    /// Parsing checks the escape sequences, so only nodes built by hand can fail.
+    pub fn unescape(&self) -> Result<Cow<'_, str>, EscapeError> {
+        unescape(&self.value, self.context)
+    }
+
+    /// Like [`TextNode::unescape`], but keeps borrowing the input when possible.
+    pub fn into_unescaped(self) -> Result<Cow<'inp, str>, EscapeError> {
+        match self.value {
+            Cow::Borrowed(raw) => unescape(raw, self.context),
+            Cow::Owned(raw) => match unescape(&raw, self.context)? {
+                Cow::Borrowed(_) => Ok(Cow::Owned(raw)),
+                Cow::Owned(value) => Ok(Cow::Owned(value)),
+            },
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct DictionaryNode<'inp> {
     pub entries: Vec<DictionaryEntryNode<'inp>>,
     pub context: NodeContext,
 }
 
+impl DictionaryNode<'_> {
+    pub fn into_owned(self) -> DictionaryNode<'static> {
+        DictionaryNode {
+            entries: self
+                .entries
+                .into_iter()
+                .map(DictionaryEntryNode::into_owned)
+                .collect(),
+            context: self.context,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct DictionaryEntryNode<'inp> {
     pub key: DictionaryEntryKey<'inp>,
@@ -54,12 +430,38 @@ pub struct DictionaryEntryNode<'inp> {
     pub context: NodeContext,
 }
 
+impl DictionaryEntryNode<'_> {
+    pub fn into_owned(self) -> DictionaryEntryNode<'static> {
+        DictionaryEntryNode {
+            key: self.key.into_owned(),
+            value: self.value.into_owned(),
+            context: self.context,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum DictionaryEntryKey<'inp> {
     Number(NumberNode<'inp>),
     Text(TextNode<'inp>),
 }
 
+impl DictionaryEntryKey<'_> {
+    pub const fn context(&self) -> NodeContext {
+        match self {
+            DictionaryEntryKey::Number(node) => node.context,
+            DictionaryEntryKey::Text(node) => node.context,
+        }
+    }
+
+    pub fn into_owned(self) -> DictionaryEntryKey<'static> {
+        match self {
+            DictionaryEntryKey::Number(node) => DictionaryEntryKey::Number(node.into_owned()),
+            DictionaryEntryKey::Text(node) => DictionaryEntryKey::Text(node.into_owned()),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ObjectNode<'inp> {
     pub name: TextNode<'inp>,
@@ -67,6 +469,20 @@ pub struct ObjectNode<'inp> {
     pub context: NodeContext,
 }
 
+impl ObjectNode<'_> {
+    pub fn into_owned(self) -> ObjectNode<'static> {
+        ObjectNode {
+            name: self.name.into_owned(),
+            entries: self
+                .entries
+                .into_iter()
+                .map(ObjectEntryNode::into_owned)
+                .collect(),
+            context: self.context,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ObjectEntryNode<'inp> {
     pub key: TextNode<'inp>,
@@ -74,8 +490,44 @@ pub struct ObjectEntryNode<'inp> {
     pub context: NodeContext,
 }
 
+impl ObjectEntryNode<'_> {
+    pub fn into_owned(self) -> ObjectEntryNode<'static> {
+        ObjectEntryNode {
+            key: self.key.into_owned(),
+            value: self.value.into_owned(),
+            context: self.context,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct ListNode<'inp> {
     pub entries: Vec<Did<'inp>>,
     pub context: NodeContext,
 }
+
+impl ListNode<'_> {
+    pub fn into_owned(self) -> ListNode<'static> {
+        ListNode {
+            entries: self.entries.into_iter().map(Did::into_owned).collect(),
+            context: self.context,
+        }
+    }
+}
+
+/// Placeholder for text that could not be parsed.
+#[derive(Debug, Clone)]
+pub struct ErrorNode<'inp> {
+    /// The skipped text, without the trivia after it.
+    pub text: Cow<'inp, str>,
+    pub context: NodeContext,
+}
+
+impl ErrorNode<'_> {
+    pub fn into_owned(self) -> ErrorNode<'static> {
+        ErrorNode {
+            text: Cow::Owned(self.text.into_owned()),
+            context: self.context,
+        }
+    }
+}
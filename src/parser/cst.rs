@@ -0,0 +1,598 @@
+//! Concrete syntax tree, which keeps every character of the input: whitespace,
+//! comments, brackets, commas and equals signs.
+//!
+//! Writing the tree with [`Display`](std::fmt::Display) gives back the input byte for
+//! byte, and [`Document::into_did`] gives the typed tree [`Did`].
+
+use std::{borrow::Cow, fmt, mem, vec};
+
+use pest::iterators::{Pair, Pairs};
+
+use super::{
+    ast::{
+        DictionaryEntryKey, DictionaryEntryNode, DictionaryNode, Did, EmptyNode, ListNode,
+        LogicalNode, NodeContext, NumberNode, ObjectEntryNode, ObjectNode, TextNode,
+    },
+    Rule,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeKind {
+    /// The whole input: the root value with the trivia around it.
+    Document,
+    Empty,
+    Logical,
+    Number,
+    Text,
+    Dictionary,
+    DictionaryEntry,
+    Object,
+    ObjectEntry,
+    List,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    Whitespace,
+    /// `;; …` up to the end of the line.
+    LineComment,
+    /// `;;; … ;;;`
+    BlockComment,
+    /// `пусто`, `так` or `ні`.
+    Keyword,
+    Number,
+    /// A quoted text, including the quotes.
+    Text,
+    Identifier,
+    LeftParenthesis,
+    RightParenthesis,
+    LeftBracket,
+    RightBracket,
+    Comma,
+    EqualsSign,
+}
+
+impl TokenKind {
+    /// Whitespace and comments, which do not affect the meaning of a document.
+    pub fn is_trivia(self) -> bool {
+        matches!(
+            self,
+            Self::Whitespace | Self::LineComment | Self::BlockComment
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Token<'inp> {
+    pub kind: TokenKind,
+    pub text: Cow<'inp, str>,
+    pub context: NodeContext,
+}
+
+#[derive(Debug, Clone)]
+pub enum Element<'inp> {
+    Node(Node<'inp>),
+    Token(Token<'inp>),
+}
+
+/// Node of the concrete syntax tree.
+///
+/// Values (empty, logical, number and text) hold a single token. Keys of entries and
+/// object names are tokens of their entry or object.
+#[derive(Debug, Clone)]
+pub struct Node<'inp> {
+    pub kind: NodeKind,
+    pub children: Vec<Element<'inp>>,
+    pub context: NodeContext,
+}
+
+impl<'inp> Node<'inp> {
+    /// Child nodes, e.g. the entries of a dictionary or the value of an entry.
+    pub fn nodes(&self) -> impl Iterator<Item = &Node<'inp>> {
+        self.children.iter().filter_map(|element| match element {
+            Element::Node(node) => Some(node),
+            Element::Token(_) => None,
+        })
+    }
+
+    /// Child tokens, without the tokens of child nodes.
+    pub fn tokens(&self) -> impl Iterator<Item = &Token<'inp>> {
+        self.children.iter().filter_map(|element| match element {
+            Element::Node(_) => None,
+            Element::Token(token) => Some(token),
+        })
+    }
+
+    /// The first child token that is not trivia: the value of a leaf, the key of an entry
+    /// or the name of an object.
+    pub fn token(&self) -> Option<&Token<'inp>> {
+        self.tokens().find(|token| !token.kind.is_trivia())
+    }
+
+    /// Converts a value node into the typed tree.
+    ///
+    /// # Panics
+    ///
+    /// If the node is an entry, which is not a value.
+    pub fn into_did(self) -> Did<'inp> {
+        // Lists, dictionaries and objects being converted, innermost last, so that
+        // deep nesting does not take stack.
+        let mut frames: Vec<Frame<'inp>> = Vec::new();
+        let mut node = Some(self);
+        let mut value = None;
+        loop {
+            if let Some(mut next) = node.take() {
+                let context = next.context;
+                match next.kind {
+                    NodeKind::Document => {
+                        node = Some(next.into_nodes().next().expect("a document has a value"));
+                        continue;
+                    }
+                    NodeKind::Empty => value = Some(Did::Empty(EmptyNode { context })),
+                    NodeKind::Logical => {
+                        value = Some(Did::Logical(LogicalNode {
+                            value: next.into_token().text == "так",
+                            context,
+                        }))
+                    }
+                    NodeKind::Number => value = Some(Did::Number(next.into_token().into_number())),
+                    NodeKind::Text => value = Some(Did::Text(next.into_token().into_text())),
+                    NodeKind::Dictionary | NodeKind::Object | NodeKind::List => {
+                        let name = (next.kind == NodeKind::Object).then(|| {
+                            next.token()
+                                .expect("an object has a name")
+                                .clone()
+                                .into_text()
+                        });
+                        frames.push(Frame {
+                            kind: next.kind,
+                            context,
+                            name,
+                            children: mem::take(&mut next.children).into_iter(),
+                            keys: Vec::new(),
+                            values: Vec::new(),
+                        });
+                    }
+                    NodeKind::DictionaryEntry | NodeKind::ObjectEntry => {
+                        panic!("{:?} is not a value", next.kind)
+                    }
+                }
+            }
+            let Some(frame) = frames.last_mut() else {
+                return value.expect("a value is converted");
+            };
+            frame.values.extend(value.take());
+            match frame.next() {
+                Some(next) => node = Some(next),
+                None => value = frames.pop().map(Frame::finish),
+            }
+        }
+    }
+
+    pub fn into_owned(self) -> Node<'static> {
+        // Copied nodes with the children they have left to copy, innermost last, so that
+        // deep nesting does not take stack.
+        let mut frames: Vec<(Node<'static>, vec::IntoIter<Element<'inp>>)> = Vec::new();
+        let mut node = self;
+        loop {
+            let children = mem::take(&mut node.children).into_iter();
+            let owned = Node {
+                kind: node.kind,
+                children: Vec::new(),
+                context: node.context,
+            };
+            frames.push((owned, children));
+            loop {
+                let (owned, children) = frames.last_mut().expect("a node is being copied");
+                match children.next() {
+                    Some(Element::Token(token)) => {
+                        owned.children.push(Element::Token(token.into_owned()))
+                    }
+                    Some(Element::Node(next)) => {
+                        node = next;
+                        break;
+                    }
+                    None => {
+                        let (owned, _) = frames.pop().expect("a node is being copied");
+                        match frames.last_mut() {
+                            Some((parent, _)) => parent.children.push(Element::Node(owned)),
+                            None => return owned,
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn into_nodes(mut self) -> impl Iterator<Item = Node<'inp>> {
+        mem::take(&mut self.children)
+            .into_iter()
+            .filter_map(|element| match element {
+                Element::Node(node) => Some(node),
+                Element::Token(_) => None,
+            })
+    }
+
+    fn into_token(mut self) -> Token<'inp> {
+        mem::take(&mut self.children)
+            .into_iter()
+            .find_map(|element| match element {
+                Element::Token(token) if !token.kind.is_trivia() => Some(token),
+                _ => None,
+            })
+            .expect("a leaf has a token")
+    }
+
+    /// Key token and value node of an entry.
+    fn into_entry(mut self) -> (Token<'inp>, Node<'inp>) {
+        let mut key = None;
+        let mut value = None;
+        for element in mem::take(&mut self.children) {
+            match element {
+                Element::Token(token) if key.is_none() && !token.kind.is_trivia() => {
+                    key = Some(token)
+                }
+                Element::Node(node) => value = Some(node),
+                Element::Token(_) => {}
+            }
+        }
+        (
+            key.expect("an entry has a key"),
+            value.expect("an entry has a value"),
+        )
+    }
+}
+
+/// Nested nodes are dropped one by one, so that dropping a deep tree does not recurse.
+impl Drop for Node<'_> {
+    fn drop(&mut self) {
+        if self.nodes().all(|node| node.nodes().next().is_none()) {
+            return;
+        }
+        let mut stack = mem::take(&mut self.children);
+        while let Some(element) = stack.pop() {
+            if let Element::Node(mut node) = element {
+                stack.append(&mut node.children);
+            }
+        }
+    }
+}
+
+/// A list, dictionary or object that [`Node::into_did`] is converting.
+struct Frame<'inp> {
+    kind: NodeKind,
+    context: NodeContext,
+    /// The name of an object.
+    name: Option<TextNode<'inp>>,
+    /// The children that are not converted yet.
+    children: vec::IntoIter<Element<'inp>>,
+    /// Keys and contexts of the entries, for dictionaries and objects.
+    keys: Vec<(Token<'inp>, NodeContext)>,
+    values: Vec<Did<'inp>>,
+}
+
+impl<'inp> Frame<'inp> {
+    /// The next value to convert, remembering the key of its entry.
+    fn next(&mut self) -> Option<Node<'inp>> {
+        let node = self.children.find_map(|element| match element {
+            Element::Node(node) => Some(node),
+            Element::Token(_) => None,
+        })?;
+        if self.kind == NodeKind::List {
+            return Some(node);
+        }
+        let context = node.context;
+        let (key, value) = node.into_entry();
+        self.keys.push((key, context));
+        Some(value)
+    }
+
+    fn finish(self) -> Did<'inp> {
+        let context = self.context;
+        if self.kind == NodeKind::List {
+            return Did::List(ListNode {
+                entries: self.values,
+                context,
+            });
+        }
+        let entries = self.keys.into_iter().zip(self.values);
+        match self.kind {
+            NodeKind::Dictionary => Did::Dictionary(DictionaryNode {
+                entries: entries
+                    .map(|((key, context), value)| DictionaryEntryNode {
+                        key: match key.kind {
+                            TokenKind::Number => DictionaryEntryKey::Number(key.into_number()),
+                            _ => DictionaryEntryKey::Text(key.into_text()),
+                        },
+                        value,
+                        context,
+                    })
+                    .collect(),
+                context,
+            }),
+            _ => Did::Object(ObjectNode {
+                name: self.name.expect("an object has a name"),
+                entries: entries
+                    .map(|((key, context), value)| ObjectEntryNode {
+                        key: key.into_text(),
+                        value,
+                        context,
+                    })
+                    .collect(),
+                context,
+            }),
+        }
+    }
+}
+
+impl<'inp> Token<'inp> {
+    pub fn into_owned(self) -> Token<'static> {
+        Token {
+            kind: self.kind,
+            text: Cow::Owned(self.text.into_owned()),
+            context: self.context,
+        }
+    }
+
+    fn into_number(self) -> NumberNode<'inp> {
+        NumberNode {
+            value: self.text,
+            context: self.context,
+        }
+    }
+
+    /// Text of an identifier, or the text between the quotes.
+    fn into_text(self) -> TextNode<'inp> {
+        let value = match (self.kind, self.text) {
+            (TokenKind::Text, Cow::Borrowed(text)) => Cow::Borrowed(&text[1..text.len() - 1]),
+            (TokenKind::Text, Cow::Owned(text)) => Cow::Owned(text[1..text.len() - 1].to_owned()),
+            (_, text) => text,
+        };
+        TextNode {
+            value,
+            context: self.context,
+        }
+    }
+}
+
+/// A parsed document that keeps all of its source text.
+#[derive(Debug, Clone)]
+pub struct Document<'inp> {
+    root: Node<'inp>,
+}
+
+impl<'inp> Document<'inp> {
+    /// The [`NodeKind::Document`] node.
+    pub fn root(&self) -> &Node<'inp> {
+        &self.root
+    }
+
+    /// The node of the document's value.
+    pub fn value(&self) -> &Node<'inp> {
+        self.root.nodes().next().expect("a document has a value")
+    }
+
+    pub fn to_did(&self) -> Did<'inp> {
+        self.clone().into_did()
+    }
+
+    pub fn into_did(self) -> Did<'inp> {
+        self.root.into_did()
+    }
+
+    pub fn into_owned(self) -> Document<'static> {
+        Document {
+            root: self.root.into_owned(),
+        }
+    }
+
+    pub(crate) fn root_mut(&mut self) -> &mut Node<'inp> {
+        &mut self.root
+    }
+}
+
+impl fmt::Display for Token<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+impl fmt::Display for Node<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for element in &self.children {
+            match element {
+                Element::Node(node) => node.fmt(f)?,
+                Element::Token(token) => token.fmt(f)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Document<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.root.fmt(f)
+    }
+}
+
+/// Builds the tree from the pairs of a successful parse.
+///
+/// Trivia is not in the pairs, so it is lexed from the gaps between them.
+pub(crate) struct Builder<'inp> {
+    input: &'inp str,
+    cursor: NodeContext,
+}
+
+impl<'inp> Builder<'inp> {
+    pub(crate) fn new(input: &'inp str) -> Self {
+        Self {
+            input,
+            cursor: NodeContext::default(),
+        }
+    }
+
+    pub(crate) fn document(mut self, value: Pair<'inp, Rule>) -> Document<'inp> {
+        let mut children = Vec::new();
+        self.gap(value.as_span().start(), &mut children);
+        children.push(Element::Node(self.node(value)));
+        self.gap(self.input.len(), &mut children);
+        Document {
+            root: Node {
+                kind: NodeKind::Document,
+                children,
+                context: NodeContext::default().until(self.cursor),
+            },
+        }
+    }
+
+    fn node(&mut self, pair: Pair<'inp, Rule>) -> Node<'inp> {
+        // Nodes being built, innermost last, so that deep nesting does not take stack.
+        let mut frames = vec![self.open(pair)];
+        loop {
+            let frame = frames.last_mut().expect("the outermost node is built last");
+            match frame.pairs.as_mut().and_then(Iterator::next) {
+                Some(inner) => {
+                    self.gap(inner.as_span().start(), &mut frame.children);
+                    if mem::take(&mut frame.key) || is_punctuation(inner.as_rule()) {
+                        let token = self.token_of(&inner);
+                        frame.children.push(Element::Token(token));
+                    } else {
+                        let frame = self.open(inner);
+                        frames.push(frame);
+                    }
+                }
+                None => {
+                    let mut frame = frames.pop().expect("the frame was just looked at");
+                    self.gap(frame.end, &mut frame.children);
+                    let node = Node {
+                        kind: frame.kind,
+                        children: frame.children,
+                        context: frame.context.until(self.cursor),
+                    };
+                    match frames.last_mut() {
+                        Some(parent) => parent.children.push(Element::Node(node)),
+                        None => return node,
+                    }
+                }
+            }
+        }
+    }
+
+    /// Starts building the node of `pair`; leaves are complete right away.
+    fn open(&mut self, pair: Pair<'inp, Rule>) -> Open<'inp> {
+        let context = self.cursor;
+        let end = pair.as_span().end();
+        let kind = match pair.as_rule() {
+            Rule::empty => NodeKind::Empty,
+            Rule::logical => NodeKind::Logical,
+            Rule::number => NodeKind::Number,
+            Rule::text => NodeKind::Text,
+            Rule::dict => NodeKind::Dictionary,
+            Rule::dict_entry => NodeKind::DictionaryEntry,
+            Rule::object => NodeKind::Object,
+            Rule::object_entry => NodeKind::ObjectEntry,
+            Rule::list => NodeKind::List,
+            rule => unreachable!("{rule:?} {:?}", pair.as_str()),
+        };
+
+        let mut children = Vec::new();
+        let pairs = match kind {
+            NodeKind::Empty | NodeKind::Logical | NodeKind::Number | NodeKind::Text => {
+                children.push(Element::Token(self.token_of(&pair)));
+                None
+            }
+            _ => Some(pair.into_inner()),
+        };
+        Open {
+            kind,
+            context,
+            end,
+            children,
+            pairs,
+            // Keys of entries and names of objects come first and are tokens.
+            key: matches!(
+                kind,
+                NodeKind::DictionaryEntry | NodeKind::ObjectEntry | NodeKind::Object
+            ),
+        }
+    }
+
+    fn token_of(&mut self, pair: &Pair<'inp, Rule>) -> Token<'inp> {
+        let kind = match pair.as_rule() {
+            Rule::empty | Rule::logical => TokenKind::Keyword,
+            Rule::number => TokenKind::Number,
+            Rule::text => TokenKind::Text,
+            Rule::ident => TokenKind::Identifier,
+            Rule::left_paren => TokenKind::LeftParenthesis,
+            Rule::right_paren => TokenKind::RightParenthesis,
+            Rule::left_bracket => TokenKind::LeftBracket,
+            Rule::right_bracket => TokenKind::RightBracket,
+            Rule::comma => TokenKind::Comma,
+            Rule::equals => TokenKind::EqualsSign,
+            rule => unreachable!("{rule:?} {:?}", pair.as_str()),
+        };
+        self.token(kind, pair.as_span().end())
+    }
+
+    /// Lexes the trivia up to `end`.
+    fn gap(&mut self, end: usize, children: &mut Vec<Element<'inp>>) {
+        while (self.cursor.index as usize) < end {
+            let rest = &self.input[self.cursor.index as usize..end];
+            let (kind, len) = lex_gap(rest);
+            let end = self.cursor.index as usize + len;
+            children.push(Element::Token(self.token(kind, end)));
+        }
+    }
+
+    fn token(&mut self, kind: TokenKind, end: usize) -> Token<'inp> {
+        let text = &self.input[self.cursor.index as usize..end];
+        let context = self.cursor;
+        self.cursor.advance(text);
+        Token {
+            kind,
+            text: Cow::Borrowed(text),
+            context: context.until(self.cursor),
+        }
+    }
+}
+
+/// A node that [`Builder`] has started building.
+struct Open<'inp> {
+    kind: NodeKind,
+    context: NodeContext,
+    end: usize,
+    children: Vec<Element<'inp>>,
+    /// The inner pairs left to build, for nodes that are not leaves.
+    pairs: Option<Pairs<'inp, Rule>>,
+    /// Whether the next inner pair is a key or a name.
+    key: bool,
+}
+
+/// Kind and length of the trivia token at the start of a gap between pairs.
+fn lex_gap(rest: &str) -> (TokenKind, usize) {
+    if let Some(comment) = rest.strip_prefix(";;;") {
+        let len = comment.find(";;;").expect("checked by the grammar") + 6;
+        return (TokenKind::BlockComment, len);
+    }
+    if rest.starts_with(";;") {
+        let len = rest.find(['\n', '\r']).unwrap_or(rest.len());
+        return (TokenKind::LineComment, len);
+    }
+    let whitespace = rest
+        .find(|c| !matches!(c, ' ' | '\t' | '\n' | '\r'))
+        .unwrap_or(rest.len());
+    assert!(whitespace > 0, "unexpected {rest:?} between pairs");
+    (TokenKind::Whitespace, whitespace)
+}
+
+fn is_punctuation(rule: Rule) -> bool {
+    matches!(
+        rule,
+        Rule::left_paren
+            | Rule::right_paren
+            | Rule::left_bracket
+            | Rule::right_bracket
+            | Rule::comma
+            | Rule::equals
+    )
+}
@@ -0,0 +1,147 @@
+//! Finding repeated keys of dictionaries and objects.
+//!
+//! Keys are compared by value: the numbers `1` and `1.0` are the same, as are the texts
+//! `а` and `"а"`.
+
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+    fmt,
+};
+
+use super::{ast::*, escape::unescape, number::Decimal};
+
+/// What to do with the entries of a dictionary or an object that have the same key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DuplicateKeys {
+    /// Keep all of the entries.
+    #[default]
+    Allow,
+    /// Fail the parsing.
+    Reject,
+    /// Keep only the last of the entries, with a [`Warning`] for each of the others.
+    ///
+    /// Only the functions whose names end with `_with_warnings` return the warnings.
+    LastWins,
+}
+
+/// A problem in a valid document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Warning {
+    /// The entry at `first` is dropped, since its key is repeated at `key`.
+    DuplicateKey {
+        first: NodeContext,
+        key: NodeContext,
+    },
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DuplicateKey { first, key } => write!(
+                f,
+                "Ключ повторюється, тож попередній запис відкинуто \
+                 (рядок: {}, стовпчик: {}; попередній запис: рядок: {}, стовпчик: {})",
+                key.line, key.column, first.line, first.column
+            ),
+        }
+    }
+}
+
+/// A key as it is compared.
+#[derive(PartialEq, Eq, Hash)]
+enum Key<'a> {
+    Number(Decimal),
+    /// A number that is not a valid decimal, compared as written.
+    Literal(&'a str),
+    Text(Cow<'a, str>),
+}
+
+impl<'a> Key<'a> {
+    fn of_number(node: &'a NumberNode<'_>) -> Self {
+        node.as_decimal()
+            .map_or(Self::Literal(&node.value), Self::Number)
+    }
+
+    fn of_text(node: &'a TextNode<'_>) -> Self {
+        // Parsed texts have valid escape sequences, a text built by hand with
+        // an invalid one is compared as written.
+        Self::Text(unescape(&node.value, node.context).unwrap_or(Cow::Borrowed(&node.value)))
+    }
+}
+
+/// Contexts of the keys of the entries that are repeated later, paired with the context
+/// of the key of the next entry with the same key, in the order of the input.
+pub(crate) fn find(did: &Did<'_>) -> Vec<(NodeContext, NodeContext)> {
+    let mut duplicates = Vec::new();
+    let mut stack = vec![did];
+    while let Some(did) = stack.pop() {
+        match did {
+            Did::Dictionary(node) => {
+                duplicates.extend(pairs(node.entries.iter().map(|entry| {
+                    let key = match &entry.key {
+                        DictionaryEntryKey::Number(key) => Key::of_number(key),
+                        DictionaryEntryKey::Text(key) => Key::of_text(key),
+                    };
+                    (key, entry.key.context())
+                })));
+                stack.extend(node.entries.iter().rev().map(|entry| &entry.value));
+            }
+            Did::Object(node) => {
+                duplicates.extend(pairs(
+                    node.entries
+                        .iter()
+                        .map(|entry| (Key::of_text(&entry.key), entry.key.context)),
+                ));
+                stack.extend(node.entries.iter().rev().map(|entry| &entry.value));
+            }
+            Did::List(node) => stack.extend(node.entries.iter().rev()),
+            _ => {}
+        }
+    }
+    duplicates.sort_by_key(|(_, key)| key.index);
+    duplicates
+}
+
+fn pairs<'a>(
+    keys: impl Iterator<Item = (Key<'a>, NodeContext)>,
+) -> Vec<(NodeContext, NodeContext)> {
+    let mut previous = HashMap::new();
+    let mut pairs = Vec::new();
+    for (key, context) in keys {
+        if let Some(first) = previous.insert(key, context) {
+            pairs.push((first, context));
+        }
+    }
+    pairs
+}
+
+/// Drops the entries whose keys are repeated later, returning a warning for each.
+pub(crate) fn keep_last(did: &mut Did<'_>) -> Vec<Warning> {
+    let duplicates = find(did);
+    if duplicates.is_empty() {
+        return Vec::new();
+    }
+    let dropped: HashSet<_> = duplicates.iter().map(|(first, _)| first.index).collect();
+    let mut stack = vec![did];
+    while let Some(did) = stack.pop() {
+        match did {
+            Did::Dictionary(node) => {
+                node.entries
+                    .retain(|entry| !dropped.contains(&entry.key.context().index));
+                stack.extend(node.entries.iter_mut().map(|entry| &mut entry.value));
+            }
+            Did::Object(node) => {
+                node.entries
+                    .retain(|entry| !dropped.contains(&entry.key.context.index));
+                stack.extend(node.entries.iter_mut().map(|entry| &mut entry.value));
+            }
+            Did::List(node) => stack.extend(node.entries.iter_mut()),
+            _ => {}
+        }
+    }
+    duplicates
+        .into_iter()
+        .map(|(first, key)| Warning::DuplicateKey { first, key })
+        .collect()
+}
@@ -0,0 +1,134 @@
+//! Decoding the escape sequences of text nodes.
+
+use std::{borrow::Cow, fmt};
+
+use super::ast::NodeContext;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EscapeError {
+    pub kind: EscapeErrorKind,
+    /// Position of the backslash that starts the bad escape sequence.
+    pub context: NodeContext,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EscapeErrorKind {
+    /// `\` followed by a character that does not start an escape sequence.
+    UnknownEscape(char),
+    /// `\` at the very end of the text.
+    TrailingBackslash,
+    /// `\u` not followed by four hexadecimal digits.
+    InvalidUnicodeEscape,
+    /// `\uXXXX` with a surrogate code unit that has no pair.
+    UnpairedSurrogate(u16),
+}
+
+impl fmt::Display for EscapeErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::UnknownEscape(c) => write!(f, "Невідома escape-послідовність `\\{c}`"),
+            Self::TrailingBackslash => write!(f, "Незавершена escape-послідовність `\\`"),
+            Self::InvalidUnicodeEscape => {
+                write!(f, "Після `\\u` очікувалися чотири шістнадцяткові цифри")
+            }
+            Self::UnpairedSurrogate(unit) => {
+                write!(f, "Сурогат `\\u{unit:04X}` не має пари")
+            }
+        }
+    }
+}
+
+impl fmt::Display for EscapeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} (рядок: {}, стовпчик: {})",
+            self.kind, self.context.line, self.context.column
+        )
+    }
+}
+
+impl std::error::Error for EscapeError {}
+
+/// Replaces the escape sequences of a text as written between the quotes.
+///
+/// `context` is the position of the opening quote; it is used to point errors
+/// at the exact escape sequence. Borrows `raw` if it has no escape sequences.
+pub fn unescape(raw: &str, context: NodeContext) -> Result<Cow<'_, str>, EscapeError> {
+    // The position is only measured for an error, which ends the decoding, so
+    // decoding stays linear in the length of the text.
+    decode(raw).map_err(|(start, kind)| {
+        let mut position = context;
+        position.advance("\"");
+        position.advance(&raw[..start]);
+        EscapeError {
+            kind,
+            context: position.until(position),
+        }
+    })
+}
+
+/// Like [`unescape`], but fails with the byte offset in `raw` of the bad escape sequence.
+pub(crate) fn decode(raw: &str) -> Result<Cow<'_, str>, (usize, EscapeErrorKind)> {
+    let Some(first) = raw.find('\\') else {
+        return Ok(Cow::Borrowed(raw));
+    };
+    let mut out = String::with_capacity(raw.len());
+    out.push_str(&raw[..first]);
+
+    let mut rest = &raw[first..];
+    while let Some(pos) = rest.find('\\') {
+        out.push_str(&rest[..pos]);
+        let start = raw.len() - rest.len() + pos;
+        let error = |kind| (start, kind);
+
+        let mut chars = rest[pos + 1..].chars();
+        let decoded = match chars.next() {
+            Some('"') => '"',
+            Some('\\') => '\\',
+            Some('b') => '\u{8}',
+            Some('f') => '\u{c}',
+            Some('n') => '\n',
+            Some('r') => '\r',
+            Some('t') => '\t',
+            Some('u') => {
+                let unit = hex_unit(chars.as_str())
+                    .ok_or_else(|| error(EscapeErrorKind::InvalidUnicodeEscape))?;
+                chars = chars.as_str()[4..].chars();
+                match unit {
+                    0xD800..=0xDBFF => {
+                        let low = chars
+                            .as_str()
+                            .strip_prefix("\\u")
+                            .and_then(hex_unit)
+                            .filter(|low| (0xDC00..=0xDFFF).contains(low))
+                            .ok_or_else(|| error(EscapeErrorKind::UnpairedSurrogate(unit)))?;
+                        chars = chars.as_str()[6..].chars();
+                        let code = 0x10000
+                            + ((u32::from(unit) - 0xD800) << 10)
+                            + (u32::from(low) - 0xDC00);
+                        char::from_u32(code).expect("a surrogate pair is a valid scalar value")
+                    }
+                    0xDC00..=0xDFFF => return Err(error(EscapeErrorKind::UnpairedSurrogate(unit))),
+                    unit => char::from_u32(unit.into()).expect("not a surrogate"),
+                }
+            }
+            Some(c) => return Err(error(EscapeErrorKind::UnknownEscape(c))),
+            None => return Err(error(EscapeErrorKind::TrailingBackslash)),
+        };
+        out.push(decoded);
+        rest = chars.as_str();
+    }
+    out.push_str(rest);
+    Ok(Cow::Owned(out))
+}
+
+/// Reads the four hexadecimal digits at the start of `s`.
+fn hex_unit(s: &str) -> Option<u16> {
+    let hex = s.get(..4)?;
+    if hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        u16::from_str_radix(hex, 16).ok()
+    } else {
+        None
+    }
+}
@@ -1,248 +1,312 @@
 pub mod ast;
+pub mod cst;
+mod duplicates;
+pub mod escape;
+pub mod number;
+mod recover;
 
-use pest::error::Error;
-use pest::iterators::Pair;
-use pest::Parser;
-use pest_derive::Parser;
+use pest::{iterators::Pair, Parser};
 
-#[cfg(not(feature = "wasm"))]
 use self::ast::*;
+pub use self::duplicates::{DuplicateKeys, Warning};
+pub(crate) use self::grammar::Rule;
+pub(crate) use self::recover::unclosed;
+pub use self::recover::{parse_recovering, parse_recovering_with, parse_recovering_with_warnings};
+use crate::{error::Reason, line_index::LineIndex, Error};
 
-#[derive(Parser)]
-#[grammar = "parser/grammar.pest"]
-struct DidParser;
+/// The grammar is kept private, so that its rules do not become a part of the API.
+mod grammar {
+    use pest_derive::Parser;
 
-#[cfg(not(feature = "wasm"))]
-pub fn parse(input: &str) -> Result<Did<'_>, Error<Rule>> {
-    let input = DidParser::parse(Rule::did, input)?.next().unwrap();
-
-    fn ast_node(pair: Pair<'_, Rule>) -> Did<'_> {
-        let (line, col) = pair.line_col();
-        let context = NodeContext {
-            line: line as u64,
-            column: col as u64,
-            index: pair.as_span().start() as u64,
-        };
-        match pair.as_rule() {
-            Rule::empty => Did::Empty(EmptyNode { context }),
-            Rule::logical => Did::Logical(LogicalNode {
-                value: match pair.as_str() {
-                    "так" => true,
-                    "ні" => false,
-                    _ => unreachable!(),
-                },
-                context,
-            }),
-            Rule::number => Did::Number(NumberNode {
-                value: pair.as_str(),
-                context,
-            }),
-            Rule::text => Did::Text(TextNode {
-                value: pair.into_inner().next().unwrap().as_str(),
-                context,
-            }),
-            Rule::dict => Did::Dictionary(DictionaryNode {
-                entries: pair
-                    .into_inner()
-                    .map(|pair| {
-                        let mut inner_rules = pair.into_inner();
-                        let key_pair = inner_rules.next().unwrap();
-                        let (line, col) = key_pair.line_col();
-                        let key_context = NodeContext {
-                            line: line as u64,
-                            column: col as u64,
-                            index: key_pair.as_span().start() as u64,
-                        };
-                        let key = match key_pair.as_rule() {
-                            Rule::number => DictionaryEntryKey::Number(NumberNode {
-                                value: key_pair.as_str(),
-                                context: key_context,
-                            }),
-                            Rule::text => DictionaryEntryKey::Text(TextNode {
-                                value: key_pair.into_inner().next().unwrap().as_str(),
-                                context: key_context,
-                            }),
-                            Rule::ident => DictionaryEntryKey::Text(TextNode {
-                                value: key_pair.as_str(),
-                                context: key_context,
-                            }),
-                            rule => unreachable!("{rule:?} {:?}", key_pair.as_str()),
-                        };
-                        let value = ast_node(inner_rules.next().unwrap());
-                        DictionaryEntryNode {
-                            context: key_context,
-                            key,
-                            value,
-                        }
-                    })
-                    .collect(),
-                context,
-            }),
-            Rule::object => Did::Object({
-                let mut inner_rules = pair.into_inner();
-                let name: &str = inner_rules.next().unwrap().as_str();
-                ObjectNode {
-                    name: TextNode {
-                        value: name,
-                        context,
-                    },
-                    entries: inner_rules
-                        .map(|pair| {
-                            let mut inner_rules = pair.into_inner();
-                            let key_pair = inner_rules.next().unwrap();
-                            let (line, col) = key_pair.line_col();
-                            let key_context = NodeContext {
-                                line: line as u64,
-                                column: col as u64,
-                                index: key_pair.as_span().start() as u64,
-                            };
-                            let key = match key_pair.as_rule() {
-                                Rule::ident => TextNode {
-                                    value: key_pair.as_str(),
-                                    context: key_context,
-                                },
-                                _ => unreachable!(),
-                            };
-                            let value = ast_node(inner_rules.next().unwrap());
-                            ObjectEntryNode {
-                                context: key_context,
-                                key,
-                                value,
-                            }
-                        })
-                        .collect(),
-                    context,
-                }
-            }),
-            Rule::list => Did::List(ListNode {
-                entries: pair.into_inner().map(ast_node).collect(),
-                context,
-            }),
-            rule => unreachable!("{rule:?} {:?}", pair.as_str()),
+    #[derive(Parser)]
+    #[grammar = "parser/grammar.pest"]
+    pub(crate) struct DidParser;
+}
+
+/// Settings of parsing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseOptions {
+    /// The most lists, dictionaries and objects that can be nested in one another.
+    ///
+    /// The grammar, the recovering parser, dropping a `Did` and the functions that walk it,
+    /// such as the visitors and the formatter, recurse once per level, so this keeps them
+    /// from overflowing the stack on hostile input. Large limits may still overflow it,
+    /// especially on threads with small stacks; see [`Did::drop_deep`] for dropping deep
+    /// trees. Building and converting trees take no stack per level.
+    ///
+    /// [`Did::drop_deep`]: ast::Did::drop_deep
+    pub max_depth: usize,
+    /// The longest input, in bytes.
+    pub max_input_len: usize,
+    /// The most values in the document, nested ones included and keys not.
+    pub max_nodes: usize,
+    /// The longest text, in bytes as written between the quotes.
+    pub max_text_len: usize,
+    /// The most entries of one list, dictionary or object.
+    pub max_entries: usize,
+    /// What to do with the entries of a dictionary or an object that have the same key.
+    ///
+    /// Concrete syntax trees keep all of their entries, so only rejecting applies to them.
+    pub duplicate_keys: DuplicateKeys,
+}
+
+/// Only the depth is limited by default.
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            max_depth: 128,
+            max_input_len: usize::MAX,
+            max_nodes: usize::MAX,
+            max_text_len: usize::MAX,
+            max_entries: usize::MAX,
+            duplicate_keys: DuplicateKeys::Allow,
         }
     }
+}
+
+#[cfg(not(feature = "wasm"))]
+pub fn parse(input: &str) -> Result<Did<'_>, Error> {
+    parse_did(input)
+}
+
+/// Text values of the returned tree have their escape sequences decoded.
+#[cfg(feature = "wasm")]
+pub fn parse(input: &str) -> Result<crate::api::AstNode, Error> {
+    parse_with(input, &ParseOptions::default())
+}
 
-    Ok(ast_node(input))
+/// Warnings are discarded, see [`parse_with_warnings`].
+#[cfg(not(feature = "wasm"))]
+pub fn parse_with<'inp>(input: &'inp str, options: &ParseOptions) -> Result<Did<'inp>, Error> {
+    parse_did_with(input, options)
 }
 
+/// Text values of the returned tree have their escape sequences decoded.
+///
+/// Warnings are discarded, see [`parse_with_warnings`].
 #[cfg(feature = "wasm")]
-use crate::api::*;
+pub fn parse_with(input: &str, options: &ParseOptions) -> Result<crate::api::AstNode, Error> {
+    parse_with_warnings(input, options).map(|(root, _)| root)
+}
+
+#[cfg(not(feature = "wasm"))]
+pub fn parse_with_warnings<'inp>(
+    input: &'inp str,
+    options: &ParseOptions,
+) -> Result<(Did<'inp>, Vec<Warning>), Error> {
+    parse_did_with_warnings(input, options)
+}
 
+/// Text values of the returned tree have their escape sequences decoded.
 #[cfg(feature = "wasm")]
-pub fn parse(input: &str) -> Result<AstNode, Error<Rule>> {
-    use wai_bindgen_rust::Handle;
+pub fn parse_with_warnings(
+    input: &str,
+    options: &ParseOptions,
+) -> Result<(crate::api::AstNode, Vec<Warning>), Error> {
+    let (did, warnings) = parse_did_with_warnings(input, options)?;
+    let root = crate::api::AstNode::try_from(did)
+        .map_err(|err| Error::from_escape(&LineIndex::new(input), err))?;
+    Ok((root, warnings))
+}
 
-    use crate::BoxedAstNode;
+/// Parses `input` into the native syntax tree, regardless of the `wasm` feature.
+pub fn parse_did(input: &str) -> Result<Did<'_>, Error> {
+    parse_did_with(input, &ParseOptions::default())
+}
 
-    let input = DidParser::parse(Rule::did, input)?.next().unwrap();
+/// Warnings are discarded, see [`parse_did_with_warnings`].
+pub fn parse_did_with<'inp>(input: &'inp str, options: &ParseOptions) -> Result<Did<'inp>, Error> {
+    parse_did_with_warnings(input, options).map(|(did, _)| did)
+}
 
-    fn ast_node(pair: Pair<'_, Rule>) -> AstNode {
-        let (line, col) = pair.line_col();
-        let context = NodeContext {
-            line: line as u64,
-            column: col as u64,
-            index: pair.as_span().start() as u64,
-        };
-        match pair.as_rule() {
-            Rule::empty => AstNode::Empty(EmptyNode { context }),
-            Rule::logical => AstNode::Logical(LogicalNode {
-                value: match pair.as_str() {
-                    "так" => true,
-                    "ні" => false,
-                    _ => unreachable!(),
-                },
-                context,
-            }),
-            Rule::number => AstNode::Number(NumberNode {
-                value: pair.as_str().to_owned(),
-                context,
-            }),
-            Rule::text => AstNode::Text(TextNode {
-                value: pair.into_inner().next().unwrap().as_str().to_owned(),
-                context,
-            }),
-            Rule::dict => AstNode::Dictionary(DictionaryNode {
-                entries: pair
-                    .into_inner()
-                    .map(|pair| {
-                        let mut inner_rules = pair.into_inner();
-                        let key_pair = inner_rules.next().unwrap();
-                        let (line, col) = key_pair.line_col();
-                        let key_context = NodeContext {
-                            line: line as u64,
-                            column: col as u64,
-                            index: key_pair.as_span().start() as u64,
-                        };
-                        let key = match key_pair.as_rule() {
-                            Rule::number => DictionaryEntryKey::Number(NumberNode {
-                                value: key_pair.as_str().to_owned(),
-                                context: key_context,
-                            }),
-                            Rule::text => DictionaryEntryKey::Text(TextNode {
-                                value: key_pair.into_inner().next().unwrap().as_str().to_owned(),
-                                context: key_context,
-                            }),
-                            Rule::ident => DictionaryEntryKey::Text(TextNode {
-                                value: key_pair.as_str().to_owned(),
-                                context: key_context,
-                            }),
-                            rule => unreachable!("{rule:?} {:?}", key_pair.as_str()),
-                        };
-                        let value: Handle<BoxedAstNode> =
-                            ast_node(inner_rules.next().unwrap()).into();
-                        DictionaryEntryNode {
-                            context: key_context,
-                            key,
-                            value,
-                        }
+/// Also returns the warnings about the document, see [`DuplicateKeys::LastWins`].
+pub fn parse_did_with_warnings<'inp>(
+    input: &'inp str,
+    options: &ParseOptions,
+) -> Result<(Did<'inp>, Vec<Warning>), Error> {
+    let mut did = build_cst(input, options)?.into_did();
+    let warnings = match options.duplicate_keys {
+        DuplicateKeys::Allow => Vec::new(),
+        DuplicateKeys::Reject => {
+            reject_duplicates(input, &did)?;
+            Vec::new()
+        }
+        DuplicateKeys::LastWins => duplicates::keep_last(&mut did),
+    };
+    Ok((did, warnings))
+}
+
+/// Parses `input` into the concrete syntax tree, which keeps all of its text.
+pub fn parse_cst(input: &str) -> Result<cst::Document<'_>, Error> {
+    parse_cst_with(input, &ParseOptions::default())
+}
+
+pub fn parse_cst_with<'inp>(
+    input: &'inp str,
+    options: &ParseOptions,
+) -> Result<cst::Document<'inp>, Error> {
+    let document = build_cst(input, options)?;
+    if options.duplicate_keys == DuplicateKeys::Reject {
+        reject_duplicates(input, &document.clone().into_did())?;
+    }
+    Ok(document)
+}
+
+/// Fails at the second key of the first pair of entries with the same key.
+fn reject_duplicates(input: &str, did: &Did<'_>) -> Result<(), Error> {
+    match duplicates::find(did).first() {
+        Some(&(first, key)) => Err(Error::duplicate_key(&LineIndex::new(input), first, key)),
+        None => Ok(()),
+    }
+}
+
+/// Parses `input` into the concrete syntax tree, checking the limits of `options`.
+fn build_cst<'inp>(input: &'inp str, options: &ParseOptions) -> Result<cst::Document<'inp>, Error> {
+    check_input_len(input, options.max_input_len)?;
+    // The grammar recurses on nesting too, so the depth is checked before it runs.
+    check_depth(input, options.max_depth)?;
+    let value = grammar::DidParser::parse(Rule::did, input)
+        .map_err(|err| Error::from_pest(&LineIndex::new(input), err))?
+        .next()
+        .unwrap();
+    check_limits(input, &value, options)?;
+    Ok(cst::Builder::new(input).document(value))
+}
+
+/// Fails if `input` is longer than `max_input_len`.
+fn check_input_len(input: &str, max_input_len: usize) -> Result<(), Error> {
+    if input.len() <= max_input_len {
+        return Ok(());
+    }
+    let mut start = max_input_len;
+    while !input.is_char_boundary(start) {
+        start -= 1;
+    }
+    Err(Error::limit(
+        &LineIndex::new(input),
+        start,
+        Reason::InputTooLong(max_input_len),
+    ))
+}
+
+/// Fails at the first value that exceeds the limits on nodes, texts or entries,
+/// or at the first invalid escape sequence.
+fn check_limits(input: &str, value: &Pair<'_, Rule>, options: &ParseOptions) -> Result<(), Error> {
+    let mut nodes = 0usize;
+    // Start and reason of the first excess. An excess of entries is found at the start
+    // of their container, so values after it are still checked until its position.
+    let mut first: Option<(usize, Reason)> = None;
+    // `flatten` walks the pairs without recursion, in the order of the input.
+    for pair in std::iter::once(value.clone()).chain(value.clone().into_inner().flatten()) {
+        let start = pair.as_span().start();
+        if first.as_ref().is_some_and(|&(first, _)| first <= start) {
+            break;
+        }
+        let excess = match pair.as_rule() {
+            rule if is_value(rule) => {
+                nodes += 1;
+                let entries = matches!(rule, Rule::dict | Rule::object | Rule::list)
+                    .then(|| {
+                        pair.clone()
+                            .into_inner()
+                            .filter(|inner| is_value(inner.as_rule()) || is_entry(inner.as_rule()))
+                            .nth(options.max_entries)
+                    })
+                    .flatten();
+                if nodes > options.max_nodes {
+                    Some((start, Reason::TooManyNodes(options.max_nodes)))
+                } else {
+                    entries.map(|entry| {
+                        (
+                            entry.as_span().start(),
+                            Reason::TooManyEntries(options.max_entries),
+                        )
                     })
-                    .collect(),
-                context,
-            }),
-            Rule::object => AstNode::Object({
-                let mut inner_rules = pair.into_inner();
-                let name: String = inner_rules.next().unwrap().as_str().to_owned();
-                ObjectNode {
-                    name: TextNode {
-                        value: name,
-                        context,
-                    },
-                    entries: inner_rules
-                        .map(|pair| {
-                            let mut inner_rules = pair.into_inner();
-                            let key_pair = inner_rules.next().unwrap();
-                            let (line, col) = key_pair.line_col();
-                            let key_context = NodeContext {
-                                line: line as u64,
-                                column: col as u64,
-                                index: key_pair.as_span().start() as u64,
-                            };
-                            let key = match key_pair.as_rule() {
-                                Rule::ident => TextNode {
-                                    value: key_pair.as_str().to_owned(),
-                                    context: key_context,
-                                },
-                                _ => unreachable!(),
-                            };
-                            let value: Handle<BoxedAstNode> =
-                                ast_node(inner_rules.next().unwrap()).into();
-                            ObjectEntryNode {
-                                context: key_context,
-                                key,
-                                value,
-                            }
-                        })
-                        .collect(),
-                    context,
                 }
-            }),
-            Rule::list => AstNode::List(ListNode {
-                entries: pair.into_inner().map(ast_node).map(Into::into).collect(),
-                context,
-            }),
-            rule => unreachable!("{rule:?} {:?}", pair.as_str()),
+            }
+            Rule::text_inner if pair.as_str().len() > options.max_text_len => {
+                Some((start - 1, Reason::TextTooLong(options.max_text_len)))
+            }
+            // Checked here, so that every tree, native or not, has only valid texts.
+            Rule::text_inner => escape::decode(pair.as_str())
+                .err()
+                .map(|(offset, kind)| (start + offset, Reason::Escape(kind))),
+            _ => None,
+        };
+        if let Some(excess) = excess {
+            if first.as_ref().is_none_or(|first| first.0 > excess.0) {
+                first = Some(excess);
+            }
         }
     }
+    match first {
+        Some((start, Reason::Escape(kind))) => {
+            Err(Error::escape(&LineIndex::new(input), start, kind))
+        }
+        Some((start, reason)) => Err(Error::limit(&LineIndex::new(input), start, reason)),
+        None => Ok(()),
+    }
+}
+
+fn is_value(rule: Rule) -> bool {
+    matches!(
+        rule,
+        Rule::empty
+            | Rule::logical
+            | Rule::number
+            | Rule::text
+            | Rule::dict
+            | Rule::object
+            | Rule::list
+    )
+}
+
+fn is_entry(rule: Rule) -> bool {
+    matches!(rule, Rule::dict_entry | Rule::object_entry)
+}
 
-    Ok(ast_node(input))
+/// Fails at the first bracket that is nested deeper than `max_depth`,
+/// skipping texts and comments.
+fn check_depth(input: &str, max_depth: usize) -> Result<(), Error> {
+    let mut depth = 0usize;
+    let mut index = 0;
+    while let Some(c) = input[index..].chars().next() {
+        let rest = &input[index..];
+        let len = match c {
+            '(' | '[' => {
+                depth += 1;
+                if depth > max_depth {
+                    return Err(Error::limit(
+                        &LineIndex::new(input),
+                        index,
+                        Reason::TooDeep(max_depth),
+                    ));
+                }
+                1
+            }
+            ')' | ']' => {
+                depth = depth.saturating_sub(1);
+                1
+            }
+            '"' => {
+                // Up to the closing quote, or to the end of the line if there is none.
+                let mut escaped = false;
+                rest.char_indices()
+                    .skip(1)
+                    .find(|&(_, c)| {
+                        let end = !escaped && matches!(c, '"' | '\n' | '\r');
+                        escaped = !escaped && c == '\\';
+                        end
+                    })
+                    .map_or(rest.len(), |(i, _)| i + 1)
+            }
+            ';' if rest.starts_with(";;;") => {
+                rest[3..].find(";;;").map_or(rest.len(), |pos| pos + 6)
+            }
+            ';' if rest.starts_with(";;") => rest.find(['\n', '\r']).unwrap_or(rest.len()),
+            c => c.len_utf8(),
+        };
+        index += len;
+    }
+    Ok(())
 }
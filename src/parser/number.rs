@@ -0,0 +1,428 @@
+//! Converting number nodes to Rust number types.
+
+use std::{cmp::Ordering, fmt, str::FromStr};
+
+use super::ast::NodeContext;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NumberError {
+    pub kind: NumberErrorKind,
+    pub context: NodeContext,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NumberErrorKind {
+    /// The value is not a number literal.
+    Invalid,
+    /// The number does not fit into the target type.
+    Overflow,
+    /// An integer was asked for, but the number has a fractional part.
+    Fractional,
+}
+
+impl fmt::Display for NumberErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Invalid => write!(f, "Некоректне число"),
+            Self::Overflow => write!(f, "Число виходить за межі допустимого діапазону"),
+            Self::Fractional => write!(f, "Очікувалося ціле число"),
+        }
+    }
+}
+
+impl fmt::Display for NumberError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} (рядок: {}, стовпчик: {})",
+            self.kind, self.context.line, self.context.column
+        )
+    }
+}
+
+impl std::error::Error for NumberError {}
+
+/// A Rust number type that the value of a number node can be converted to.
+pub trait FromNumber: Sized {
+    fn from_number(value: &str) -> Result<Self, NumberErrorKind>;
+}
+
+macro_rules! integer_impls {
+    ($wide:ident: $($ty:ty),*) => {
+        $(
+            impl FromNumber for $ty {
+                fn from_number(value: &str) -> Result<Self, NumberErrorKind> {
+                    if let Ok(value) = value.parse() {
+                        return Ok(value);
+                    }
+                    let wide = value.parse::<Decimal>()?.$wide()?;
+                    wide.try_into().map_err(|_| NumberErrorKind::Overflow)
+                }
+            }
+        )*
+    };
+}
+
+integer_impls!(to_i128: i8, i16, i32, i64, i128, isize);
+integer_impls!(to_u128: u8, u16, u32, u64, u128, usize);
+
+macro_rules! float_impls {
+    ($($ty:ty),*) => {
+        $(
+            impl FromNumber for $ty {
+                fn from_number(value: &str) -> Result<Self, NumberErrorKind> {
+                    let decimal = value.parse::<Decimal>()?;
+                    let sign = if decimal.negative { "-" } else { "" };
+                    let float: $ty = format!("{sign}{}e{}", decimal.digits, decimal.exponent)
+                        .parse()
+                        .map_err(|_| NumberErrorKind::Invalid)?;
+                    if float.is_finite() {
+                        Ok(float)
+                    } else {
+                        Err(NumberErrorKind::Overflow)
+                    }
+                }
+            }
+        )*
+    };
+}
+
+float_impls!(f32, f64);
+
+impl FromNumber for Decimal {
+    fn from_number(value: &str) -> Result<Self, NumberErrorKind> {
+        value.parse()
+    }
+}
+
+pub(crate) fn parse<T: FromNumber>(value: &str, context: NodeContext) -> Result<T, NumberError> {
+    T::from_number(value).map_err(|kind| NumberError { kind, context })
+}
+
+/// Typed accessors shared by the native and the wasm number nodes.
+macro_rules! number_accessors {
+    () => {
+        /// Converts the value to any [`FromNumber`](crate::parser::number::FromNumber) type.
+        pub fn parse<T: $crate::parser::number::FromNumber>(
+            &self,
+        ) -> Result<T, $crate::parser::number::NumberError> {
+            $crate::parser::number::parse(&self.value, self.context.into())
+        }
+
+        pub fn as_i64(&self) -> Result<i64, $crate::parser::number::NumberError> {
+            self.parse()
+        }
+
+        pub fn as_u64(&self) -> Result<u64, $crate::parser::number::NumberError> {
+            self.parse()
+        }
+
+        pub fn as_i128(&self) -> Result<i128, $crate::parser::number::NumberError> {
+            self.parse()
+        }
+
+        /// Nearest `f64`; fails only if the number is too large to be finite.
+        pub fn as_f64(&self) -> Result<f64, $crate::parser::number::NumberError> {
+            self.parse()
+        }
+
+        /// Exact value of any size and precision.
+        pub fn as_decimal(
+            &self,
+        ) -> Result<$crate::parser::number::Decimal, $crate::parser::number::NumberError> {
+            self.parse()
+        }
+
+        /// Base the literal is written in: 16, 2 and 8 for `0x`, `0b` and `0o`, 10 otherwise.
+        pub fn radix(&self) -> u32 {
+            $crate::parser::number::radix(&self.value)
+        }
+
+        /// The value in decimal without separators, e.g. `255` for `0xF_F`
+        /// and `0.0015` for `+1.5e-3`. See [`Decimal`](crate::parser::number::Decimal).
+        pub fn normalized(&self) -> Result<String, $crate::parser::number::NumberError> {
+            self.as_decimal().map(|decimal| decimal.to_string())
+        }
+    };
+}
+
+pub(crate) use number_accessors;
+
+/// Exact decimal number of arbitrary size and precision.
+///
+/// Literals in radix 16, 8 or 2 are converted up to 1024 significant digits;
+/// longer ones fail with [`NumberErrorKind::Overflow`].
+///
+/// Kept normalized: the digits have no leading or trailing zeros and zero is never
+/// negative, so equal numbers compare equal whatever literal they were written as.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Decimal {
+    negative: bool,
+    /// Significant digits of the absolute value.
+    digits: String,
+    /// Power of ten the digits are multiplied by.
+    exponent: i64,
+}
+
+/// Longest run of zeros [`Decimal`] pads with before it switches to the scientific notation.
+const MAX_PADDING: i64 = 20;
+
+impl Decimal {
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.exponent >= 0
+    }
+
+    /// Significant digits of the absolute value, without the decimal point.
+    pub fn digits(&self) -> &str {
+        &self.digits
+    }
+
+    /// Power of ten the [`digits`](Decimal::digits) are multiplied by.
+    pub fn exponent(&self) -> i64 {
+        self.exponent
+    }
+
+    pub fn to_i128(&self) -> Result<i128, NumberErrorKind> {
+        let magnitude = self.magnitude()?;
+        if self.negative {
+            0i128
+                .checked_sub_unsigned(magnitude)
+                .ok_or(NumberErrorKind::Overflow)
+        } else {
+            magnitude.try_into().map_err(|_| NumberErrorKind::Overflow)
+        }
+    }
+
+    pub fn to_u128(&self) -> Result<u128, NumberErrorKind> {
+        let magnitude = self.magnitude()?;
+        if self.negative {
+            Err(NumberErrorKind::Overflow)
+        } else {
+            Ok(magnitude)
+        }
+    }
+
+    fn magnitude(&self) -> Result<u128, NumberErrorKind> {
+        if !self.is_integer() {
+            return Err(NumberErrorKind::Fractional);
+        }
+        // `u128::MAX` has 39 digits.
+        if (self.digits.len() as i64).saturating_add(self.exponent) > 39 {
+            return Err(NumberErrorKind::Overflow);
+        }
+        let digits: u128 = self.digits.parse().map_err(|_| NumberErrorKind::Overflow)?;
+        10u128
+            .checked_pow(self.exponent as u32)
+            .and_then(|scale| digits.checked_mul(scale))
+            .ok_or(NumberErrorKind::Overflow)
+    }
+
+    /// Builds a normalized number out of decimal `digits` and a power of ten.
+    fn new(negative: bool, digits: &str, exponent: i64) -> Result<Self, NumberErrorKind> {
+        let digits = digits.trim_start_matches('0');
+        let significant = digits.trim_end_matches('0');
+        if significant.is_empty() {
+            return Ok(Self::default());
+        }
+        let trailing_zeros = (digits.len() - significant.len()) as i64;
+        Ok(Self {
+            negative,
+            digits: significant.to_owned(),
+            exponent: exponent
+                .checked_add(trailing_zeros)
+                .ok_or(NumberErrorKind::Overflow)?,
+        })
+    }
+}
+
+impl FromStr for Decimal {
+    type Err = NumberErrorKind;
+
+    /// Parses a number literal of any syntax accepted by the grammar.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (negative, unsigned) = split_sign(s);
+        let radix = radix(s);
+        if radix != 10 {
+            let digits = strip_separators(&unsigned[2..], radix)?;
+            return Self::new(negative, &to_decimal(&digits, radix)?, 0);
+        }
+
+        let (mantissa, exponent) = match unsigned.split_once(['e', 'E']) {
+            Some((mantissa, exponent)) => {
+                let (exponent_negative, exponent) = split_sign(exponent);
+                let exponent: i64 = strip_separators(exponent, 10)?
+                    .parse()
+                    .map_err(|_| NumberErrorKind::Overflow)?;
+                (
+                    mantissa,
+                    if exponent_negative {
+                        -exponent
+                    } else {
+                        exponent
+                    },
+                )
+            }
+            None => (unsigned, 0),
+        };
+        let (integer, fraction) = match mantissa.split_once('.') {
+            Some((integer, fraction)) => (integer, Some(fraction)),
+            None => (mantissa, None),
+        };
+        let mut digits = strip_separators(integer, 10)?;
+        let mut scale = 0;
+        if let Some(fraction) = fraction {
+            let fraction = strip_separators(fraction, 10)?;
+            scale = fraction.len() as i64;
+            digits.push_str(&fraction);
+        }
+        let exponent = exponent
+            .checked_sub(scale)
+            .ok_or(NumberErrorKind::Overflow)?;
+        Self::new(negative, &digits, exponent)
+    }
+}
+
+impl Default for Decimal {
+    fn default() -> Self {
+        Self {
+            negative: false,
+            digits: "0".to_owned(),
+            exponent: 0,
+        }
+    }
+}
+
+/// Orders by value, so `1.5` is less than `0x2`.
+impl Ord for Decimal {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let sign = |decimal: &Self| match (decimal.digits.as_str(), decimal.negative) {
+            ("0", _) => 0,
+            (_, true) => -1,
+            (_, false) => 1,
+        };
+        match sign(self).cmp(&sign(other)) {
+            Ordering::Equal if sign(self) != 0 => {}
+            ordering => return ordering,
+        }
+        // The digits have no leading zeros, so the count of digits before the point
+        // decides first, and then the digits themselves.
+        let integer_len = |decimal: &Self| decimal.digits.len() as i128 + decimal.exponent as i128;
+        let magnitude = integer_len(self)
+            .cmp(&integer_len(other))
+            .then_with(|| self.digits.cmp(&other.digits));
+        if self.negative {
+            magnitude.reverse()
+        } else {
+            magnitude
+        }
+    }
+}
+
+impl PartialOrd for Decimal {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Writes the plain notation (`1500`, `0.0015`), unless it would take more than
+/// twenty padding zeros; then the scientific one (`1.5e-30`).
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.negative {
+            f.write_str("-")?;
+        }
+        let digits = self.digits.as_str();
+        // Number of digits before the decimal point.
+        let point = (digits.len() as i64).saturating_add(self.exponent);
+        if (0..=MAX_PADDING).contains(&self.exponent) {
+            write!(f, "{digits}{:0<1$}", "", self.exponent as usize)
+        } else if self.exponent < 0 && point > 0 {
+            let (integer, fraction) = digits.split_at(point as usize);
+            write!(f, "{integer}.{fraction}")
+        } else if self.exponent < 0 && -point < MAX_PADDING {
+            write!(f, "0.{:0<1$}{digits}", "", -point as usize)
+        } else {
+            let (first, rest) = digits.split_at(1);
+            f.write_str(first)?;
+            if !rest.is_empty() {
+                write!(f, ".{rest}")?;
+            }
+            write!(f, "e{}", point.saturating_sub(1))
+        }
+    }
+}
+
+/// Base of a number literal: 16, 2 and 8 for `0x`, `0b` and `0o`, 10 otherwise.
+pub(crate) fn radix(value: &str) -> u32 {
+    let (_, unsigned) = split_sign(value);
+    match unsigned.get(..2) {
+        Some("0x") => 16,
+        Some("0b") => 2,
+        Some("0o") => 8,
+        _ => 10,
+    }
+}
+
+fn split_sign(value: &str) -> (bool, &str) {
+    if let Some(rest) = value.strip_prefix('-') {
+        (true, rest)
+    } else {
+        (false, value.strip_prefix('+').unwrap_or(value))
+    }
+}
+
+/// Removes the `_` separators, checking that they only stand between digits.
+fn strip_separators(digits: &str, radix: u32) -> Result<String, NumberErrorKind> {
+    let mut stripped = String::with_capacity(digits.len());
+    for group in digits.split('_') {
+        if group.is_empty() || !group.chars().all(|c| c.is_digit(radix)) {
+            return Err(NumberErrorKind::Invalid);
+        }
+        stripped.push_str(group);
+    }
+    Ok(stripped)
+}
+
+/// Most significant digits of a literal in a radix other than 10 that [`Decimal`] converts;
+/// longer literals overflow, since the conversion takes time quadratic in their length.
+const MAX_RADIX_DIGITS: usize = 1024;
+
+/// Converts digits in `radix`, a power of two, to decimal ones.
+fn to_decimal(digits: &str, radix: u32) -> Result<String, NumberErrorKind> {
+    const BASE: u64 = 1_000_000_000;
+
+    let digits = digits.trim_start_matches('0');
+    if digits.len() > MAX_RADIX_DIGITS {
+        return Err(NumberErrorKind::Overflow);
+    }
+    // Every limb is multiplied once per chunk of digits that fits into 32 bits,
+    // rather than once per digit.
+    let bits = radix.trailing_zeros() as usize;
+    let chunk_len = 32 / bits;
+    // Little-endian limbs of nine decimal digits each.
+    let mut limbs: Vec<u64> = vec![0];
+    for chunk in digits.as_bytes().chunks(chunk_len) {
+        let chunk = std::str::from_utf8(chunk).expect("checked by strip_separators");
+        let mut carry = u64::from_str_radix(chunk, radix).expect("checked by strip_separators");
+        let scale = 1u64 << (bits * chunk.len());
+        for limb in &mut limbs {
+            let value = *limb * scale + carry;
+            *limb = value % BASE;
+            carry = value / BASE;
+        }
+        while carry > 0 {
+            limbs.push(carry % BASE);
+            carry /= BASE;
+        }
+    }
+    let mut limbs = limbs.iter().rev();
+    let mut decimal = limbs.next().expect("at least one limb").to_string();
+    for limb in limbs {
+        decimal.push_str(&format!("{limb:09}"));
+    }
+    Ok(decimal)
+}
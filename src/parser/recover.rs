@@ -0,0 +1,500 @@
+//! Parsing that recovers from syntax errors.
+//!
+//! The parser does not stop at the first error: invalid text is replaced by a
+//! [`Did::Error`] node, and parsing goes on from the nearest `,`, `)` or `]`.
+
+use std::borrow::Cow;
+
+use pest::Parser;
+
+use super::{
+    ast::*, duplicates, escape, grammar::DidParser, DuplicateKeys, ParseOptions, Rule, Warning,
+};
+use crate::{
+    error::{self, Reason},
+    line_index::LineIndex,
+    Error, ErrorKind,
+};
+
+/// Parses `input` without stopping at syntax errors.
+///
+/// Returns a tree where the text that could not be parsed is replaced by [`Did::Error`]
+/// nodes, along with every error found. For a valid input the tree is the same as the one
+/// of [`parse_did`](super::parse_did), and there are no errors.
+pub fn parse_recovering(input: &str) -> (Did<'_>, Vec<Error>) {
+    parse_recovering_with(input, &ParseOptions::default())
+}
+
+/// Warnings are discarded, see [`parse_recovering_with_warnings`].
+pub fn parse_recovering_with<'inp>(
+    input: &'inp str,
+    options: &ParseOptions,
+) -> (Did<'inp>, Vec<Error>) {
+    let (did, errors, _) = parse_recovering_with_warnings(input, options);
+    (did, errors)
+}
+
+/// Also returns the warnings about the document, see [`DuplicateKeys::LastWins`].
+pub fn parse_recovering_with_warnings<'inp>(
+    input: &'inp str,
+    options: &ParseOptions,
+) -> (Did<'inp>, Vec<Error>, Vec<Warning>) {
+    if let Err(error) = super::check_input_len(input, options.max_input_len) {
+        let did = Did::Error(ErrorNode {
+            text: Cow::Borrowed(""),
+            context: NodeContext::default(),
+        });
+        return (did, vec![error], Vec::new());
+    }
+    let mut parser = Recovering::new(input, options);
+    parser.trivia();
+    let mut did = parser.value(ErrorKind::AstNode);
+    parser.trivia();
+    if !parser.rest().is_empty() {
+        parser.error(vec![ErrorKind::Eof]);
+    }
+    let Recovering {
+        lines, mut errors, ..
+    } = parser;
+    let warnings = match options.duplicate_keys {
+        DuplicateKeys::Allow => Vec::new(),
+        DuplicateKeys::Reject => {
+            errors.extend(
+                duplicates::find(&did)
+                    .into_iter()
+                    .map(|(first, key)| Error::duplicate_key(&lines, first, key)),
+            );
+            errors.sort_by_key(|err| err.start);
+            Vec::new()
+        }
+        DuplicateKeys::LastWins => duplicates::keep_last(&mut did),
+    };
+    (did, errors, warnings)
+}
+
+/// Byte index of the start of the innermost list, dictionary or object that is not closed
+/// at the end of `input`, e.g. the input up to a parse error.
+pub(crate) fn unclosed(input: &str) -> Option<usize> {
+    let options = ParseOptions::default();
+    let mut parser = Recovering::new(input, &options);
+    parser.trivia();
+    parser.value(ErrorKind::AstNode);
+    parser.unclosed
+}
+
+struct Recovering<'inp, 'opt> {
+    input: &'inp str,
+    options: &'opt ParseOptions,
+    /// Number of the values so far.
+    nodes: usize,
+    cursor: NodeContext,
+    /// Closing brackets and starts of the lists, dictionaries and objects the cursor is in.
+    closers: Vec<(char, usize)>,
+    /// Start of the innermost value that is not closed at the end of the input.
+    unclosed: Option<usize>,
+    /// Lines of the input, shared by all of the errors.
+    lines: LineIndex<'inp>,
+    errors: Vec<Error>,
+}
+
+impl<'inp, 'opt> Recovering<'inp, 'opt> {
+    fn new(input: &'inp str, options: &'opt ParseOptions) -> Self {
+        Self {
+            input,
+            options,
+            nodes: 0,
+            cursor: NodeContext::default(),
+            closers: Vec::new(),
+            unclosed: None,
+            lines: LineIndex::new(input),
+            errors: Vec::new(),
+        }
+    }
+
+    fn value(&mut self, missing: ErrorKind) -> Did<'inp> {
+        let context = self.cursor;
+        self.nodes += 1;
+        if self.nodes > self.options.max_nodes {
+            // Only the first value over the limit is reported.
+            let reason = (self.nodes == self.options.max_nodes + 1)
+                .then_some(Reason::TooManyNodes(self.options.max_nodes));
+            return self.skip_limited(context, reason);
+        }
+        if self.rest().starts_with(['(', '[']) && self.closers.len() == self.options.max_depth {
+            return self.skip_limited(context, Some(Reason::TooDeep(self.options.max_depth)));
+        }
+        if self.rest().starts_with('(') {
+            let entries = self.entries(
+                context,
+                ')',
+                ErrorKind::DictionaryEntryKey,
+                ErrorKind::RightParenthesis,
+                Self::dictionary_entry,
+            );
+            return Did::Dictionary(DictionaryNode {
+                entries,
+                context: context.until(self.cursor),
+            });
+        }
+        if self.rest().starts_with('[') {
+            let entries = self.entries(
+                context,
+                ']',
+                ErrorKind::AstNode,
+                ErrorKind::RightBracket,
+                |parser| Some(parser.value(ErrorKind::AstNode)),
+            );
+            return Did::List(ListNode {
+                entries,
+                context: context.until(self.cursor),
+            });
+        }
+        if let Some((_, context)) = self.token(Rule::empty) {
+            return Did::Empty(EmptyNode { context });
+        }
+        if let Some((value, context)) = self.token(Rule::logical) {
+            return Did::Logical(LogicalNode {
+                value: value == "так",
+                context,
+            });
+        }
+        if let Some((value, context)) = self.token(Rule::number) {
+            return Did::Number(NumberNode {
+                value: Cow::Borrowed(value),
+                context,
+            });
+        }
+        if let Some((value, context)) = self.token(Rule::text) {
+            return Did::Text(self.text(value, context));
+        }
+        if let Some((name, name_context)) = self.token(Rule::ident) {
+            self.trivia();
+            if !self.rest().starts_with('(') {
+                self.error(vec![ErrorKind::LeftParenthesis]);
+                return Did::Error(ErrorNode {
+                    text: Cow::Borrowed(name),
+                    context: name_context,
+                });
+            }
+            if self.closers.len() == self.options.max_depth {
+                return self.skip_limited(context, Some(Reason::TooDeep(self.options.max_depth)));
+            }
+            let entries = self.entries(
+                context,
+                ')',
+                ErrorKind::Identifier,
+                ErrorKind::RightParenthesis,
+                Self::object_entry,
+            );
+            return Did::Object(ObjectNode {
+                name: TextNode {
+                    value: Cow::Borrowed(name),
+                    context: name_context,
+                },
+                entries,
+                context: context.until(self.cursor),
+            });
+        }
+
+        self.error(vec![missing]);
+        let start = self.cursor.index as usize;
+        self.skip();
+        let text = self.input[start..self.cursor.index as usize].trim_end();
+        Did::Error(ErrorNode {
+            text: Cow::Borrowed(text),
+            context: context.spanning(text),
+        })
+    }
+
+    /// Skips the value from `context` that exceeds a limit at the cursor for `reason`.
+    fn skip_limited(&mut self, context: NodeContext, reason: Option<Reason>) -> Did<'inp> {
+        if let Some(reason) = reason {
+            let start = self.cursor.index as usize;
+            self.errors.push(Error::limit(&self.lines, start, reason));
+        }
+        self.skip();
+        let text = self.input[context.index as usize..self.cursor.index as usize].trim_end();
+        Did::Error(ErrorNode {
+            text: Cow::Borrowed(text),
+            context: context.spanning(text),
+        })
+    }
+
+    /// Entries between the opening bracket at the cursor and `close`, of the value
+    /// that starts at `start`.
+    ///
+    /// `entry` returns `None` for an entry that has to be skipped as a whole.
+    fn entries<T>(
+        &mut self,
+        start: NodeContext,
+        close: char,
+        missing: ErrorKind,
+        close_kind: ErrorKind,
+        mut entry: impl FnMut(&mut Self) -> Option<T>,
+    ) -> Vec<T> {
+        self.advance(1);
+        self.closers.push((close, start.index as usize));
+        let mut entries = Vec::new();
+        let mut limited = false;
+        'entries: loop {
+            self.trivia();
+            if self.eat(close) {
+                break;
+            }
+            if self.at_stop() {
+                self.error(vec![missing, close_kind]);
+                if self.skip_stray_closer() {
+                    continue;
+                }
+                break;
+            }
+            if entries.len() == self.options.max_entries {
+                // The entries over the limit are skipped, and only the first is reported.
+                if !limited {
+                    let start = self.cursor.index as usize;
+                    let reason = Reason::TooManyEntries(self.options.max_entries);
+                    self.errors.push(Error::limit(&self.lines, start, reason));
+                    limited = true;
+                }
+                self.skip();
+            } else {
+                entries.extend(entry(self));
+            }
+
+            // Only the first problem after an entry is reported, the rest is skipped.
+            let mut reported = false;
+            loop {
+                self.trivia();
+                if self.eat(',') {
+                    continue 'entries;
+                }
+                if self.eat(close) {
+                    break 'entries;
+                }
+                if !reported {
+                    self.error(vec![ErrorKind::Comma, close_kind]);
+                    reported = true;
+                }
+                if !self.skip_stray_closer() {
+                    if self.at_stop() {
+                        break 'entries;
+                    }
+                    self.skip();
+                }
+            }
+        }
+        self.closers.pop();
+        entries
+    }
+
+    /// Skips a closing bracket that does not close anything the cursor is in.
+    fn skip_stray_closer(&mut self) -> bool {
+        match self.rest().chars().next() {
+            Some(c @ (')' | ']')) if !self.closers.iter().any(|&(close, _)| close == c) => {
+                self.advance(1);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn dictionary_entry(&mut self) -> Option<DictionaryEntryNode<'inp>> {
+        let key = if let Some((key, context)) = self.token(Rule::number) {
+            DictionaryEntryKey::Number(NumberNode {
+                value: Cow::Borrowed(key),
+                context,
+            })
+        } else if let Some((key, context)) = self.token(Rule::ident) {
+            DictionaryEntryKey::Text(TextNode {
+                value: Cow::Borrowed(key),
+                context,
+            })
+        } else if let Some((key, context)) = self.token(Rule::text) {
+            DictionaryEntryKey::Text(self.text(key, context))
+        } else {
+            self.error(vec![ErrorKind::DictionaryEntryKey]);
+            self.skip();
+            return None;
+        };
+        let value = self.entry_value();
+        Some(DictionaryEntryNode {
+            context: key.context().through(value.context()),
+            key,
+            value,
+        })
+    }
+
+    fn object_entry(&mut self) -> Option<ObjectEntryNode<'inp>> {
+        let Some((key, context)) = self.token(Rule::ident) else {
+            self.error(vec![ErrorKind::Identifier]);
+            self.skip();
+            return None;
+        };
+        let value = self.entry_value();
+        Some(ObjectEntryNode {
+            key: TextNode {
+                value: Cow::Borrowed(key),
+                context,
+            },
+            context: context.through(value.context()),
+            value,
+        })
+    }
+
+    /// The `=` and the value after a key.
+    fn entry_value(&mut self) -> Did<'inp> {
+        self.trivia();
+        if !self.eat('=') {
+            self.error(vec![ErrorKind::EqualsSign]);
+            if self.at_stop() || self.rest().starts_with(',') {
+                return Did::Error(ErrorNode {
+                    text: Cow::Borrowed(""),
+                    context: self.cursor,
+                });
+            }
+        }
+        self.trivia();
+        self.value(ErrorKind::EntryValue)
+    }
+
+    /// A text node from a text token with its quotes, reporting it if it is too long
+    /// or has an invalid escape sequence.
+    fn text(&mut self, token: &'inp str, context: NodeContext) -> TextNode<'inp> {
+        let value = &token[1..token.len() - 1];
+        if value.len() > self.options.max_text_len {
+            let reason = Reason::TextTooLong(self.options.max_text_len);
+            self.errors
+                .push(Error::limit(&self.lines, context.index as usize, reason));
+        } else if let Err((offset, kind)) = escape::decode(value) {
+            let start = context.index as usize + 1 + offset;
+            self.errors.push(Error::escape(&self.lines, start, kind));
+        }
+        TextNode {
+            value: Cow::Borrowed(value),
+            context,
+        }
+    }
+
+    /// Text and context of the `rule` token at the cursor, which is consumed.
+    fn token(&mut self, rule: Rule) -> Option<(&'inp str, NodeContext)> {
+        let rest = self.rest();
+        // pest indexes the lines of all of its input, so it only gets the token,
+        // which keeps parsing linear.
+        let window = &rest[..error::token_len(rest)];
+        let pair = DidParser::parse(rule, window).ok()?.next()?;
+        let token = &rest[..pair.as_span().end()];
+        let context = self.cursor;
+        self.advance(token.len());
+        Some((token, context.until(self.cursor)))
+    }
+
+    /// Skips the whitespace and comments at the cursor.
+    fn trivia(&mut self) {
+        loop {
+            let rest = self.rest();
+            let len = if let Some(comment) = rest.strip_prefix(";;;") {
+                match comment.find(";;;") {
+                    Some(len) => len + 6,
+                    None => {
+                        self.errors.push(Error::new(
+                            &self.lines,
+                            self.cursor.index as usize,
+                            rest.len(),
+                            Vec::new(),
+                            Some(Reason::UnterminatedComment),
+                        ));
+                        rest.len()
+                    }
+                }
+            } else if rest.starts_with(";;") {
+                rest.find(['\n', '\r']).unwrap_or(rest.len())
+            } else {
+                rest.find(|c| !matches!(c, ' ' | '\t' | '\n' | '\r'))
+                    .unwrap_or(rest.len())
+            };
+            if len == 0 {
+                break;
+            }
+            self.advance(len);
+        }
+    }
+
+    /// Skips to the next `,`, `)` or `]` outside of nested brackets and texts.
+    fn skip(&mut self) {
+        let mut depth = 0usize;
+        while let Some(c) = self.rest().chars().next() {
+            match c {
+                ',' | ')' | ']' if depth == 0 => break,
+                '(' | '[' => depth += 1,
+                ')' | ']' => depth -= 1,
+                ';' if self.rest().starts_with(";;") => {
+                    self.trivia();
+                    continue;
+                }
+                '"' => {
+                    let len = unterminated_text_len(self.rest());
+                    self.advance(len);
+                    continue;
+                }
+                '\r' if self.rest().starts_with("\r\n") => {
+                    self.advance(2);
+                    continue;
+                }
+                _ => {}
+            }
+            self.advance(c.len_utf8());
+        }
+    }
+
+    /// Whether the cursor is at the end of the input or at a closing bracket.
+    fn at_stop(&self) -> bool {
+        matches!(self.rest().chars().next(), None | Some(')' | ']'))
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        let found = self.rest().starts_with(c);
+        if found {
+            self.advance(c.len_utf8());
+        }
+        found
+    }
+
+    /// Reports that none of `expected` is at the cursor,
+    /// unless an error is already reported there.
+    fn error(&mut self, expected: Vec<ErrorKind>) {
+        let start = self.cursor.index as usize;
+        if start == self.input.len() && self.unclosed.is_none() {
+            self.unclosed = self.closers.last().map(|&(_, start)| start);
+        }
+        if self.errors.last().is_some_and(|err| err.start == start) {
+            return;
+        }
+        self.errors
+            .push(Error::expected(&self.lines, start, expected));
+    }
+
+    fn rest(&self) -> &'inp str {
+        &self.input[self.cursor.index as usize..]
+    }
+
+    fn advance(&mut self, len: usize) {
+        let text = &self.rest()[..len];
+        self.cursor.advance(text);
+    }
+}
+
+/// Byte length of a text that may lack the closing quote, which then ends with the line.
+fn unterminated_text_len(rest: &str) -> usize {
+    let mut chars = rest.char_indices().skip(1);
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => return i + 1,
+            '\n' | '\r' => return i,
+            '\\' => {
+                chars.next();
+            }
+            _ => {}
+        }
+    }
+    rest.len()
+}
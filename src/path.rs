@@ -0,0 +1,231 @@
+//! Looking up values in a document by path.
+//!
+//! A path is made of [`Key`]s. It can also be written as a pointer in the style of
+//! JSON Pointer: `/параметри/висота` or `/оцінки/0`. The characters `~` and `/` in keys
+//! are written as `~0` and `~1`, and the empty pointer stands for the whole document.
+//!
+//! Dictionary keys are compared by value: the key `1` finds the entries `1=...`,
+//! `1.0=...` and `"1"=...`. If a key is repeated, the last entry is found.
+//!
+//! ```
+//! use mavka_did::{parser, path::Key};
+//!
+//! let input = "Людина(параметри=(висота=175, вага=69), оцінки=[5, 4])";
+//! let document = parser::parse_did(input).unwrap();
+//! let height = document.pointer("/параметри/висота").unwrap();
+//! assert_eq!(&input[height.context().range()], "175");
+//! assert_eq!(document.pointer_as::<u32>("/оцінки/1").unwrap(), Ok(4));
+//! assert!(document.get_path(&[Key::from("оцінки"), Key::from(2)]).is_none());
+//! ```
+
+use std::borrow::Cow;
+
+use crate::{
+    convert::{self, FromDid},
+    parser::{
+        ast::{DictionaryEntryKey, DictionaryEntryNode, Did, NodeContext, ObjectEntryNode},
+        escape::unescape,
+        number::Decimal,
+    },
+};
+
+/// One step of a path into a document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Key<'k> {
+    /// Key of a dictionary or an object entry.
+    Name(&'k str),
+    /// Position in a list, or a number key of a dictionary.
+    Index(usize),
+}
+
+impl<'k> From<&'k str> for Key<'k> {
+    fn from(name: &'k str) -> Self {
+        Self::Name(name)
+    }
+}
+
+impl From<usize> for Key<'_> {
+    fn from(index: usize) -> Self {
+        Self::Index(index)
+    }
+}
+
+impl<'inp> Did<'inp> {
+    /// The value at `path`, or `None` if there is nothing there.
+    pub fn get_path(&self, path: &[Key<'_>]) -> Option<&Did<'inp>> {
+        path.iter().try_fold(self, |did, &key| child(did, key))
+    }
+
+    /// The value at `path` for changing in place, see [`Did::get_path`].
+    pub fn get_path_mut(&mut self, path: &[Key<'_>]) -> Option<&mut Did<'inp>> {
+        path.iter().try_fold(self, |did, &key| child_mut(did, key))
+    }
+
+    /// Reads the value at `path` as `T`, or returns `None` if there is nothing there.
+    pub fn get_path_as<T: FromDid>(&self, path: &[Key<'_>]) -> Option<Result<T, convert::Error>> {
+        self.get_path(path).map(T::from_did)
+    }
+
+    /// The value at `pointer`, or `None` if there is nothing there or the pointer
+    /// is not valid.
+    ///
+    /// A segment of digits is a list index, anything else only finds entries.
+    pub fn pointer(&self, pointer: &str) -> Option<&Did<'inp>> {
+        segments(pointer)?
+            .iter()
+            .try_fold(self, |did, segment| child(did, step(did, segment)?))
+    }
+
+    /// The value at `pointer` for changing in place, see [`Did::pointer`].
+    pub fn pointer_mut(&mut self, pointer: &str) -> Option<&mut Did<'inp>> {
+        segments(pointer)?.iter().try_fold(self, |did, segment| {
+            let key = step(did, segment)?;
+            child_mut(did, key)
+        })
+    }
+
+    /// Reads the value at `pointer` as `T`, see [`Did::pointer`].
+    pub fn pointer_as<T: FromDid>(&self, pointer: &str) -> Option<Result<T, convert::Error>> {
+        self.pointer(pointer).map(T::from_did)
+    }
+}
+
+fn child<'a, 'inp>(did: &'a Did<'inp>, key: Key<'_>) -> Option<&'a Did<'inp>> {
+    match did {
+        Did::Dictionary(node) => {
+            let position = position(Container::Dictionary, &node.entries, dictionary_key, key)?;
+            Some(&node.entries[position].value)
+        }
+        Did::Object(node) => {
+            let position = position(Container::Object, &node.entries, object_key, key)?;
+            Some(&node.entries[position].value)
+        }
+        Did::List(node) => match key {
+            Key::Index(index) => node.entries.get(index),
+            Key::Name(_) => None,
+        },
+        _ => None,
+    }
+}
+
+fn child_mut<'a, 'inp>(did: &'a mut Did<'inp>, key: Key<'_>) -> Option<&'a mut Did<'inp>> {
+    match did {
+        Did::Dictionary(node) => {
+            let position = position(Container::Dictionary, &node.entries, dictionary_key, key)?;
+            Some(&mut node.entries[position].value)
+        }
+        Did::Object(node) => {
+            let position = position(Container::Object, &node.entries, object_key, key)?;
+            Some(&mut node.entries[position].value)
+        }
+        Did::List(node) => match key {
+            Key::Index(index) => node.entries.get_mut(index),
+            Key::Name(_) => None,
+        },
+        _ => None,
+    }
+}
+
+fn dictionary_key<'a>(entry: &'a DictionaryEntryNode<'_>) -> EntryKey<'a> {
+    match &entry.key {
+        DictionaryEntryKey::Number(key) => EntryKey::Number(&key.value),
+        DictionaryEntryKey::Text(key) => EntryKey::Escaped(&key.value, key.context),
+    }
+}
+
+fn object_key<'a>(entry: &'a ObjectEntryNode<'_>) -> EntryKey<'a> {
+    EntryKey::Name(&entry.key.value)
+}
+
+/// Container of entries that [`position`] looks in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Container {
+    Dictionary,
+    Object,
+}
+
+/// Key of a dictionary or an object entry, as [`position`] compares it.
+pub(crate) enum EntryKey<'a> {
+    /// A number key of a dictionary, as written.
+    Number(&'a str),
+    /// A text key of a dictionary, as written between the quotes.
+    Escaped(&'a str, NodeContext),
+    /// An object entry key, an identifier or a decoded text key.
+    Name(&'a str),
+}
+
+/// Index in `entries` of the last entry at `key`.
+///
+/// Number keys are compared by value, text keys after unescaping.
+pub(crate) fn position<T>(
+    container: Container,
+    entries: &[T],
+    key_of: impl Fn(&T) -> EntryKey<'_>,
+    key: Key<'_>,
+) -> Option<usize> {
+    match (container, key) {
+        (Container::Dictionary, Key::Name(name)) => {
+            let number = name.parse::<Decimal>().ok();
+            entries.iter().rposition(|entry| match key_of(entry) {
+                EntryKey::Number(key) => number.is_some() && key.parse::<Decimal>().ok() == number,
+                EntryKey::Escaped(key, context) => {
+                    unescape(key, context).is_ok_and(|key| key == name)
+                }
+                EntryKey::Name(key) => key == name,
+            })
+        }
+        (Container::Dictionary, Key::Index(index)) => {
+            position(container, entries, key_of, Key::Name(&index.to_string()))
+        }
+        (Container::Object, Key::Name(name)) => entries
+            .iter()
+            .rposition(|entry| matches!(key_of(entry), EntryKey::Name(key) if key == name)),
+        (Container::Object, Key::Index(_)) => None,
+    }
+}
+
+/// The key a pointer `segment` stands for in `did`.
+fn step<'s>(did: &Did<'_>, segment: &'s str) -> Option<Key<'s>> {
+    match did {
+        Did::List(_) => index(segment).map(Key::Index),
+        _ => Some(Key::Name(segment)),
+    }
+}
+
+/// Unescaped segments of `pointer`, or `None` if it is not a valid pointer.
+pub(crate) fn segments(pointer: &str) -> Option<Vec<Cow<'_, str>>> {
+    if pointer.is_empty() {
+        return Some(Vec::new());
+    }
+    pointer
+        .strip_prefix('/')?
+        .split('/')
+        .map(|segment| {
+            if !segment.contains('~') {
+                return Some(Cow::Borrowed(segment));
+            }
+            let mut unescaped = String::with_capacity(segment.len());
+            let mut chars = segment.chars();
+            while let Some(c) = chars.next() {
+                unescaped.push(match c {
+                    '~' => match chars.next()? {
+                        '0' => '~',
+                        '1' => '/',
+                        _ => return None,
+                    },
+                    c => c,
+                });
+            }
+            Some(Cow::Owned(unescaped))
+        })
+        .collect()
+}
+
+/// The list index a pointer `segment` stands for: digits without leading zeros.
+pub(crate) fn index(segment: &str) -> Option<usize> {
+    let digits = !segment.is_empty() && segment.bytes().all(|b| b.is_ascii_digit());
+    if !digits || (segment.len() > 1 && segment.starts_with('0')) {
+        return None;
+    }
+    segment.parse().ok()
+}
@@ -0,0 +1,616 @@
+//! A query language for finding nodes in `Дід` documents.
+//!
+//! A query is made of steps, each of which goes from the nodes found so far to others:
+//!
+//! | Step               | Finds                                                      |
+//! |--------------------|------------------------------------------------------------|
+//! | `.ключ`, `."ключ"` | the value of an entry of a dictionary or an object, see [`path`](crate::path) |
+//! | `[0]`              | an element of a list, or the value of a number key of a dictionary |
+//! | `.*`, `[*]`        | all values of entries and elements of lists                |
+//! | `..`               | the node itself and all nodes nested in it                 |
+//! | `Людина`           | only objects named `Людина`                                |
+//! | `[?умова]`         | only nodes the condition holds for                         |
+//! | `{ключ, ключ}`     | the values of several entries of each node, in the order of the keys |
+//!
+//! So `..Людина` finds all `Людина` objects in the document, and `...вік` all `вік` entries.
+//! A condition tests the nodes themselves, so elements of a list are filtered like this:
+//! `.люди[*][?вік > 18]`.
+//!
+//! A condition compares paths from the node (`вік`, `параметри.висота`, `@` for the node
+//! itself) with values (`18`, `"Леся"`, `так`, `пусто`) using `==`, `!=`, `<`, `<=`, `>`
+//! and `>=`, and conditions are combined with `&&`, `||`, `!` and parentheses. A comparison
+//! holds if it holds for at least one of the values found. Numbers are compared by value,
+//! texts character by character, and values of different types are not equal. A path
+//! without a comparison holds if it finds anything other than `ні` and `пусто`. Conditions
+//! are nested in one another at most 128 levels deep.
+//!
+//! ```
+//! use mavka_did::parser;
+//!
+//! let input = "[Людина(імʼя=\"Леся\", вік=20), Людина(імʼя=\"Тарас\", вік=17)]";
+//! let document = parser::parse_did(input).unwrap();
+//! let names = document.select("..Людина[?вік > 18].імʼя").unwrap();
+//! assert_eq!(names.len(), 1);
+//! assert_eq!(&input[names[0].context().range()], "\"Леся\"");
+//! ```
+
+use std::{borrow::Cow, cmp::Ordering, fmt, str::FromStr};
+
+use crate::{
+    parser::{
+        ast::{Did, NodeContext},
+        escape::{unescape, EscapeErrorKind},
+        number::Decimal,
+    },
+    path::Key,
+    writer::is_alpha,
+};
+
+/// A parsed query, see the [module documentation](self) for its syntax.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Query {
+    segments: Vec<Segment>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Key(String),
+    Index(usize),
+    Children,
+    Descendants,
+    Name(String),
+    Filter(Predicate),
+    Select(Vec<String>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Predicate {
+    /// Terms of a `||` chain, kept flat so long chains don't recurse.
+    Or(Vec<Predicate>),
+    /// Terms of a `&&` chain.
+    And(Vec<Predicate>),
+    Not(Box<Predicate>),
+    Test(Operand),
+    Compare(Operand, Operator, Operand),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Operand {
+    /// Segments applied to the tested node.
+    Path(Vec<Segment>),
+    Literal(Value<'static>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operator {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+/// A value as it is compared.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Value<'a> {
+    Empty,
+    Logical(bool),
+    Number(Decimal),
+    Text(Cow<'a, str>),
+    /// A container, or a number or a text that cannot be read; equal to nothing.
+    Other,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryError {
+    pub kind: QueryErrorKind,
+    /// Column of the error in the query, starting from 1.
+    pub column: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QueryErrorKind {
+    /// A character that cannot be there.
+    UnexpectedChar(char),
+    /// The query ends in the middle of a step.
+    UnexpectedEnd,
+    /// A number or an index that cannot be read.
+    InvalidNumber,
+    /// A text with a bad escape sequence.
+    InvalidEscape(EscapeErrorKind),
+    /// Conditions nested deeper than the limit, which is given.
+    TooDeep(usize),
+}
+
+impl fmt::Display for QueryErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnexpectedChar(c) => write!(f, "Неочікуваний символ `{c}`"),
+            Self::UnexpectedEnd => write!(f, "Неочікуваний кінець запиту"),
+            Self::InvalidNumber => write!(f, "Некоректне число"),
+            Self::InvalidEscape(kind) => kind.fmt(f),
+            Self::TooDeep(max_depth) => {
+                write!(
+                    f,
+                    "Умови вкладено глибше за дозволене (найбільша глибина: {max_depth})"
+                )
+            }
+        }
+    }
+}
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (стовпчик: {})", self.kind, self.column)
+    }
+}
+
+impl std::error::Error for QueryError {}
+
+impl Query {
+    pub fn parse(query: &str) -> Result<Self, QueryError> {
+        let mut parser = Parser {
+            query,
+            position: 0,
+            depth: 0,
+        };
+        let segments = parser.segments(false)?;
+        Ok(Self { segments })
+    }
+
+    /// Nodes the query finds in `did`, in the order of the steps that found them.
+    ///
+    /// The same node is returned more than once if several steps lead to it.
+    pub fn select<'a, 'inp>(&self, did: &'a Did<'inp>) -> Vec<&'a Did<'inp>> {
+        apply(&self.segments, vec![did])
+    }
+}
+
+impl FromStr for Query {
+    type Err = QueryError;
+
+    fn from_str(query: &str) -> Result<Self, Self::Err> {
+        Self::parse(query)
+    }
+}
+
+impl<'inp> Did<'inp> {
+    /// Parses the `query` and returns the nodes it finds, see [`Query::select`].
+    pub fn select(&self, query: &str) -> Result<Vec<&Did<'inp>>, QueryError> {
+        Ok(Query::parse(query)?.select(self))
+    }
+}
+
+fn apply<'a, 'inp>(segments: &[Segment], mut nodes: Vec<&'a Did<'inp>>) -> Vec<&'a Did<'inp>> {
+    for segment in segments {
+        nodes = match segment {
+            Segment::Key(key) => nodes
+                .into_iter()
+                .filter_map(|did| did.get_path(&[Key::Name(key)]))
+                .collect(),
+            Segment::Index(index) => nodes
+                .into_iter()
+                .filter_map(|did| did.get_path(&[Key::Index(*index)]))
+                .collect(),
+            Segment::Children => {
+                let mut children = Vec::new();
+                for did in nodes {
+                    push_children(did, &mut children);
+                }
+                children
+            }
+            Segment::Descendants => {
+                let mut descendants = Vec::new();
+                for did in nodes {
+                    // Walked without recursion, in the order of the input.
+                    let mut stack = vec![did];
+                    while let Some(did) = stack.pop() {
+                        descendants.push(did);
+                        let start = stack.len();
+                        push_children(did, &mut stack);
+                        stack[start..].reverse();
+                    }
+                }
+                descendants
+            }
+            Segment::Name(name) => nodes
+                .into_iter()
+                .filter(|did| matches!(did, Did::Object(node) if node.name.value == *name))
+                .collect(),
+            Segment::Filter(predicate) => nodes
+                .into_iter()
+                .filter(|did| predicate.test(did))
+                .collect(),
+            Segment::Select(keys) => nodes
+                .into_iter()
+                .flat_map(|did| {
+                    keys.iter()
+                        .filter_map(move |key| did.get_path(&[Key::Name(key)]))
+                })
+                .collect(),
+        };
+    }
+    nodes
+}
+
+fn push_children<'a, 'inp>(did: &'a Did<'inp>, children: &mut Vec<&'a Did<'inp>>) {
+    match did {
+        Did::Dictionary(node) => children.extend(node.entries.iter().map(|entry| &entry.value)),
+        Did::Object(node) => children.extend(node.entries.iter().map(|entry| &entry.value)),
+        Did::List(node) => children.extend(&node.entries),
+        _ => {}
+    }
+}
+
+impl Predicate {
+    fn test(&self, did: &Did<'_>) -> bool {
+        match self {
+            Self::Or(terms) => terms.iter().any(|term| term.test(did)),
+            Self::And(terms) => terms.iter().all(|term| term.test(did)),
+            Self::Not(predicate) => !predicate.test(did),
+            Self::Test(operand) => operand
+                .values(did)
+                .iter()
+                .any(|value| !matches!(value, Value::Empty | Value::Logical(false))),
+            Self::Compare(left, operator, right) => {
+                let right = right.values(did);
+                left.values(did).iter().any(|left| {
+                    right
+                        .iter()
+                        .any(|right| operator.holds(left.compare(right)))
+                })
+            }
+        }
+    }
+}
+
+impl Operand {
+    fn values<'a>(&'a self, did: &'a Did<'_>) -> Vec<Value<'a>> {
+        match self {
+            Self::Path(segments) => apply(segments, vec![did])
+                .into_iter()
+                .map(Value::of)
+                .collect(),
+            Self::Literal(value) => vec![value.clone()],
+        }
+    }
+}
+
+impl Operator {
+    fn holds(self, ordering: Option<Ordering>) -> bool {
+        match self {
+            Self::Equal => ordering == Some(Ordering::Equal),
+            Self::NotEqual => ordering != Some(Ordering::Equal),
+            Self::Less => ordering == Some(Ordering::Less),
+            Self::LessOrEqual => matches!(ordering, Some(Ordering::Less | Ordering::Equal)),
+            Self::Greater => ordering == Some(Ordering::Greater),
+            Self::GreaterOrEqual => matches!(ordering, Some(Ordering::Greater | Ordering::Equal)),
+        }
+    }
+}
+
+impl<'a> Value<'a> {
+    fn of(did: &'a Did<'_>) -> Self {
+        match did {
+            Did::Empty(_) => Self::Empty,
+            Did::Logical(node) => Self::Logical(node.value),
+            Did::Number(node) => node.as_decimal().map_or(Self::Other, Self::Number),
+            Did::Text(node) => node.unescape().map_or(Self::Other, Self::Text),
+            _ => Self::Other,
+        }
+    }
+
+    /// `None` for values of different types, and for logical values that differ.
+    fn compare(&self, other: &Self) -> Option<Ordering> {
+        match (self, other) {
+            (Self::Empty, Self::Empty) => Some(Ordering::Equal),
+            (Self::Logical(a), Self::Logical(b)) => (a == b).then_some(Ordering::Equal),
+            (Self::Number(a), Self::Number(b)) => Some(a.cmp(b)),
+            (Self::Text(a), Self::Text(b)) => Some(a.cmp(b)),
+            _ => None,
+        }
+    }
+}
+
+struct Parser<'q> {
+    query: &'q str,
+    /// Byte offset of the next character.
+    position: usize,
+    /// Conditions being parsed, see [`MAX_DEPTH`].
+    depth: usize,
+}
+
+/// The most conditions that can be nested in one another with `!`, parentheses and
+/// filters. Parsing and testing them recurse, so this keeps hostile queries from
+/// overflowing the stack, like [`ParseOptions::max_depth`] does for documents.
+///
+/// [`ParseOptions::max_depth`]: crate::parser::ParseOptions::max_depth
+const MAX_DEPTH: usize = 128;
+
+impl<'q> Parser<'q> {
+    /// Steps up to the end of the query, or, for a `relative` path in a condition,
+    /// up to anything that cannot continue it.
+    fn segments(&mut self, relative: bool) -> Result<Vec<Segment>, QueryError> {
+        let mut segments = Vec::new();
+        loop {
+            self.skip_whitespace();
+            let segment = match self.peek() {
+                Some('.') if self.eat("..") => Segment::Descendants,
+                Some('.') => {
+                    self.position += 1;
+                    match self.peek() {
+                        Some('*') => {
+                            self.position += 1;
+                            Segment::Children
+                        }
+                        Some('{') => self.select()?,
+                        _ => Segment::Key(self.key()?),
+                    }
+                }
+                Some('[') => {
+                    self.position += 1;
+                    self.skip_whitespace();
+                    let segment = match self.peek() {
+                        Some('*') => {
+                            self.position += 1;
+                            Segment::Children
+                        }
+                        Some('?') => {
+                            self.position += 1;
+                            Segment::Filter(self.or()?)
+                        }
+                        Some(c) if c.is_ascii_digit() => Segment::Index(self.index()?),
+                        _ => return Err(self.unexpected()),
+                    };
+                    self.skip_whitespace();
+                    self.expect(']')?;
+                    segment
+                }
+                Some('{') => self.select()?,
+                _ if relative => break,
+                Some(_) => match self.word() {
+                    Some(name) => Segment::Name(name.to_owned()),
+                    None => return Err(self.unexpected()),
+                },
+                None => break,
+            };
+            segments.push(segment);
+        }
+        Ok(segments)
+    }
+
+    fn select(&mut self) -> Result<Segment, QueryError> {
+        self.expect('{')?;
+        let mut keys = Vec::new();
+        loop {
+            self.skip_whitespace();
+            keys.push(self.key()?);
+            self.skip_whitespace();
+            if !self.eat(",") {
+                self.expect('}')?;
+                return Ok(Segment::Select(keys));
+            }
+        }
+    }
+
+    fn key(&mut self) -> Result<String, QueryError> {
+        match self.peek() {
+            Some('"') => self.text(),
+            _ => match self.word() {
+                Some(key) => Ok(key.to_owned()),
+                None => Err(self.unexpected()),
+            },
+        }
+    }
+
+    fn or(&mut self) -> Result<Predicate, QueryError> {
+        let mut terms = vec![self.and()?];
+        while {
+            self.skip_whitespace();
+            self.eat("||")
+        } {
+            terms.push(self.and()?);
+        }
+        Ok(match terms.len() {
+            1 => terms.remove(0),
+            _ => Predicate::Or(terms),
+        })
+    }
+
+    fn and(&mut self) -> Result<Predicate, QueryError> {
+        let mut terms = vec![self.not()?];
+        while {
+            self.skip_whitespace();
+            self.eat("&&")
+        } {
+            terms.push(self.not()?);
+        }
+        Ok(match terms.len() {
+            1 => terms.remove(0),
+            _ => Predicate::And(terms),
+        })
+    }
+
+    fn not(&mut self) -> Result<Predicate, QueryError> {
+        self.skip_whitespace();
+        if self.depth == MAX_DEPTH {
+            return Err(self.error_at(self.position, QueryErrorKind::TooDeep(MAX_DEPTH)));
+        }
+        self.depth += 1;
+        let predicate = self.term();
+        self.depth -= 1;
+        predicate
+    }
+
+    fn term(&mut self) -> Result<Predicate, QueryError> {
+        if self.eat("!") {
+            return Ok(Predicate::Not(Box::new(self.not()?)));
+        }
+        if self.eat("(") {
+            let predicate = self.or()?;
+            self.skip_whitespace();
+            self.expect(')')?;
+            return Ok(predicate);
+        }
+        let left = self.operand()?;
+        self.skip_whitespace();
+        let operator = [
+            ("==", Operator::Equal),
+            ("!=", Operator::NotEqual),
+            ("<=", Operator::LessOrEqual),
+            (">=", Operator::GreaterOrEqual),
+            ("<", Operator::Less),
+            (">", Operator::Greater),
+        ]
+        .into_iter()
+        .find_map(|(token, operator)| self.eat(token).then_some(operator));
+        match operator {
+            Some(operator) => Ok(Predicate::Compare(left, operator, self.operand()?)),
+            None => Ok(Predicate::Test(left)),
+        }
+    }
+
+    fn operand(&mut self) -> Result<Operand, QueryError> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('@') => {
+                self.position += 1;
+                Ok(Operand::Path(self.segments(true)?))
+            }
+            Some('.' | '[') => Ok(Operand::Path(self.segments(true)?)),
+            Some('"') => Ok(Operand::Literal(Value::Text(self.text()?.into()))),
+            Some(c) if c.is_ascii_digit() || c == '-' || c == '+' => {
+                Ok(Operand::Literal(Value::Number(self.number()?)))
+            }
+            _ => match self.word() {
+                Some("пусто") => Ok(Operand::Literal(Value::Empty)),
+                Some("так") => Ok(Operand::Literal(Value::Logical(true))),
+                Some("ні") => Ok(Operand::Literal(Value::Logical(false))),
+                Some(key) => {
+                    let mut segments = vec![Segment::Key(key.to_owned())];
+                    segments.extend(self.segments(true)?);
+                    Ok(Operand::Path(segments))
+                }
+                None => Err(self.unexpected()),
+            },
+        }
+    }
+
+    /// An identifier, or a keyword.
+    fn word(&mut self) -> Option<&'q str> {
+        let rest = &self.query[self.position..];
+        let mut chars = rest.char_indices().peekable();
+        chars.next_if(|&(_, c)| c == '_' || is_alpha(c))?;
+        let mut len = rest.len();
+        while let Some((i, c)) = chars.next() {
+            let continues = match c {
+                c if c.is_ascii_digit() || c == '_' || is_alpha(c) => true,
+                '\'' | 'ʼ' => chars.peek().is_some_and(|&(_, c)| is_alpha(c)),
+                _ => false,
+            };
+            if !continues {
+                len = i;
+                break;
+            }
+        }
+        self.position += len;
+        Some(&rest[..len])
+    }
+
+    fn text(&mut self) -> Result<String, QueryError> {
+        let start = self.position;
+        self.expect('"')?;
+        let rest = &self.query[self.position..];
+        let mut escaped = false;
+        let end = rest
+            .find(|c| {
+                let end = !escaped && c == '"';
+                escaped = !escaped && c == '\\';
+                end
+            })
+            .ok_or_else(|| self.error_at(self.query.len(), QueryErrorKind::UnexpectedEnd))?;
+        let context = NodeContext::at(self.query, start);
+        let text = unescape(&rest[..end], context).map_err(|err| QueryError {
+            kind: QueryErrorKind::InvalidEscape(err.kind),
+            column: err.context.column as usize,
+        })?;
+        self.position += end + 1;
+        Ok(text.into_owned())
+    }
+
+    fn number(&mut self) -> Result<Decimal, QueryError> {
+        let start = self.position;
+        let rest = &self.query[start..];
+        let hex = rest.trim_start_matches(['-', '+']).starts_with("0x");
+        let mut previous = None;
+        let len = rest
+            .char_indices()
+            .find(|&(i, c)| {
+                let exponent_sign = !hex && matches!(previous, Some('e' | 'E'));
+                let part = c.is_ascii_alphanumeric()
+                    || matches!(c, '_' | '.')
+                    || (matches!(c, '-' | '+') && (i == 0 || exponent_sign));
+                previous = Some(c);
+                !part
+            })
+            .map_or(rest.len(), |(i, _)| i);
+        self.position += len;
+        rest[..len]
+            .parse()
+            .map_err(|_| self.error_at(start, QueryErrorKind::InvalidNumber))
+    }
+
+    fn index(&mut self) -> Result<usize, QueryError> {
+        let start = self.position;
+        let rest = &self.query[start..];
+        let len = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        self.position += len;
+        rest[..len]
+            .parse()
+            .map_err(|_| self.error_at(start, QueryErrorKind::InvalidNumber))
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.query[self.position..].chars().next()
+    }
+
+    fn eat(&mut self, token: &str) -> bool {
+        let found = self.query[self.position..].starts_with(token);
+        if found {
+            self.position += token.len();
+        }
+        found
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), QueryError> {
+        if self.peek() == Some(c) {
+            self.position += c.len_utf8();
+            Ok(())
+        } else {
+            Err(self.unexpected())
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = &self.query[self.position..];
+        self.position += rest.len() - rest.trim_start().len();
+    }
+
+    fn unexpected(&self) -> QueryError {
+        let kind = match self.peek() {
+            Some(c) => QueryErrorKind::UnexpectedChar(c),
+            None => QueryErrorKind::UnexpectedEnd,
+        };
+        self.error_at(self.position, kind)
+    }
+
+    fn error_at(&self, position: usize, kind: QueryErrorKind) -> QueryError {
+        QueryError {
+            kind,
+            column: self.query[..position].chars().count() + 1,
+        }
+    }
+}
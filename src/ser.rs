@@ -0,0 +1,455 @@
+//! Serializing types that implement [`serde::Serialize`] into `Дід`.
+
+use std::fmt;
+
+use serde::{ser, Serialize};
+
+use crate::{
+    de::VARIANT_VALUE_KEY,
+    parser::ast::{
+        DictionaryEntryKey, DictionaryEntryNode, DictionaryNode, Did, EmptyNode, ListNode,
+        LogicalNode, NodeContext, NumberNode, ObjectEntryNode, ObjectNode, TextNode,
+    },
+    writer,
+};
+
+/// Serializes `value` into compact single-line `Дід` text.
+pub fn to_string<T: Serialize + ?Sized>(value: &T) -> Result<String, Error> {
+    to_did(value).map(|did| writer::to_string(&did))
+}
+
+/// Serializes `value` into `Дід` text with every entry on its own line.
+pub fn to_string_pretty<T: Serialize + ?Sized>(value: &T) -> Result<String, Error> {
+    to_did(value).map(|did| writer::to_string_pretty(&did))
+}
+
+/// Serializes `value` into a native syntax tree.
+///
+/// - structs become objects named after the struct: `Людина(імʼя="Леся")`;
+/// - maps become dictionaries, sequences and tuples become lists;
+/// - `None` and `()` become `пусто`;
+/// - enum variants become objects named after the variant. The content of
+///   newtype and tuple variants is stored in the `значення` entry.
+pub fn to_did<T: Serialize + ?Sized>(value: &T) -> Result<Did<'static>, Error> {
+    value.serialize(Serializer)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Error(String);
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl ser::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Self(msg.to_string())
+    }
+}
+
+fn number(value: impl fmt::Display) -> Did<'static> {
+    Did::Number(NumberNode {
+        value: value.to_string().into(),
+        context: NodeContext::default(),
+    })
+}
+
+fn float(value: impl fmt::Display, is_finite: bool) -> Result<Did<'static>, Error> {
+    if is_finite {
+        Ok(number(value))
+    } else {
+        Err(Error(format!(
+            "число {value} не можна записати у форматі `Дід`"
+        )))
+    }
+}
+
+fn text(value: &str) -> TextNode<'static> {
+    TextNode {
+        value: writer::escape(value).into_owned().into(),
+        context: NodeContext::default(),
+    }
+}
+
+fn ident(value: &str) -> Result<TextNode<'static>, Error> {
+    if writer::is_identifier(value) {
+        Ok(TextNode {
+            value: value.to_owned().into(),
+            context: NodeContext::default(),
+        })
+    } else {
+        Err(Error(format!(
+            "`{value}` не є ідентифікатором, тому не може бути назвою чи ключем об'єкта"
+        )))
+    }
+}
+
+fn object(name: &str, entries: Vec<ObjectEntryNode<'static>>) -> Result<Did<'static>, Error> {
+    Ok(Did::Object(ObjectNode {
+        name: ident(name)?,
+        entries,
+        context: NodeContext::default(),
+    }))
+}
+
+fn object_entry(key: &str, value: Did<'static>) -> Result<ObjectEntryNode<'static>, Error> {
+    Ok(ObjectEntryNode {
+        key: ident(key)?,
+        value,
+        context: NodeContext::default(),
+    })
+}
+
+fn list(entries: Vec<Did<'static>>) -> Did<'static> {
+    Did::List(ListNode {
+        entries,
+        context: NodeContext::default(),
+    })
+}
+
+/// Serializer that builds a native syntax tree.
+pub struct Serializer;
+
+impl ser::Serializer for Serializer {
+    type Ok = Did<'static>;
+    type Error = Error;
+
+    type SerializeSeq = SerializeList;
+    type SerializeTuple = SerializeList;
+    type SerializeTupleStruct = SerializeList;
+    type SerializeTupleVariant = SerializeList;
+    type SerializeMap = SerializeDictionary;
+    type SerializeStruct = SerializeObject;
+    type SerializeStructVariant = SerializeObject;
+
+    fn serialize_bool(self, v: bool) -> Result<Did<'static>, Error> {
+        Ok(Did::Logical(LogicalNode {
+            value: v,
+            context: NodeContext::default(),
+        }))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Did<'static>, Error> {
+        Ok(number(v))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Did<'static>, Error> {
+        Ok(number(v))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Did<'static>, Error> {
+        Ok(number(v))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Did<'static>, Error> {
+        Ok(number(v))
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<Did<'static>, Error> {
+        Ok(number(v))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Did<'static>, Error> {
+        Ok(number(v))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Did<'static>, Error> {
+        Ok(number(v))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Did<'static>, Error> {
+        Ok(number(v))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Did<'static>, Error> {
+        Ok(number(v))
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<Did<'static>, Error> {
+        Ok(number(v))
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Did<'static>, Error> {
+        float(v, v.is_finite())
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Did<'static>, Error> {
+        float(v, v.is_finite())
+    }
+
+    fn serialize_char(self, v: char) -> Result<Did<'static>, Error> {
+        Ok(Did::Text(text(v.encode_utf8(&mut [0; 4]))))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Did<'static>, Error> {
+        Ok(Did::Text(text(v)))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Did<'static>, Error> {
+        Ok(list(v.iter().copied().map(number).collect()))
+    }
+
+    fn serialize_none(self) -> Result<Did<'static>, Error> {
+        self.serialize_unit()
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Did<'static>, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Did<'static>, Error> {
+        Ok(Did::Empty(EmptyNode {
+            context: NodeContext::default(),
+        }))
+    }
+
+    fn serialize_unit_struct(self, name: &'static str) -> Result<Did<'static>, Error> {
+        object(name, Vec::new())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Did<'static>, Error> {
+        object(variant, Vec::new())
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Did<'static>, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Did<'static>, Error> {
+        let value = value.serialize(Serializer)?;
+        object(variant, vec![object_entry(VARIANT_VALUE_KEY, value)?])
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<SerializeList, Error> {
+        Ok(SerializeList {
+            variant: None,
+            entries: Vec::with_capacity(len.unwrap_or_default()),
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SerializeList, Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<SerializeList, Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SerializeList, Error> {
+        Ok(SerializeList {
+            variant: Some(variant),
+            entries: Vec::with_capacity(len),
+        })
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<SerializeDictionary, Error> {
+        Ok(SerializeDictionary {
+            entries: Vec::with_capacity(len.unwrap_or_default()),
+            key: None,
+        })
+    }
+
+    fn serialize_struct(self, name: &'static str, len: usize) -> Result<SerializeObject, Error> {
+        Ok(SerializeObject {
+            name,
+            entries: Vec::with_capacity(len),
+        })
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SerializeObject, Error> {
+        self.serialize_struct(variant, len)
+    }
+}
+
+pub struct SerializeList {
+    /// Name of the tuple variant being serialized.
+    variant: Option<&'static str>,
+    entries: Vec<Did<'static>>,
+}
+
+impl SerializeList {
+    fn push<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.entries.push(value.serialize(Serializer)?);
+        Ok(())
+    }
+
+    fn finish(self) -> Result<Did<'static>, Error> {
+        let list = list(self.entries);
+        match self.variant {
+            Some(variant) => object(variant, vec![object_entry(VARIANT_VALUE_KEY, list)?]),
+            None => Ok(list),
+        }
+    }
+}
+
+impl ser::SerializeSeq for SerializeList {
+    type Ok = Did<'static>;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Did<'static>, Error> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTuple for SerializeList {
+    type Ok = Did<'static>;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Did<'static>, Error> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleStruct for SerializeList {
+    type Ok = Did<'static>;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Did<'static>, Error> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleVariant for SerializeList {
+    type Ok = Did<'static>;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Did<'static>, Error> {
+        self.finish()
+    }
+}
+
+pub struct SerializeDictionary {
+    entries: Vec<DictionaryEntryNode<'static>>,
+    key: Option<DictionaryEntryKey<'static>>,
+}
+
+impl ser::SerializeMap for SerializeDictionary {
+    type Ok = Did<'static>;
+    type Error = Error;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Error> {
+        self.key = Some(match key.serialize(Serializer)? {
+            Did::Number(node) => DictionaryEntryKey::Number(node),
+            Did::Text(node) => DictionaryEntryKey::Text(node),
+            Did::Object(node) if node.entries.is_empty() => DictionaryEntryKey::Text(node.name),
+            _ => {
+                return Err(Error(
+                    "ключ словника має бути текстом або числом".to_owned(),
+                ))
+            }
+        });
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        let key = self
+            .key
+            .take()
+            .expect("serialize_value called before serialize_key");
+        self.entries.push(DictionaryEntryNode {
+            key,
+            value: value.serialize(Serializer)?,
+            context: NodeContext::default(),
+        });
+        Ok(())
+    }
+
+    fn end(self) -> Result<Did<'static>, Error> {
+        Ok(Did::Dictionary(DictionaryNode {
+            entries: self.entries,
+            context: NodeContext::default(),
+        }))
+    }
+}
+
+pub struct SerializeObject {
+    name: &'static str,
+    entries: Vec<ObjectEntryNode<'static>>,
+}
+
+impl ser::SerializeStruct for SerializeObject {
+    type Ok = Did<'static>;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        self.entries
+            .push(object_entry(key, value.serialize(Serializer)?)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Did<'static>, Error> {
+        object(self.name, self.entries)
+    }
+}
+
+impl ser::SerializeStructVariant for SerializeObject {
+    type Ok = Did<'static>;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        ser::SerializeStruct::serialize_field(self, key, value)
+    }
+
+    fn end(self) -> Result<Did<'static>, Error> {
+        ser::SerializeStruct::end(self)
+    }
+}
@@ -0,0 +1,418 @@
+//! Walking and transforming the abstract syntax tree.
+//!
+//! [`Visitor`] walks a tree by references, [`VisitorMut`] changes it in place, and
+//! [`Fold`] builds a new tree out of an old one. Every method walks the nested nodes by
+//! default with the `walk_*` or `fold_*` function of this module, so it is enough to
+//! override only the methods of the nodes of interest and call these functions from
+//! them when needed.
+//!
+//! Keys of entries and names of objects are walked too: by default they are passed to
+//! `visit_text` or `visit_number`.
+//!
+//! ```
+//! use mavka_did::{
+//!     parser::{self, ast::ObjectNode},
+//!     visit::{self, Visitor},
+//! };
+//!
+//! /// Collects the names of all objects.
+//! struct Names<'ast>(Vec<&'ast str>);
+//!
+//! impl<'ast> Visitor<'ast> for Names<'ast> {
+//!     fn visit_object(&mut self, node: &'ast ObjectNode<'ast>) {
+//!         self.0.push(&node.name.value);
+//!         visit::walk_object(self, node);
+//!     }
+//! }
+//!
+//! let document = parser::parse_did("Склад(товари=[Ноутбук(ціна=10), Телефон()])").unwrap();
+//! let mut names = Names(Vec::new());
+//! names.visit_did(&document);
+//! assert_eq!(names.0, ["Склад", "Ноутбук", "Телефон"]);
+//! ```
+
+use crate::parser::ast::{
+    DictionaryEntryKey, DictionaryEntryNode, DictionaryNode, Did, EmptyNode, ErrorNode, ListNode,
+    LogicalNode, NumberNode, ObjectEntryNode, ObjectNode, TextNode,
+};
+
+/// Walks a tree by shared references.
+///
+/// Nodes are borrowed for `'ast`, so the visitor can keep references to them.
+pub trait Visitor<'ast> {
+    fn visit_did(&mut self, did: &'ast Did<'ast>) {
+        walk_did(self, did);
+    }
+
+    fn visit_empty(&mut self, _node: &'ast EmptyNode) {}
+
+    fn visit_logical(&mut self, _node: &'ast LogicalNode) {}
+
+    fn visit_number(&mut self, _node: &'ast NumberNode<'ast>) {}
+
+    fn visit_text(&mut self, _node: &'ast TextNode<'ast>) {}
+
+    fn visit_dictionary(&mut self, node: &'ast DictionaryNode<'ast>) {
+        walk_dictionary(self, node);
+    }
+
+    fn visit_dictionary_entry(&mut self, entry: &'ast DictionaryEntryNode<'ast>) {
+        walk_dictionary_entry(self, entry);
+    }
+
+    fn visit_dictionary_key(&mut self, key: &'ast DictionaryEntryKey<'ast>) {
+        walk_dictionary_key(self, key);
+    }
+
+    fn visit_object(&mut self, node: &'ast ObjectNode<'ast>) {
+        walk_object(self, node);
+    }
+
+    fn visit_object_name(&mut self, name: &'ast TextNode<'ast>) {
+        self.visit_text(name);
+    }
+
+    fn visit_object_entry(&mut self, entry: &'ast ObjectEntryNode<'ast>) {
+        walk_object_entry(self, entry);
+    }
+
+    fn visit_object_key(&mut self, key: &'ast TextNode<'ast>) {
+        self.visit_text(key);
+    }
+
+    fn visit_list(&mut self, node: &'ast ListNode<'ast>) {
+        walk_list(self, node);
+    }
+
+    fn visit_error(&mut self, _node: &'ast ErrorNode<'ast>) {}
+}
+
+pub fn walk_did<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, did: &'ast Did<'ast>) {
+    match did {
+        Did::Empty(node) => visitor.visit_empty(node),
+        Did::Logical(node) => visitor.visit_logical(node),
+        Did::Number(node) => visitor.visit_number(node),
+        Did::Text(node) => visitor.visit_text(node),
+        Did::Dictionary(node) => visitor.visit_dictionary(node),
+        Did::Object(node) => visitor.visit_object(node),
+        Did::List(node) => visitor.visit_list(node),
+        Did::Error(node) => visitor.visit_error(node),
+    }
+}
+
+pub fn walk_dictionary<'ast, V: Visitor<'ast> + ?Sized>(
+    visitor: &mut V,
+    node: &'ast DictionaryNode<'ast>,
+) {
+    for entry in &node.entries {
+        visitor.visit_dictionary_entry(entry);
+    }
+}
+
+pub fn walk_dictionary_entry<'ast, V: Visitor<'ast> + ?Sized>(
+    visitor: &mut V,
+    entry: &'ast DictionaryEntryNode<'ast>,
+) {
+    visitor.visit_dictionary_key(&entry.key);
+    visitor.visit_did(&entry.value);
+}
+
+pub fn walk_dictionary_key<'ast, V: Visitor<'ast> + ?Sized>(
+    visitor: &mut V,
+    key: &'ast DictionaryEntryKey<'ast>,
+) {
+    match key {
+        DictionaryEntryKey::Number(key) => visitor.visit_number(key),
+        DictionaryEntryKey::Text(key) => visitor.visit_text(key),
+    }
+}
+
+pub fn walk_object<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, node: &'ast ObjectNode<'ast>) {
+    visitor.visit_object_name(&node.name);
+    for entry in &node.entries {
+        visitor.visit_object_entry(entry);
+    }
+}
+
+pub fn walk_object_entry<'ast, V: Visitor<'ast> + ?Sized>(
+    visitor: &mut V,
+    entry: &'ast ObjectEntryNode<'ast>,
+) {
+    visitor.visit_object_key(&entry.key);
+    visitor.visit_did(&entry.value);
+}
+
+pub fn walk_list<'ast, V: Visitor<'ast> + ?Sized>(visitor: &mut V, node: &'ast ListNode<'ast>) {
+    for did in &node.entries {
+        visitor.visit_did(did);
+    }
+}
+
+/// Walks a tree by mutable references, to change it in place.
+pub trait VisitorMut<'inp> {
+    fn visit_did_mut(&mut self, did: &mut Did<'inp>) {
+        walk_did_mut(self, did);
+    }
+
+    fn visit_empty_mut(&mut self, _node: &mut EmptyNode) {}
+
+    fn visit_logical_mut(&mut self, _node: &mut LogicalNode) {}
+
+    fn visit_number_mut(&mut self, _node: &mut NumberNode<'inp>) {}
+
+    fn visit_text_mut(&mut self, _node: &mut TextNode<'inp>) {}
+
+    fn visit_dictionary_mut(&mut self, node: &mut DictionaryNode<'inp>) {
+        walk_dictionary_mut(self, node);
+    }
+
+    fn visit_dictionary_entry_mut(&mut self, entry: &mut DictionaryEntryNode<'inp>) {
+        walk_dictionary_entry_mut(self, entry);
+    }
+
+    fn visit_dictionary_key_mut(&mut self, key: &mut DictionaryEntryKey<'inp>) {
+        walk_dictionary_key_mut(self, key);
+    }
+
+    fn visit_object_mut(&mut self, node: &mut ObjectNode<'inp>) {
+        walk_object_mut(self, node);
+    }
+
+    fn visit_object_name_mut(&mut self, name: &mut TextNode<'inp>) {
+        self.visit_text_mut(name);
+    }
+
+    fn visit_object_entry_mut(&mut self, entry: &mut ObjectEntryNode<'inp>) {
+        walk_object_entry_mut(self, entry);
+    }
+
+    fn visit_object_key_mut(&mut self, key: &mut TextNode<'inp>) {
+        self.visit_text_mut(key);
+    }
+
+    fn visit_list_mut(&mut self, node: &mut ListNode<'inp>) {
+        walk_list_mut(self, node);
+    }
+
+    fn visit_error_mut(&mut self, _node: &mut ErrorNode<'inp>) {}
+}
+
+pub fn walk_did_mut<'inp, V: VisitorMut<'inp> + ?Sized>(visitor: &mut V, did: &mut Did<'inp>) {
+    match did {
+        Did::Empty(node) => visitor.visit_empty_mut(node),
+        Did::Logical(node) => visitor.visit_logical_mut(node),
+        Did::Number(node) => visitor.visit_number_mut(node),
+        Did::Text(node) => visitor.visit_text_mut(node),
+        Did::Dictionary(node) => visitor.visit_dictionary_mut(node),
+        Did::Object(node) => visitor.visit_object_mut(node),
+        Did::List(node) => visitor.visit_list_mut(node),
+        Did::Error(node) => visitor.visit_error_mut(node),
+    }
+}
+
+pub fn walk_dictionary_mut<'inp, V: VisitorMut<'inp> + ?Sized>(
+    visitor: &mut V,
+    node: &mut DictionaryNode<'inp>,
+) {
+    for entry in &mut node.entries {
+        visitor.visit_dictionary_entry_mut(entry);
+    }
+}
+
+pub fn walk_dictionary_entry_mut<'inp, V: VisitorMut<'inp> + ?Sized>(
+    visitor: &mut V,
+    entry: &mut DictionaryEntryNode<'inp>,
+) {
+    visitor.visit_dictionary_key_mut(&mut entry.key);
+    visitor.visit_did_mut(&mut entry.value);
+}
+
+pub fn walk_dictionary_key_mut<'inp, V: VisitorMut<'inp> + ?Sized>(
+    visitor: &mut V,
+    key: &mut DictionaryEntryKey<'inp>,
+) {
+    match key {
+        DictionaryEntryKey::Number(key) => visitor.visit_number_mut(key),
+        DictionaryEntryKey::Text(key) => visitor.visit_text_mut(key),
+    }
+}
+
+pub fn walk_object_mut<'inp, V: VisitorMut<'inp> + ?Sized>(
+    visitor: &mut V,
+    node: &mut ObjectNode<'inp>,
+) {
+    visitor.visit_object_name_mut(&mut node.name);
+    for entry in &mut node.entries {
+        visitor.visit_object_entry_mut(entry);
+    }
+}
+
+pub fn walk_object_entry_mut<'inp, V: VisitorMut<'inp> + ?Sized>(
+    visitor: &mut V,
+    entry: &mut ObjectEntryNode<'inp>,
+) {
+    visitor.visit_object_key_mut(&mut entry.key);
+    visitor.visit_did_mut(&mut entry.value);
+}
+
+pub fn walk_list_mut<'inp, V: VisitorMut<'inp> + ?Sized>(
+    visitor: &mut V,
+    node: &mut ListNode<'inp>,
+) {
+    for did in &mut node.entries {
+        visitor.visit_did_mut(did);
+    }
+}
+
+/// Builds a new tree out of an old one, taking it by value.
+///
+/// Entries and list elements cannot be removed by the methods of their own nodes;
+/// the method of their container can do it.
+pub trait Fold<'inp> {
+    fn fold_did(&mut self, did: Did<'inp>) -> Did<'inp> {
+        fold_did(self, did)
+    }
+
+    fn fold_empty(&mut self, node: EmptyNode) -> EmptyNode {
+        node
+    }
+
+    fn fold_logical(&mut self, node: LogicalNode) -> LogicalNode {
+        node
+    }
+
+    fn fold_number(&mut self, node: NumberNode<'inp>) -> NumberNode<'inp> {
+        node
+    }
+
+    fn fold_text(&mut self, node: TextNode<'inp>) -> TextNode<'inp> {
+        node
+    }
+
+    fn fold_dictionary(&mut self, node: DictionaryNode<'inp>) -> DictionaryNode<'inp> {
+        fold_dictionary(self, node)
+    }
+
+    fn fold_dictionary_entry(
+        &mut self,
+        entry: DictionaryEntryNode<'inp>,
+    ) -> DictionaryEntryNode<'inp> {
+        fold_dictionary_entry(self, entry)
+    }
+
+    fn fold_dictionary_key(&mut self, key: DictionaryEntryKey<'inp>) -> DictionaryEntryKey<'inp> {
+        fold_dictionary_key(self, key)
+    }
+
+    fn fold_object(&mut self, node: ObjectNode<'inp>) -> ObjectNode<'inp> {
+        fold_object(self, node)
+    }
+
+    fn fold_object_name(&mut self, name: TextNode<'inp>) -> TextNode<'inp> {
+        self.fold_text(name)
+    }
+
+    fn fold_object_entry(&mut self, entry: ObjectEntryNode<'inp>) -> ObjectEntryNode<'inp> {
+        fold_object_entry(self, entry)
+    }
+
+    fn fold_object_key(&mut self, key: TextNode<'inp>) -> TextNode<'inp> {
+        self.fold_text(key)
+    }
+
+    fn fold_list(&mut self, node: ListNode<'inp>) -> ListNode<'inp> {
+        fold_list(self, node)
+    }
+
+    fn fold_error(&mut self, node: ErrorNode<'inp>) -> ErrorNode<'inp> {
+        node
+    }
+}
+
+pub fn fold_did<'inp, F: Fold<'inp> + ?Sized>(folder: &mut F, did: Did<'inp>) -> Did<'inp> {
+    match did {
+        Did::Empty(node) => Did::Empty(folder.fold_empty(node)),
+        Did::Logical(node) => Did::Logical(folder.fold_logical(node)),
+        Did::Number(node) => Did::Number(folder.fold_number(node)),
+        Did::Text(node) => Did::Text(folder.fold_text(node)),
+        Did::Dictionary(node) => Did::Dictionary(folder.fold_dictionary(node)),
+        Did::Object(node) => Did::Object(folder.fold_object(node)),
+        Did::List(node) => Did::List(folder.fold_list(node)),
+        Did::Error(node) => Did::Error(folder.fold_error(node)),
+    }
+}
+
+pub fn fold_dictionary<'inp, F: Fold<'inp> + ?Sized>(
+    folder: &mut F,
+    node: DictionaryNode<'inp>,
+) -> DictionaryNode<'inp> {
+    DictionaryNode {
+        entries: node
+            .entries
+            .into_iter()
+            .map(|entry| folder.fold_dictionary_entry(entry))
+            .collect(),
+        context: node.context,
+    }
+}
+
+pub fn fold_dictionary_entry<'inp, F: Fold<'inp> + ?Sized>(
+    folder: &mut F,
+    entry: DictionaryEntryNode<'inp>,
+) -> DictionaryEntryNode<'inp> {
+    DictionaryEntryNode {
+        key: folder.fold_dictionary_key(entry.key),
+        value: folder.fold_did(entry.value),
+        context: entry.context,
+    }
+}
+
+pub fn fold_dictionary_key<'inp, F: Fold<'inp> + ?Sized>(
+    folder: &mut F,
+    key: DictionaryEntryKey<'inp>,
+) -> DictionaryEntryKey<'inp> {
+    match key {
+        DictionaryEntryKey::Number(key) => DictionaryEntryKey::Number(folder.fold_number(key)),
+        DictionaryEntryKey::Text(key) => DictionaryEntryKey::Text(folder.fold_text(key)),
+    }
+}
+
+pub fn fold_object<'inp, F: Fold<'inp> + ?Sized>(
+    folder: &mut F,
+    node: ObjectNode<'inp>,
+) -> ObjectNode<'inp> {
+    ObjectNode {
+        name: folder.fold_object_name(node.name),
+        entries: node
+            .entries
+            .into_iter()
+            .map(|entry| folder.fold_object_entry(entry))
+            .collect(),
+        context: node.context,
+    }
+}
+
+pub fn fold_object_entry<'inp, F: Fold<'inp> + ?Sized>(
+    folder: &mut F,
+    entry: ObjectEntryNode<'inp>,
+) -> ObjectEntryNode<'inp> {
+    ObjectEntryNode {
+        key: folder.fold_object_key(entry.key),
+        value: folder.fold_did(entry.value),
+        context: entry.context,
+    }
+}
+
+pub fn fold_list<'inp, F: Fold<'inp> + ?Sized>(
+    folder: &mut F,
+    node: ListNode<'inp>,
+) -> ListNode<'inp> {
+    ListNode {
+        entries: node
+            .entries
+            .into_iter()
+            .map(|did| folder.fold_did(did))
+            .collect(),
+        context: node.context,
+    }
+}
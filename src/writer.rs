@@ -0,0 +1,694 @@
+//! Writing syntax trees back into `Дід` source text.
+
+use std::{
+    borrow::Cow,
+    fmt::{self, Write},
+};
+
+use crate::parser::{
+    self,
+    ast::{DictionaryEntryKey, DictionaryNode, Did, ListNode, ObjectNode},
+    cst::{self, Element, NodeKind, TokenKind},
+};
+
+/// A syntax tree that can be written as `Дід` source text.
+pub trait Emit {
+    fn emit(&self, w: &mut Writer<'_>) -> fmt::Result;
+}
+
+/// Layout options of the written text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Style {
+    /// Number of columns per nesting level.
+    pub indent_width: usize,
+    /// Indent with tabs instead of spaces.
+    pub hard_tabs: bool,
+    /// Number of columns a tab takes when checking `max_width`.
+    pub tab_width: usize,
+    /// Lists, dictionaries and objects that do not fit into this width are wrapped,
+    /// one entry per line.
+    pub max_width: usize,
+    pub trailing_comma: TrailingComma,
+    /// Write `ключ = значення` instead of `ключ=значення`.
+    pub spaces_around_equals: bool,
+    /// Write `[1, 2]` instead of `[1,2]` when entries are not wrapped.
+    pub space_after_comma: bool,
+}
+
+/// When to put a comma after the last entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TrailingComma {
+    Always,
+    #[default]
+    Never,
+    /// Only when the entries are wrapped.
+    Vertical,
+}
+
+impl Style {
+    /// Everything on a single line, without optional spaces.
+    pub const fn compact() -> Self {
+        Self {
+            indent_width: 0,
+            hard_tabs: false,
+            tab_width: 4,
+            max_width: usize::MAX,
+            trailing_comma: TrailingComma::Never,
+            spaces_around_equals: false,
+            space_after_comma: false,
+        }
+    }
+
+    /// Every entry on its own line.
+    pub const fn pretty() -> Self {
+        Self {
+            indent_width: 2,
+            max_width: 0,
+            ..Self::compact()
+        }
+    }
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Self {
+            max_width: 80,
+            space_after_comma: true,
+            ..Self::pretty()
+        }
+    }
+}
+
+/// Writes `node` into a compact single-line string.
+pub fn to_string<T: Emit + ?Sized>(node: &T) -> String {
+    to_string_with(node, &Style::compact())
+}
+
+/// Writes `node` into a string with every entry on its own line.
+pub fn to_string_pretty<T: Emit + ?Sized>(node: &T) -> String {
+    to_string_with(node, &Style::pretty())
+}
+
+pub fn to_string_with<T: Emit + ?Sized>(node: &T, style: &Style) -> String {
+    let mut out = String::new();
+    write(&mut out, node, style).expect("writing to a String never fails");
+    out
+}
+
+/// Writes `node` into `out` laid out according to `style`.
+pub fn write<T: Emit + ?Sized>(out: &mut dyn Write, node: &T, style: &Style) -> fmt::Result {
+    node.emit(&mut Writer {
+        out,
+        style: *style,
+        depth: 0,
+        column: 0,
+        measuring: false,
+    })
+}
+
+/// Reformats a whole document.
+///
+/// Comments are kept: a comment on the line of an entry stays after it, other comments
+/// go on their own lines before the next entry or the closing bracket. Lists, dictionaries
+/// and objects with comments are always wrapped.
+///
+/// Formatting is stable: formatting the output again gives the same text.
+pub fn format(input: &str, style: &Style) -> Result<String, FormatError> {
+    let document = parser::parse_cst(input).map_err(FormatError::Parse)?;
+    let mut out = to_string_with(&document, style);
+    out.push('\n');
+    Ok(out)
+}
+
+#[derive(Debug)]
+pub enum FormatError {
+    Parse(crate::Error),
+}
+
+impl fmt::Display for FormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Parse(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for FormatError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Parse(err) => Some(err),
+        }
+    }
+}
+
+/// Output sink that keeps track of the nesting level and the current column.
+pub struct Writer<'o> {
+    out: &'o mut dyn Write,
+    style: Style,
+    depth: usize,
+    column: usize,
+    /// Set while checking whether entries fit on a line, where comments never fit.
+    measuring: bool,
+}
+
+/// An entry with the comments written before it and after it on the same line.
+pub(crate) struct Commented<'a, T> {
+    pub(crate) leading: Vec<&'a str>,
+    pub(crate) entry: &'a T,
+    pub(crate) trailing: Vec<&'a str>,
+}
+
+impl Writer<'_> {
+    fn put(&mut self, s: &str) -> fmt::Result {
+        self.column = match s.rfind('\n') {
+            Some(pos) => s[pos + 1..].chars().count(),
+            None => self.column + s.chars().count(),
+        };
+        self.out.write_str(s)
+    }
+
+    fn put_char(&mut self, c: char) -> fmt::Result {
+        self.put(c.encode_utf8(&mut [0; 4]))
+    }
+
+    pub(crate) fn empty(&mut self) -> fmt::Result {
+        self.put("пусто")
+    }
+
+    pub(crate) fn logical(&mut self, value: bool) -> fmt::Result {
+        self.put(if value { "так" } else { "ні" })
+    }
+
+    pub(crate) fn number(&mut self, value: &str) -> fmt::Result {
+        self.put(value)
+    }
+
+    /// Writes a quoted text, keeping valid escape sequences of `value` as they are.
+    pub(crate) fn text(&mut self, value: &str) -> fmt::Result {
+        self.put_char('"')?;
+        let mut chars = value.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => match escape_len(chars.as_str()) {
+                    Some(len) => {
+                        self.put_char('\\')?;
+                        self.put(&chars.as_str()[..len])?;
+                        chars = chars.as_str()[len..].chars();
+                    }
+                    None => self.put("\\\\")?,
+                },
+                c => self.escaped_char(c)?,
+            }
+        }
+        self.put_char('"')
+    }
+
+    fn escaped_char(&mut self, c: char) -> fmt::Result {
+        if needs_escape(c) {
+            let mut escaped = String::new();
+            push_escaped(&mut escaped, c);
+            self.put(&escaped)
+        } else {
+            self.put_char(c)
+        }
+    }
+
+    /// Writes a dictionary key, without quotes when it is a valid identifier.
+    pub(crate) fn key(&mut self, value: &str) -> fmt::Result {
+        if is_identifier(value) {
+            self.put(value)
+        } else {
+            self.text(value)
+        }
+    }
+
+    /// Writes an object name or an object entry key as is.
+    pub(crate) fn ident(&mut self, value: &str) -> fmt::Result {
+        self.put(value)
+    }
+
+    pub(crate) fn equals(&mut self) -> fmt::Result {
+        self.put(if self.style.spaces_around_equals {
+            " = "
+        } else {
+            "="
+        })
+    }
+
+    pub(crate) fn entries<T>(
+        &mut self,
+        open: char,
+        close: char,
+        entries: &[T],
+        mut entry: impl FnMut(&mut Writer<'_>, &T) -> fmt::Result,
+    ) -> fmt::Result {
+        let entries: Vec<_> = entries
+            .iter()
+            .map(|entry| Commented {
+                leading: Vec::new(),
+                entry,
+                trailing: Vec::new(),
+            })
+            .collect();
+        self.write_entries(open, close, &entries, &[], &mut entry)
+    }
+
+    /// Writes entries with their comments, `dangling` comments go after the last entry.
+    pub(crate) fn commented_entries<T>(
+        &mut self,
+        open: char,
+        close: char,
+        entries: &[Commented<'_, T>],
+        dangling: &[&str],
+        mut entry: impl FnMut(&mut Writer<'_>, &T) -> fmt::Result,
+    ) -> fmt::Result {
+        self.write_entries(open, close, entries, dangling, &mut entry)
+    }
+
+    fn write_entries<T>(
+        &mut self,
+        open: char,
+        close: char,
+        entries: &[Commented<'_, T>],
+        dangling: &[&str],
+        entry: &mut dyn FnMut(&mut Writer<'_>, &T) -> fmt::Result,
+    ) -> fmt::Result {
+        let commented = !dangling.is_empty()
+            || entries
+                .iter()
+                .any(|e| !e.leading.is_empty() || !e.trailing.is_empty());
+        if commented && self.measuring {
+            return Err(fmt::Error);
+        }
+        let wrap = commented || (!entries.is_empty() && !self.fits(open, close, entries, entry));
+        self.put_char(open)?;
+        if wrap {
+            self.depth += 1;
+        }
+        let trailing_comma = match self.style.trailing_comma {
+            TrailingComma::Always => true,
+            TrailingComma::Never => false,
+            TrailingComma::Vertical => wrap,
+        };
+        for (i, e) in entries.iter().enumerate() {
+            if wrap {
+                for comment in &e.leading {
+                    self.newline()?;
+                    self.put(comment)?;
+                }
+                self.newline()?;
+            }
+            entry(self, e.entry)?;
+            let last = i + 1 == entries.len();
+            if !last || trailing_comma {
+                self.put_char(',')?;
+            }
+            if !last && !wrap && self.style.space_after_comma {
+                self.put_char(' ')?;
+            }
+            for comment in &e.trailing {
+                self.put_char(' ')?;
+                self.put(comment)?;
+            }
+        }
+        if wrap {
+            for comment in dangling {
+                self.newline()?;
+                self.put(comment)?;
+            }
+            self.depth -= 1;
+            self.newline()?;
+        }
+        self.put_char(close)
+    }
+
+    /// Checks whether the entries fit into the rest of the line when written flat.
+    fn fits<T>(
+        &mut self,
+        open: char,
+        close: char,
+        entries: &[Commented<'_, T>],
+        entry: &mut dyn FnMut(&mut Writer<'_>, &T) -> fmt::Result,
+    ) -> bool {
+        if self.style.max_width == usize::MAX {
+            return true;
+        }
+        let Some(limit) = self.style.max_width.checked_sub(self.column) else {
+            return false;
+        };
+        let mut measure = Measure { width: 0, limit };
+        let mut flat = Writer {
+            out: &mut measure,
+            style: Style {
+                max_width: usize::MAX,
+                ..self.style
+            },
+            depth: 0,
+            column: 0,
+            measuring: true,
+        };
+        flat.write_entries(open, close, entries, &[], entry).is_ok()
+    }
+
+    fn newline(&mut self) -> fmt::Result {
+        self.put_char('\n')?;
+        for _ in 0..self.depth {
+            if self.style.hard_tabs {
+                self.out.write_char('\t')?;
+            } else {
+                for _ in 0..self.style.indent_width {
+                    self.out.write_char(' ')?;
+                }
+            }
+        }
+        self.column = self.depth
+            * if self.style.hard_tabs {
+                self.style.tab_width
+            } else {
+                self.style.indent_width
+            };
+        Ok(())
+    }
+}
+
+/// Sink that only counts the written characters, failing past the limit.
+struct Measure {
+    width: usize,
+    limit: usize,
+}
+
+impl Write for Measure {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.width += s.chars().count();
+        if self.width > self.limit {
+            Err(fmt::Error)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Escapes `value`, so that it can be used as the value of a native
+/// [`TextNode`](crate::parser::ast::TextNode), which keeps the text as written between the quotes.
+pub fn escape(value: &str) -> Cow<'_, str> {
+    if !value.chars().any(needs_escape) {
+        return Cow::Borrowed(value);
+    }
+    let mut escaped = String::with_capacity(value.len() + 2);
+    for c in value.chars() {
+        push_escaped(&mut escaped, c);
+    }
+    Cow::Owned(escaped)
+}
+
+fn needs_escape(c: char) -> bool {
+    matches!(c, '"' | '\\') || c.is_control()
+}
+
+fn push_escaped(out: &mut String, c: char) {
+    match c {
+        '"' => out.push_str("\\\""),
+        '\\' => out.push_str("\\\\"),
+        '\u{8}' => out.push_str("\\b"),
+        '\u{c}' => out.push_str("\\f"),
+        '\n' => out.push_str("\\n"),
+        '\r' => out.push_str("\\r"),
+        '\t' => out.push_str("\\t"),
+        c if c.is_control() => {
+            let _ = write!(out, "\\u{:04x}", c as u32);
+        }
+        c => out.push(c),
+    }
+}
+
+/// Length of a valid escape sequence at the start of `rest` (after the backslash).
+fn escape_len(rest: &str) -> Option<usize> {
+    let mut chars = rest.chars();
+    match chars.next()? {
+        '"' | '\\' | 'b' | 'f' | 'n' | 'r' | 't' => Some(1),
+        'u' if rest
+            .get(1..5)
+            .is_some_and(|hex| hex.chars().all(|c| c.is_ascii_hexdigit())) =>
+        {
+            Some(5)
+        }
+        _ => None,
+    }
+}
+
+/// Checks whether `value` matches the `ident` rule of the grammar.
+pub(crate) fn is_identifier(value: &str) -> bool {
+    let rest = match ["пусто", "так", "ні"]
+        .iter()
+        .find_map(|keyword| value.strip_prefix(keyword))
+    {
+        Some("") => return false,
+        Some(rest) => rest,
+        None => {
+            let mut chars = value.chars();
+            match chars.next() {
+                Some(c) if c == '_' || is_alpha(c) => chars.as_str(),
+                _ => return false,
+            }
+        }
+    };
+    let mut chars = rest.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_ascii_digit() || c == '_' || is_alpha(c) => {}
+            '\'' | 'ʼ' if chars.next().is_some_and(is_alpha) => {}
+            _ => return false,
+        }
+    }
+    true
+}
+
+pub(crate) fn is_alpha(c: char) -> bool {
+    c.is_ascii_alphabetic()
+        || matches!(c, 'а'..='я' | 'А'..='Я' | 'і' | 'І' | 'ї' | 'Ї' | 'є' | 'Є' | 'ґ' | 'Ґ')
+}
+
+impl Emit for Did<'_> {
+    fn emit(&self, w: &mut Writer<'_>) -> fmt::Result {
+        match self {
+            Did::Empty(_) => w.empty(),
+            Did::Logical(n) => w.logical(n.value),
+            Did::Number(n) => w.number(&n.value),
+            Did::Text(n) => w.text(&n.value),
+            Did::Dictionary(n) => n.emit(w),
+            Did::Object(n) => n.emit(w),
+            Did::List(n) => n.emit(w),
+            // Skipped text of a recovered tree is written back as it was.
+            Did::Error(n) => w.put(&n.text),
+        }
+    }
+}
+
+impl Emit for DictionaryNode<'_> {
+    fn emit(&self, w: &mut Writer<'_>) -> fmt::Result {
+        w.entries('(', ')', &self.entries, |w, entry| {
+            match &entry.key {
+                DictionaryEntryKey::Number(key) => w.number(&key.value)?,
+                DictionaryEntryKey::Text(key) => w.key(&key.value)?,
+            }
+            w.equals()?;
+            entry.value.emit(w)
+        })
+    }
+}
+
+impl Emit for ObjectNode<'_> {
+    fn emit(&self, w: &mut Writer<'_>) -> fmt::Result {
+        w.ident(&self.name.value)?;
+        w.entries('(', ')', &self.entries, |w, entry| {
+            w.ident(&entry.key.value)?;
+            w.equals()?;
+            entry.value.emit(w)
+        })
+    }
+}
+
+impl Emit for ListNode<'_> {
+    fn emit(&self, w: &mut Writer<'_>) -> fmt::Result {
+        w.entries('[', ']', &self.entries, |w, entry| entry.emit(w))
+    }
+}
+
+impl Emit for cst::Document<'_> {
+    fn emit(&self, w: &mut Writer<'_>) -> fmt::Result {
+        let mut value = false;
+        let mut same_line = false;
+        for element in &self.root().children {
+            match element {
+                Element::Node(node) => {
+                    node.emit(w)?;
+                    value = true;
+                    same_line = true;
+                }
+                Element::Token(token) if is_comment(token.kind) => {
+                    if !value {
+                        w.put(&token.text)?;
+                        w.newline()?;
+                    } else {
+                        if same_line {
+                            w.put_char(' ')?;
+                        } else {
+                            w.newline()?;
+                        }
+                        w.put(&token.text)?;
+                        same_line = false;
+                    }
+                }
+                Element::Token(token) => {
+                    if token.text.contains('\n') {
+                        same_line = false;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Emit for cst::Node<'_> {
+    fn emit(&self, w: &mut Writer<'_>) -> fmt::Result {
+        let (open, close) = match self.kind {
+            NodeKind::Document => return self.nodes().try_for_each(|value| value.emit(w)),
+            NodeKind::DictionaryEntry | NodeKind::ObjectEntry => return emit_entry(w, self),
+            NodeKind::Empty | NodeKind::Logical | NodeKind::Number => {
+                return self.token().map_or(Ok(()), |token| w.put(&token.text))
+            }
+            NodeKind::Text => {
+                return self
+                    .token()
+                    .map_or(Ok(()), |token| w.text(unquote(&token.text)))
+            }
+            NodeKind::List => ('[', ']'),
+            NodeKind::Dictionary => ('(', ')'),
+            NodeKind::Object => {
+                if let Some(name) = self.token() {
+                    w.ident(&name.text)?;
+                }
+                ('(', ')')
+            }
+        };
+        let (entries, dangling) = commented_entries(self);
+        w.commented_entries(open, close, &entries, &dangling, emit_entry)
+    }
+}
+
+/// Writes an entry of a dictionary or an object, or an element of a list.
+fn emit_entry(w: &mut Writer<'_>, node: &cst::Node<'_>) -> fmt::Result {
+    let key = match node.kind {
+        NodeKind::DictionaryEntry | NodeKind::ObjectEntry => node.token(),
+        _ => return node.emit(w),
+    };
+    match key {
+        Some(key) if key.kind == TokenKind::Text => w.key(unquote(&key.text))?,
+        Some(key) => w.put(&key.text)?,
+        None => {}
+    }
+    w.equals()?;
+    match node.nodes().next() {
+        Some(value) => value.emit(w),
+        None => Ok(()),
+    }
+}
+
+/// Entries of a list, a dictionary or an object with the comments around them, and
+/// the comments after the last entry.
+///
+/// A comment on the line where an entry ends trails that entry. Comments between the key,
+/// `=` and the value of an entry go before that entry, and comments between an object
+/// name and its parenthesis before the first entry.
+fn commented_entries<'a>(
+    node: &'a cst::Node<'_>,
+) -> (Vec<Commented<'a, cst::Node<'a>>>, Vec<&'a str>) {
+    let mut entries: Vec<Commented<'a, cst::Node<'a>>> = Vec::new();
+    let mut pending = Vec::new();
+    let mut same_line = false;
+    for element in &node.children {
+        match element {
+            Element::Node(entry) => {
+                // Lists, dictionaries and objects write the comments inside them.
+                if matches!(
+                    entry.kind,
+                    NodeKind::DictionaryEntry | NodeKind::ObjectEntry
+                ) {
+                    pending.extend(
+                        entry
+                            .tokens()
+                            .filter(|token| is_comment(token.kind))
+                            .map(|token| &*token.text),
+                    );
+                }
+                entries.push(Commented {
+                    leading: std::mem::take(&mut pending),
+                    entry,
+                    trailing: Vec::new(),
+                });
+                same_line = true;
+            }
+            Element::Token(token) if is_comment(token.kind) => match entries.last_mut() {
+                Some(last) if same_line => {
+                    last.trailing.push(&token.text);
+                    same_line = token.kind == TokenKind::BlockComment;
+                }
+                _ => pending.push(&token.text),
+            },
+            Element::Token(token) => {
+                if token.text.contains('\n') {
+                    same_line = false;
+                }
+            }
+        }
+    }
+    (entries, pending)
+}
+
+fn is_comment(kind: TokenKind) -> bool {
+    matches!(kind, TokenKind::LineComment | TokenKind::BlockComment)
+}
+
+/// The text of a quoted token between the quotes.
+fn unquote(text: &str) -> &str {
+    &text[1..text.len() - 1]
+}
+
+#[cfg(feature = "wasm")]
+mod wasm {
+    use std::fmt;
+
+    use super::{escape, Emit, Writer};
+    use crate::api::{AstNode, DictionaryEntryKey};
+
+    // Text values of `AstNode` are decoded, so they are escaped in full.
+
+    impl Emit for AstNode {
+        fn emit(&self, w: &mut Writer<'_>) -> fmt::Result {
+            match self {
+                AstNode::Empty(_) => w.empty(),
+                AstNode::Logical(n) => w.logical(n.value),
+                AstNode::Number(n) => w.number(&n.value),
+                AstNode::Text(n) => w.text(&escape(&n.value)),
+                AstNode::Dictionary(n) => w.entries('(', ')', &n.entries, |w, entry| {
+                    match &entry.key {
+                        DictionaryEntryKey::Number(key) => w.number(&key.value)?,
+                        DictionaryEntryKey::Text(key) => w.key(&escape(&key.value))?,
+                    }
+                    w.equals()?;
+                    entry.value.0.emit(w)
+                }),
+                AstNode::Object(n) => {
+                    w.ident(&n.name.value)?;
+                    w.entries('(', ')', &n.entries, |w, entry| {
+                        w.ident(&entry.key.value)?;
+                        w.equals()?;
+                        entry.value.0.emit(w)
+                    })
+                }
+                AstNode::List(n) => w.entries('[', ']', &n.entries, |w, entry| entry.0.emit(w)),
+            }
+        }
+    }
+}
@@ -0,0 +1,110 @@
+use mavka_did::{
+    parser::{self, ast::Did},
+    writer::{self, Style},
+};
+
+#[test]
+fn comments_are_allowed_wherever_whitespace_is() {
+    let cases = [
+        (";; перед\n[1] ;; після", "[1]"),
+        ("[1 ;; після одиниці\n, 2]", "[1,2]"),
+        (
+            ";;; блок\n з ;; всередині ;;;(а ;;;;;; = ;;; ; ;;; 1)",
+            "(а=1)",
+        ),
+        (
+            "Об(;;; перед ключем ;;;а=;;; перед значенням ;;;так)",
+            "Об(а=так)",
+        ),
+        ("[\"а\" ;; без переходу на новий рядок", ""),
+        ("пусто ;;", "пусто"),
+        ("[1, ;;\r\n2]", "[1,2]"),
+    ];
+    for (input, expected) in cases {
+        let written = parser::parse_did(input).map(|did| writer::to_string(&did));
+        if expected.is_empty() {
+            assert!(written.is_err(), "{input}");
+        } else {
+            assert_eq!(written.as_deref(), Ok(expected), "{input}");
+        }
+    }
+}
+
+#[test]
+fn comment_markers_in_texts_are_text() {
+    let Did::Text(text) = parser::parse_did("\";; не коментар ;;;\"").unwrap() else {
+        panic!("not a text");
+    };
+    assert_eq!(text.value, ";; не коментар ;;;");
+}
+
+#[test]
+fn positions_after_comments_are_counted() {
+    let Did::List(list) = parser::parse_did(";;; ї\nї ;;;\n[ ;; ї\n  1]").unwrap() else {
+        panic!("not a list");
+    };
+    let context = list.entries[0].context();
+    assert_eq!((context.line, context.column, context.index), (4, 3, 24));
+}
+
+const COMMENTED: &str = "\
+;; заголовок
+(а=1, ;; після а
+;; перед б
+б=[1, ;;; блок ;;; 2],
+;; в кінці
+) ;; хвіст";
+
+#[test]
+fn formatting_keeps_comments() {
+    let formatted = writer::format(COMMENTED, &Style::default()).unwrap();
+    assert_eq!(
+        formatted,
+        "\
+;; заголовок
+(
+  а=1, ;; після а
+  ;; перед б
+  б=[
+    1, ;;; блок ;;;
+    2
+  ]
+  ;; в кінці
+) ;; хвіст
+"
+    );
+    assert_eq!(
+        writer::format(&formatted, &Style::default()).unwrap(),
+        formatted
+    );
+}
+
+#[test]
+fn containers_with_comments_are_wrapped_whatever_the_width() {
+    let style = Style {
+        hard_tabs: true,
+        ..Style::default()
+    };
+    // The comment is written once, inside the list it is in, and its list does not fit
+    // on one line, so neither does the outer one.
+    let formatted = writer::format("[[1 ;; а\n], [2]]", &style).unwrap();
+    assert_eq!(formatted, "[\n\t[\n\t\t1 ;; а\n\t],\n\t[2]\n]\n");
+    assert_eq!(writer::format(&formatted, &style).unwrap(), formatted);
+}
+
+#[test]
+fn comments_inside_entries_go_before_them() {
+    let formatted = writer::format("Об ;; н\n(а=1, б ;; к\n= ;;; в ;;; 2)", &Style::default());
+    assert_eq!(
+        formatted.unwrap(),
+        "\
+Об(
+  ;; н
+  а=1,
+  ;; к
+  ;;; в ;;;
+  б=2
+)
+"
+    );
+}
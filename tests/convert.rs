@@ -0,0 +1,65 @@
+use std::collections::BTreeMap;
+
+use mavka_did::{
+    convert::{Error, ErrorKind},
+    parser, writer, FromDid, ToDid,
+};
+
+fn read<T: FromDid>(input: &str) -> Result<T, Error> {
+    T::from_did(&parser::parse_did(input).unwrap())
+}
+
+fn write<T: ToDid + ?Sized>(value: &T) -> String {
+    writer::to_string(&value.to_did())
+}
+
+#[test]
+fn primitives_convert_both_ways() {
+    assert_eq!(read::<()>("пусто"), Ok(()));
+    assert_eq!(read::<bool>("так"), Ok(true));
+    assert_eq!(read::<i8>("-128"), Ok(-128));
+    assert_eq!(read::<u64>("18446744073709551615"), Ok(u64::MAX));
+    assert_eq!(read::<f64>("2.5"), Ok(2.5));
+    assert_eq!(read::<String>(r#""а\nб""#).as_deref(), Ok("а\nб"));
+    assert_eq!(read::<Option<u8>>("пусто"), Ok(None));
+    assert_eq!(
+        read::<Vec<Option<u8>>>("[1, пусто]"),
+        Ok(vec![Some(1), None])
+    );
+
+    assert_eq!(write(&()), "пусто");
+    assert_eq!(write(&false), "ні");
+    assert_eq!(write(&-7i32), "-7");
+    assert_eq!(write(&f64::NAN), "пусто");
+    assert_eq!(write("\"лапки\""), r#""\"лапки\"""#);
+    assert_eq!(write(&vec![Some(1), None]), "[1,пусто]");
+}
+
+#[test]
+fn maps_convert_from_dictionaries_and_objects() {
+    let expected = BTreeMap::from([("1".to_owned(), 1), ("ключ".to_owned(), 2)]);
+    assert_eq!(
+        read::<BTreeMap<String, u8>>(r#"(1=1, "ключ"=2)"#),
+        Ok(expected.clone())
+    );
+    assert_eq!(
+        read::<BTreeMap<String, u8>>("Об(ключ=2)"),
+        Ok(BTreeMap::from([("ключ".to_owned(), 2)]))
+    );
+    assert_eq!(write(&expected), r#"("1"=1,ключ=2)"#);
+}
+
+#[test]
+fn errors_point_at_the_node() {
+    let err = read::<Vec<u8>>("[1,\n 256]").unwrap_err();
+    assert_eq!(err.kind, ErrorKind::InvalidValue { expected: "u8" });
+    assert_eq!((err.context.line, err.context.column), (2, 2));
+
+    let err = read::<bool>("  1").unwrap_err();
+    assert!(matches!(err.kind, ErrorKind::InvalidType { .. }), "{err}");
+    assert_eq!(err.context.column, 3);
+    assert!(
+        err.to_string().ends_with("(рядок: 1, стовпчик: 3)"),
+        "{err}"
+    );
+}
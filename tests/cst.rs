@@ -0,0 +1,119 @@
+use mavka_did::{
+    parser::{
+        self,
+        cst::{Element, Node, NodeKind, TokenKind},
+        ParseOptions,
+    },
+    writer,
+};
+
+const INPUTS: &[&str] = &[
+    "пусто",
+    "  так\n",
+    "-1_000.5e-3",
+    r#""а\n\"б\"""#,
+    "[ ]",
+    "[1 , 2,]",
+    ";; заголовок\r\n(а = 1 ,\t\"б\"=[так, ні] ;; коментар\n)",
+    "Об ;;; між ;;; ( ключ=Вкладений(x=пусто) , інший = \"ї\" )\n\n",
+    "\u{feff}[\r\n]",
+];
+
+fn kinds(node: &Node<'_>) -> Vec<TokenKind> {
+    node.tokens().map(|token| token.kind).collect()
+}
+
+#[test]
+fn documents_are_written_back_byte_for_byte() {
+    for &input in INPUTS {
+        let Ok(document) = parser::parse_cst(input) else {
+            continue;
+        };
+        assert_eq!(document.to_string(), input);
+        assert_eq!(document.into_owned().to_string(), input);
+    }
+}
+
+#[test]
+fn documents_convert_into_the_same_tree_as_parsing() {
+    for &input in INPUTS {
+        let Ok(document) = parser::parse_cst(input) else {
+            assert!(parser::parse_did(input).is_err(), "{input}");
+            continue;
+        };
+        let expected = format!("{:?}", parser::parse_did(input).unwrap());
+        assert_eq!(format!("{:?}", document.to_did()), expected, "{input}");
+        assert_eq!(format!("{:?}", document.into_did()), expected, "{input}");
+    }
+}
+
+#[test]
+fn nodes_keep_their_trivia_and_punctuation() {
+    let document = parser::parse_cst(" Об(а = 1 ;; к\n) ").unwrap();
+    assert_eq!(kinds(document.root()), [TokenKind::Whitespace; 2]);
+
+    let object = document.value();
+    assert_eq!(object.kind, NodeKind::Object);
+    assert_eq!(object.token().map(|token| &*token.text), Some("Об"));
+    assert_eq!(
+        kinds(object),
+        [
+            TokenKind::Identifier,
+            TokenKind::LeftParenthesis,
+            TokenKind::Whitespace,
+            TokenKind::LineComment,
+            TokenKind::Whitespace,
+            TokenKind::RightParenthesis
+        ]
+    );
+
+    let entry = object.nodes().next().unwrap();
+    assert_eq!(entry.kind, NodeKind::ObjectEntry);
+    assert_eq!(
+        kinds(entry),
+        [
+            TokenKind::Identifier,
+            TokenKind::Whitespace,
+            TokenKind::EqualsSign,
+            TokenKind::Whitespace
+        ]
+    );
+    let value = entry.nodes().next().unwrap();
+    assert_eq!(value.kind, NodeKind::Number);
+    assert_eq!(kinds(value), [TokenKind::Number]);
+    assert_eq!((value.context.line, value.context.column), (1, 9));
+}
+
+#[test]
+fn edited_tokens_are_written() {
+    let mut root = parser::parse_cst("[1, 2]").unwrap().root().clone();
+    let Some(Element::Node(list)) = root.children.first_mut() else {
+        panic!("no value");
+    };
+    let Some(Element::Node(number)) = list.children.get_mut(1) else {
+        panic!("no entry");
+    };
+    let Some(Element::Token(token)) = number.children.first_mut() else {
+        panic!("no token");
+    };
+    token.text = "42".into();
+    assert_eq!(root.to_string(), "[42, 2]");
+    assert_eq!(writer::to_string(&root.into_did()), "[42,2]");
+}
+
+#[test]
+fn deep_documents_are_built_and_converted() {
+    let depth = 300;
+    let input = format!("{}1{}", "[(1=".repeat(depth), ")]".repeat(depth));
+    let options = ParseOptions {
+        max_depth: 2 * depth,
+        ..ParseOptions::default()
+    };
+    let document = parser::parse_cst_with(&input, &options).unwrap();
+    assert_eq!(document.to_string(), input);
+    let expected = writer::to_string(&parser::parse_did_with(&input, &options).unwrap());
+    assert_eq!(
+        writer::to_string(&document.into_owned().into_did()),
+        expected
+    );
+}
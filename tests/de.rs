@@ -0,0 +1,135 @@
+use std::{borrow::Cow, collections::BTreeMap};
+
+use mavka_did::{
+    de::Error,
+    from_did, from_str,
+    parser::ast::{Did, NodeContext, TextNode},
+};
+use serde::Deserialize;
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct Людина<'a> {
+    імʼя: &'a str,
+    #[serde(borrow)]
+    прізвище: Cow<'a, str>,
+    вік: u8,
+    зріст: f64,
+    друг: Option<Box<Людина<'a>>>,
+    зацікавлення: Vec<String>,
+}
+
+#[test]
+fn objects_deserialize_into_structs() {
+    let input = r#"Людина(
+        імʼя="Леся",
+        прізвище="Українка",
+        вік=42,
+        зріст=1.6e0,
+        друг=пусто,
+        зацікавлення=["поезія", "драма"],
+    )"#;
+    let людина: Людина<'_> = from_str(input).unwrap();
+    assert_eq!(
+        людина,
+        Людина {
+            імʼя: "Леся",
+            прізвище: Cow::Borrowed("Українка"),
+            вік: 42,
+            зріст: 1.6,
+            друг: None,
+            зацікавлення: vec!["поезія".to_owned(), "драма".to_owned()],
+        }
+    );
+    // Texts without escapes are borrowed from the input.
+    assert!(input
+        .as_bytes()
+        .as_ptr_range()
+        .contains(&людина.імʼя.as_ptr()));
+}
+
+#[test]
+fn dictionaries_deserialize_into_maps() {
+    let map: BTreeMap<u32, bool> = from_str("(1=так, 16=ні)").unwrap();
+    assert_eq!(map, BTreeMap::from([(1, true), (16, false)]));
+    let map: BTreeMap<String, i64> = from_str(r#"(а=1, "б в"=-2)"#).unwrap();
+    assert_eq!(
+        map,
+        BTreeMap::from([("а".to_owned(), 1), ("б в".to_owned(), -2)])
+    );
+    let tuple: (char, (), Option<i8>) = from_str(r#"["ї", пусто, 1]"#).unwrap();
+    assert_eq!(tuple, ('ї', (), Some(1)));
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+enum Фігура {
+    Точка,
+    Коло { радіус: u32 },
+    Квадрат(u32),
+    Відрізок(u32, u32),
+}
+
+#[test]
+fn enums_deserialize_from_texts_objects_and_dictionaries() {
+    let cases = [
+        (r#""Точка""#, Фігура::Точка),
+        ("Точка()", Фігура::Точка),
+        ("Коло(радіус=1)", Фігура::Коло { радіус: 1 }),
+        ("Квадрат(значення=2)", Фігура::Квадрат(2)),
+        ("Відрізок(значення=[3, 4])", Фігура::Відрізок(3, 4)),
+        ("(Квадрат=5)", Фігура::Квадрат(5)),
+    ];
+    for (input, expected) in cases {
+        assert_eq!(from_str::<Фігура>(input).unwrap(), expected, "{input}");
+    }
+}
+
+#[test]
+fn errors_point_at_the_value() {
+    let err = from_str::<Людина<'_>>("Людина(\n  імʼя=\"Леся\",\n  вік=-1\n)").unwrap_err();
+    let Error::Message {
+        context: Some(context),
+        ..
+    } = &err
+    else {
+        panic!("no position: {err:?}");
+    };
+    assert_eq!((context.line, context.column), (3, 7));
+    assert!(
+        err.to_string().ends_with("(рядок: 3, стовпчик: 7)"),
+        "{err}"
+    );
+
+    let err = from_str::<u8>("[1,").unwrap_err();
+    assert!(matches!(err, Error::Parse(_)), "{err:?}");
+
+    let err = from_str::<Фігура>("Точка(а=1)").unwrap_err();
+    assert!(
+        matches!(
+            err,
+            Error::Message {
+                context: Some(_),
+                ..
+            }
+        ),
+        "{err:?}"
+    );
+}
+
+#[test]
+fn invalid_escapes_of_hand_built_trees_are_errors() {
+    let did = Did::Text(TextNode {
+        value: r"\uD800".into(),
+        context: NodeContext::default(),
+    });
+    let err = from_did::<String>(&did).unwrap_err();
+    assert!(
+        matches!(
+            err,
+            Error::Message {
+                context: Some(_),
+                ..
+            }
+        ),
+        "{err:?}"
+    );
+}
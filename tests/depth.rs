@@ -0,0 +1,150 @@
+use std::thread;
+
+use mavka_did::{
+    parser::{
+        self,
+        ast::{Did, ListNode, NodeContext, ObjectEntryNode, ObjectNode, TextNode},
+        cst::{Element, Node, NodeKind, Token, TokenKind},
+        ParseOptions,
+    },
+    Reason,
+};
+
+/// Levels of the trees built by hand, far more than a small stack could recurse into.
+const DEEP: usize = 100_000;
+
+/// Runs `f` on a thread with a stack too small to recurse once per level of [`DEEP`].
+fn on_small_stack(f: impl FnOnce() + Send + 'static) {
+    thread::Builder::new()
+        .stack_size(256 * 1024)
+        .spawn(f)
+        .unwrap()
+        .join()
+        .unwrap();
+}
+
+fn nested(depth: usize) -> String {
+    format!("{}{}", "[".repeat(depth), "]".repeat(depth))
+}
+
+fn deep_did() -> Did<'static> {
+    let context = NodeContext::default();
+    let mut did = Did::List(ListNode {
+        entries: Vec::new(),
+        context,
+    });
+    for level in 0..DEEP {
+        did = if level % 2 == 0 {
+            Did::List(ListNode {
+                entries: vec![did],
+                context,
+            })
+        } else {
+            Did::Object(ObjectNode {
+                name: TextNode {
+                    value: "Об".into(),
+                    context,
+                },
+                entries: vec![ObjectEntryNode {
+                    key: TextNode {
+                        value: "а".into(),
+                        context,
+                    },
+                    value: did,
+                    context,
+                }],
+                context,
+            })
+        };
+    }
+    did
+}
+
+fn deep_node() -> Node<'static> {
+    let token = |kind, text: &'static str| {
+        Element::Token(Token {
+            kind,
+            text: text.into(),
+            context: NodeContext::default(),
+        })
+    };
+    let list = |children| Node {
+        kind: NodeKind::List,
+        children,
+        context: NodeContext::default(),
+    };
+    let mut node = list(vec![
+        token(TokenKind::LeftBracket, "["),
+        token(TokenKind::RightBracket, "]"),
+    ]);
+    for _ in 0..DEEP {
+        node = list(vec![
+            token(TokenKind::LeftBracket, "["),
+            Element::Node(node),
+            token(TokenKind::RightBracket, "]"),
+        ]);
+    }
+    Node {
+        kind: NodeKind::Document,
+        children: vec![Element::Node(node)],
+        context: NodeContext::default(),
+    }
+}
+
+#[test]
+fn nesting_deeper_than_the_limit_is_an_error() {
+    assert!(parser::parse_did(&nested(128)).is_ok());
+    let err = parser::parse_did(&nested(129)).unwrap_err();
+    assert_eq!(err.reason, Some(Reason::TooDeep(128)));
+    assert_eq!((err.start, err.end), (128, 129));
+
+    let options = ParseOptions {
+        max_depth: 3,
+        ..ParseOptions::default()
+    };
+    assert!(parser::parse_did_with("[(1=[]), Об(а=[])]", &options).is_ok());
+    let err = parser::parse_did_with("\n[(1=Об(а=[]))]", &options).unwrap_err();
+    assert_eq!((err.line, err.column), (2, 10));
+    assert!(parser::parse_cst_with(&nested(4), &options).is_err());
+
+    let options = ParseOptions {
+        max_depth: 0,
+        ..ParseOptions::default()
+    };
+    assert!(parser::parse_did_with("1", &options).is_ok());
+    assert!(parser::parse_did_with("[]", &options).is_err());
+}
+
+#[test]
+fn brackets_in_texts_and_comments_are_not_nesting() {
+    let options = ParseOptions {
+        max_depth: 1,
+        ..ParseOptions::default()
+    };
+    let input = "[\"[[(\", \"\\\"[\", ;; [[[\n ;;; ((( ;;; 1]";
+    assert!(parser::parse_did_with(input, &options).is_ok());
+}
+
+#[test]
+fn hostile_nesting_fails_without_overflowing() {
+    let input = nested(100_000);
+    let err = parser::parse_did(&input).unwrap_err();
+    assert_eq!(err.reason, Some(Reason::TooDeep(128)));
+    assert!(parser::parse_cst(&input).is_err());
+    let (_, errors) = parser::parse_recovering(&input);
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].reason, Some(Reason::TooDeep(128)));
+}
+
+#[test]
+fn deep_trees_are_dropped_and_converted_without_recursion() {
+    on_small_stack(|| deep_did().drop_deep());
+    on_small_stack(|| deep_did().into_owned().drop_deep());
+    on_small_stack(|| drop(deep_node()));
+    on_small_stack(|| drop(deep_node().into_owned()));
+    on_small_stack(|| {
+        let did = deep_node().into_did();
+        assert!(matches!(&did, Did::List(list) if list.entries.len() == 1));
+        did.drop_deep();
+    });
+}
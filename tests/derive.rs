@@ -0,0 +1,111 @@
+use mavka_did::{
+    convert::{Error, ErrorKind},
+    parser, writer, FromDid, ToDid,
+};
+
+#[derive(Debug, PartialEq, FromDid, ToDid)]
+#[did(name = "Людина")]
+struct Person {
+    #[did(rename = "імʼя")]
+    name: String,
+    #[did(rename = "прізвище")]
+    surname: Option<String>,
+    #[did(rename = "вік", default)]
+    age: u32,
+    #[did(rename = "мова", default = "ukrainian")]
+    language: String,
+    #[did(flatten)]
+    body: Body,
+}
+
+#[derive(Debug, PartialEq, FromDid, ToDid)]
+struct Body {
+    #[did(rename = "зріст")]
+    height: u32,
+}
+
+#[derive(Debug, PartialEq, FromDid, ToDid)]
+struct Пара<T> {
+    ліва: T,
+    права: T,
+}
+
+#[derive(Debug, PartialEq, FromDid, ToDid)]
+struct Порожнеча;
+
+fn ukrainian() -> String {
+    "українська".to_owned()
+}
+
+fn read<T: FromDid>(input: &str) -> Result<T, Error> {
+    T::from_did(&parser::parse_did(input).unwrap())
+}
+
+#[test]
+fn structs_are_read_from_objects() {
+    assert_eq!(
+        read::<Person>(r#"Людина(імʼя="Леся", зріст=160)"#),
+        Ok(Person {
+            name: "Леся".to_owned(),
+            surname: None,
+            age: 0,
+            language: "українська".to_owned(),
+            body: Body { height: 160 },
+        })
+    );
+    assert_eq!(
+        read::<Пара<bool>>("Пара(ліва=так, права=ні)"),
+        Ok(Пара {
+            ліва: true,
+            права: false
+        })
+    );
+    assert_eq!(read::<Порожнеча>("Порожнеча()"), Ok(Порожнеча));
+    // The last of the entries with the same key is read.
+    assert_eq!(
+        read::<Body>("Body(зріст=1, зріст=2)"),
+        Ok(Body { height: 2 })
+    );
+}
+
+#[test]
+fn structs_are_written_as_objects() {
+    let person = Person {
+        name: "Леся".to_owned(),
+        surname: Some("Українка".to_owned()),
+        age: 42,
+        language: "українська".to_owned(),
+        body: Body { height: 160 },
+    };
+    let written = writer::to_string(&person.to_did());
+    assert_eq!(
+        written,
+        r#"Людина(імʼя="Леся",прізвище="Українка",вік=42,мова="українська",зріст=160)"#
+    );
+    assert_eq!(read::<Person>(&written), Ok(person));
+    assert_eq!(writer::to_string(&Порожнеча.to_did()), "Порожнеча()");
+}
+
+#[test]
+fn errors_name_what_is_wrong_and_where() {
+    let err = read::<Person>("Людина(зріст=1)").unwrap_err();
+    assert_eq!(err.kind, ErrorKind::MissingEntry { key: "імʼя" });
+    assert_eq!(err.context.column, 1);
+
+    let err = read::<Person>(r#"[Body(зріст=1)]"#).unwrap_err();
+    assert!(matches!(err.kind, ErrorKind::InvalidType { .. }), "{err}");
+
+    let err = read::<Body>("  Тіло(зріст=1)").unwrap_err();
+    assert_eq!(
+        err.kind,
+        ErrorKind::InvalidName {
+            expected: "Body",
+            found: "Тіло".to_owned()
+        }
+    );
+    assert_eq!(err.context.column, 3);
+
+    let err = read::<Body>("Body(зріст=-1)").unwrap_err();
+    assert_eq!(err.kind, ErrorKind::InvalidValue { expected: "u32" });
+    assert_eq!(err.context.column, 12);
+}
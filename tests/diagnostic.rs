@@ -0,0 +1,146 @@
+use mavka_did::{
+    diagnostic::{self, render, Language},
+    parser::{self, DuplicateKeys, ParseOptions},
+    Error,
+};
+
+fn error(input: &str) -> Error {
+    parser::parse_did(input).unwrap_err()
+}
+
+#[test]
+fn messages_are_rendered_in_both_languages() {
+    let input = "[1 2]";
+    let err = error(input);
+    assert_eq!(
+        render(input, &err, Language::English),
+        "\
+error: Expected a right square bracket `]` or a comma `,`
+ --> line 1, column 4
+  |
+1 | [1 2]
+  | - the list starts here
+  |    ^ found `2`
+  |
+  = hint: entries are separated with commas
+"
+    );
+    assert_eq!(
+        render(input, &err, Language::default()),
+        render(input, &err, Language::Ukrainian)
+    );
+
+    let mut written = String::new();
+    diagnostic::write(&mut written, input, &err, Language::English).unwrap();
+    assert_eq!(written, render(input, &err, Language::English));
+}
+
+#[test]
+fn openers_on_earlier_lines_are_labeled() {
+    let input = "Людина(\n  імʼя=\"Іван\",\n  вік=30\n  зріст=180\n)";
+    assert_eq!(
+        render(input, &error(input), Language::Ukrainian),
+        "\
+помилка: Очікувалася права кругла дужка `)` або кома `,`
+ --> рядок 4, стовпчик 3
+  |
+1 | Людина(
+  | ------ об'єкт почався тут
+  ...
+4 |   зріст=180
+  |   ^^^^^ знайдено `зріст`
+  |
+  = порада: записи розділяються комами
+"
+    );
+
+    let input = format!("(1=2{}3)", "\n".repeat(11));
+    assert_eq!(
+        render(&input, &error(&input), Language::English),
+        "\
+error: Expected a right parenthesis `)` or a comma `,`
+  --> line 12, column 1
+   |
+ 1 | (1=2
+   | - the dictionary starts here
+   ...
+12 | 3)
+   | ^ found `3`
+   |
+   = hint: entries are separated with commas
+"
+    );
+}
+
+#[test]
+fn tabs_are_kept_so_markers_line_up() {
+    let input = "\t[1\t2]";
+    assert_eq!(
+        render(input, &error(input), Language::English),
+        "\
+error: Expected a right square bracket `]` or a comma `,`
+ --> line 1, column 5
+  |
+1 | \t[1\t2]
+  | \t- the list starts here
+  | \t  \t^ found `2`
+  |
+  = hint: entries are separated with commas
+"
+    );
+}
+
+#[test]
+fn reasons_replace_the_found_label() {
+    let input = "(а=1, \"а\"=2)";
+    let options = ParseOptions {
+        duplicate_keys: DuplicateKeys::Reject,
+        ..ParseOptions::default()
+    };
+    let err = parser::parse_did_with(input, &options).unwrap_err();
+    assert_eq!(
+        render(input, &err, Language::English),
+        "\
+error: Duplicate key
+ --> line 1, column 7
+  |
+1 | (а=1, \"а\"=2)
+  |  - first used here
+  |       ^^^
+  |
+  = hint: keys are compared by value: `1` and `1.0` are the same, as are `а` and `\"а\"`
+"
+    );
+
+    let input = ";;; без кінця\n";
+    assert_eq!(
+        render(input, &error(input), Language::Ukrainian),
+        "\
+помилка: Незавершений блоковий коментар
+ --> рядок 1, стовпчик 1
+  |
+1 | ;;; без кінця
+  | ^^^^^^^^^^^^^
+  |
+  = порада: блоковий коментар закривається `;;;`
+"
+    );
+}
+
+#[test]
+fn the_end_of_the_input_is_marked() {
+    let input = "[1,";
+    assert_eq!(
+        render(input, &error(input), Language::English),
+        "\
+error: Expected a `Дід` node or a right square bracket `]`
+ --> line 1, column 4
+  |
+1 | [1,
+  | - the list starts here
+  |    ^ found the end of the file
+  |
+  = hint: a value can be `пусто`, `так`, `ні`, a number, a text, a list, a dictionary or an object
+"
+    );
+}
@@ -0,0 +1,106 @@
+use mavka_did::{
+    parser::{self, ast::NodeContext, DuplicateKeys, ParseOptions, Warning},
+    writer, Reason,
+};
+
+fn options(duplicate_keys: DuplicateKeys) -> ParseOptions {
+    ParseOptions {
+        duplicate_keys,
+        ..ParseOptions::default()
+    }
+}
+
+/// The written tree, and the dropped key with the key that repeats it for every warning.
+fn warnings(input: &str) -> (String, Vec<(&str, &str)>) {
+    let (did, warnings) =
+        parser::parse_did_with_warnings(input, &options(DuplicateKeys::LastWins)).unwrap();
+    let text = |context: NodeContext| &input[context.range()];
+    let warnings = warnings
+        .iter()
+        .map(|Warning::DuplicateKey { first, key }| (text(*first), text(*key)))
+        .collect();
+    (writer::to_string(&did), warnings)
+}
+
+#[test]
+fn duplicates_are_allowed_by_default() {
+    let input = "Людина(вік=1, вік=2)";
+    let (did, warnings) =
+        parser::parse_did_with_warnings(input, &options(DuplicateKeys::Allow)).unwrap();
+    assert!(warnings.is_empty());
+    assert_eq!(writer::to_string(&did), "Людина(вік=1,вік=2)");
+    assert_eq!(
+        writer::to_string(&parser::parse_did(input).unwrap()),
+        "Людина(вік=1,вік=2)"
+    );
+}
+
+#[test]
+fn keys_are_compared_by_value() {
+    let cases = [
+        ("(1=1, 1.0=2)", "1", "1.0"),
+        ("(16=1, 0x10=2)", "16", "0x10"),
+        ("(100=1, 1e2=2)", "100", "1e2"),
+        ("(а=1, \"а\"=2)", "а", "\"а\""),
+        ("(\"\\u0430\"=1, а=2)", "\"\\u0430\"", "а"),
+        ("Об(а=1, б=2, а=3)", "а", "а"),
+        ("[1, (а=(б=1, б=2))]", "б", "б"),
+    ];
+    for (input, first, second) in cases {
+        let err = parser::parse_did_with(input, &options(DuplicateKeys::Reject)).unwrap_err();
+        let Some(Reason::DuplicateKey { first: range }) = err.reason.clone() else {
+            panic!("{input}: {err:?}");
+        };
+        assert_eq!(&input[range], first, "{input}");
+        assert_eq!(&input[err.start..err.end], second, "{input}");
+        assert_eq!(err.found.as_deref(), Some(second), "{input}");
+    }
+    for input in [
+        "(1=1, 2=2)",
+        "(а=1, \"б\"=2)",
+        "(а=(а=1), б=[(а=1)])",
+        "Об(а=1, б=Об(а=2))",
+    ] {
+        assert!(
+            parser::parse_did_with(input, &options(DuplicateKeys::Reject)).is_ok(),
+            "{input}"
+        );
+    }
+}
+
+#[test]
+fn the_first_duplicate_in_the_input_is_the_error() {
+    let input = "(б=(в=1, в=2), а=1, а=2, б=3)";
+    let err = parser::parse_did_with(input, &options(DuplicateKeys::Reject)).unwrap_err();
+    assert_eq!(err.start, input.find("в=2").unwrap());
+    assert!(err.to_string().starts_with("Ключ повторюється"), "{err}");
+    assert!(parser::parse_cst_with(input, &options(DuplicateKeys::Reject)).is_err());
+}
+
+#[test]
+fn the_last_entry_wins_with_a_warning() {
+    assert_eq!(
+        warnings("(а=1, б=2, а=3, \"а\"=4)"),
+        ("(б=2,а=4)".to_owned(), vec![("а", "а"), ("а", "\"а\"")])
+    );
+    assert_eq!(
+        warnings("[Об(а=(1=1, 1.0=2), а=3)]"),
+        ("[Об(а=3)]".to_owned(), vec![("1", "1.0"), ("а", "а")])
+    );
+    assert_eq!(warnings("(а=1)"), ("(а=1)".to_owned(), vec![]));
+
+    let (_, warnings) =
+        parser::parse_did_with_warnings("(а=1,\n а=2)", &options(DuplicateKeys::LastWins)).unwrap();
+    assert_eq!(
+        warnings[0].to_string(),
+        "Ключ повторюється, тож попередній запис відкинуто \
+         (рядок: 2, стовпчик: 2; попередній запис: рядок: 1, стовпчик: 2)"
+    );
+}
+
+#[test]
+fn syntax_trees_keep_every_entry_unless_rejecting() {
+    let input = "(а=1, а=2)";
+    let document = parser::parse_cst_with(input, &options(DuplicateKeys::LastWins)).unwrap();
+    assert_eq!(document.to_string(), input);
+}
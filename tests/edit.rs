@@ -0,0 +1,185 @@
+use mavka_did::{
+    edit::{EditError, Key},
+    parser::{self, cst::Document},
+};
+
+const CONFIG: &str = "\
+;; налаштування
+Налаштування(
+  параметри=(висота=175, \"ва\\\"га\"=69, 1.0=\"один\", 2=[так, ні]), ;; у сантиметрах
+  оцінки=[5, 4,],
+  оцінки=[3],
+)
+";
+
+fn document(input: &str) -> Document<'_> {
+    parser::parse_cst(input).unwrap()
+}
+
+fn edited(input: &str, edit: impl FnOnce(&mut Document<'_>) -> Result<(), EditError>) -> String {
+    let mut document = document(input);
+    edit(&mut document).unwrap();
+    document.to_string()
+}
+
+#[test]
+fn documents_and_trees_find_the_same_values() {
+    let cst = document(CONFIG);
+    let did = parser::parse_did(CONFIG).unwrap();
+    let paths: &[&[Key<'_>]] = &[
+        &[],
+        &[Key::Name("параметри")],
+        &[Key::Name("параметри"), Key::Name("висота")],
+        &[Key::Name("параметри"), Key::Name("ва\"га")],
+        &[Key::Name("параметри"), Key::Name("1")],
+        &[Key::Name("параметри"), Key::Index(1)],
+        &[Key::Name("параметри"), Key::Index(2), Key::Index(1)],
+        &[Key::Name("параметри"), Key::Name("2.00"), Key::Index(0)],
+        &[Key::Name("оцінки"), Key::Index(0)],
+        &[Key::Name("оцінки"), Key::Index(1)],
+        &[Key::Name("параметри"), Key::Name("ва\\\"га")],
+        &[Key::Index(0)],
+        &[Key::Name("інше")],
+        &[Key::Name("параметри"), Key::Index(2), Key::Name("0")],
+    ];
+    for path in paths {
+        assert_eq!(
+            cst.get(path).map(|node| node.context.range()),
+            did.get_path(path).map(|did| did.context().range()),
+            "{path:?}"
+        );
+    }
+    let height = cst
+        .get(&[Key::Name("параметри"), Key::Name("висота")])
+        .unwrap();
+    assert_eq!(height.to_string(), "175");
+}
+
+#[test]
+fn set_changes_only_the_value() {
+    let written = edited(CONFIG, |document| {
+        document.set(&[Key::Name("параметри"), Key::Name("висота")], "180")?;
+        document.set(&[Key::Name("параметри"), Key::Index(1)], "\"один\\n\"")?;
+        document.set(&[Key::Name("оцінки"), Key::Index(0)], "[1,  2]")
+    });
+    assert_eq!(
+        written,
+        "\
+;; налаштування
+Налаштування(
+  параметри=(висота=180, \"ва\\\"га\"=69, 1.0=\"один\\n\", 2=[так, ні]), ;; у сантиметрах
+  оцінки=[5, 4,],
+  оцінки=[[1,  2]],
+)
+"
+    );
+    assert_eq!(
+        edited("[1]", |document| document.set(&[], " пусто ")),
+        "пусто"
+    );
+}
+
+#[test]
+fn set_adds_missing_entries_like_the_last_one() {
+    let written = edited("Об(а = 1)", |document| {
+        document.set(&[Key::Name("б")], "2")
+    });
+    assert_eq!(written, "Об(а = 1, б = 2)");
+    let written = edited("(\n  1=так,\n)", |document| {
+        document.set(&[Key::Name("два слова")], "ні")
+    });
+    assert_eq!(written, "(\n  1=так,\n  \"два слова\"=ні,\n)");
+    let written = edited("(1 = так, 2.5 = ні)", |document| {
+        document.set(&[Key::Name("5")], "пусто")?;
+        document.set(&[Key::Name("а")], "пусто")
+    });
+    assert_eq!(written, "(1 = так, 2.5 = ні, 5 = пусто, а = пусто)");
+    let written = edited("(1=так, \"б\"=ні)", |document| {
+        document.set(&[Key::Name("5")], "пусто")
+    });
+    assert_eq!(written, "(1=так, \"б\"=ні, \"5\"=пусто)");
+    let written = edited("Об(а ;;; к ;;; = ;; к\n  1)", |document| {
+        document.set(&[Key::Name("б")], "2")
+    });
+    assert_eq!(written, "Об(а ;;; к ;;; = ;; к\n  1, б = 2)");
+    let written = edited("Об()", |document| document.set(&[Key::Name("а")], "1"));
+    assert_eq!(written, "Об(а=1)");
+    assert_eq!(
+        parser::parse_did(&written)
+            .unwrap()
+            .pointer_as::<u8>("/а")
+            .unwrap(),
+        Ok(1)
+    );
+}
+
+#[test]
+fn push_appends_after_comments_on_the_last_line() {
+    let push = |input: &str| edited(input, |document| document.push(&[], "3"));
+    assert_eq!(push("[1, 2]"), "[1, 2, 3]");
+    assert_eq!(push("[]"), "[3]");
+    assert_eq!(push("[\n  1,\n  2,\n]"), "[\n  1,\n  2,\n  3,\n]");
+    assert_eq!(
+        push("[\n  1,\n  2 ;; два\n]"),
+        "[\n  1,\n  2, ;; два\n  3\n]"
+    );
+    assert_eq!(push("[1, 2 ;; два\n]"), "[1, 2, ;; два\n3\n]");
+}
+
+#[test]
+fn remove_takes_the_comma_and_the_comments_on_the_line() {
+    let remove = |input: &str, path: &[Key<'_>]| edited(input, |document| document.remove(path));
+    assert_eq!(remove("[1, 2, 3]", &[Key::Index(1)]), "[1, 3]");
+    assert_eq!(remove("[1, 2, 3]", &[Key::Index(2)]), "[1, 2]");
+    assert_eq!(remove("[1]", &[Key::Index(0)]), "[]");
+    assert_eq!(
+        remove("(\n  а=1, ;; перше\n  б=2,\n)", &[Key::Name("а")]),
+        "(\n  б=2,\n)"
+    );
+    assert_eq!(
+        remove(CONFIG, &[Key::Name("оцінки")]),
+        "\
+;; налаштування
+Налаштування(
+  параметри=(висота=175, \"ва\\\"га\"=69, 1.0=\"один\", 2=[так, ні]), ;; у сантиметрах
+  оцінки=[5, 4,],
+)
+"
+    );
+}
+
+#[test]
+fn failed_edits_say_why_and_change_nothing() {
+    let mut document = document(CONFIG);
+    let path = [Key::Name("параметри"), Key::Name("зріст"), Key::Index(0)];
+    assert!(matches!(
+        document.set(&path, "1"),
+        Err(EditError::NotFound { depth: 1 })
+    ));
+    assert!(matches!(
+        document.set(&[Key::Name("інше"), Key::Index(0)], "1"),
+        Err(EditError::NotFound { depth: 0 })
+    ));
+    // The last of the entries with the same key is edited.
+    assert!(matches!(
+        document.set(&[Key::Name("оцінки"), Key::Index(1)], "1"),
+        Err(EditError::NotFound { depth: 1 })
+    ));
+    assert!(matches!(
+        document.set(&[Key::Name("не ідентифікатор")], "1"),
+        Err(EditError::InvalidKey(key)) if key == "не ідентифікатор"
+    ));
+    assert!(matches!(
+        document.set(&[Key::Name("параметри")], "[1,"),
+        Err(EditError::InvalidValue(_))
+    ));
+    assert!(matches!(
+        document.push(&[Key::Name("параметри")], "1"),
+        Err(EditError::NotAList)
+    ));
+    assert!(matches!(
+        document.remove(&[]),
+        Err(EditError::NotFound { depth: 0 })
+    ));
+    assert_eq!(document.to_string(), CONFIG);
+}
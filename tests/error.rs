@@ -0,0 +1,52 @@
+use mavka_did::{parser, Error, ErrorKind};
+
+fn error(input: &str) -> Error {
+    parser::parse(input).unwrap_err()
+}
+
+#[test]
+fn errors_are_std_errors() {
+    fn boxed(input: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        parser::parse(input)?;
+        Ok(())
+    }
+    let err = boxed("[1 2]").unwrap_err();
+    assert!(err.to_string().starts_with("Очікувалася"), "{err}");
+    assert_eq!(
+        err.downcast::<Error>().unwrap().kind,
+        ErrorKind::RightBracket
+    );
+}
+
+#[test]
+fn errors_span_the_found_token() {
+    let input = "(\n  ключ=\"текст\" інше\n)";
+    let err = error(input);
+    assert_eq!(err.kind, ErrorKind::RightParenthesis);
+    assert_eq!(err.found.as_deref(), Some("інше"));
+    assert_eq!(&input[err.start..err.end], "інше");
+    // Columns count characters, not bytes.
+    assert_eq!((err.line, err.column), (2, 16));
+
+    let context = err.context(input);
+    assert_eq!((context.line, context.column), (2, 16));
+    assert_eq!(context.range(), err.start..err.end);
+    assert_eq!((context.end_line, context.end_column), (2, 20));
+}
+
+#[test]
+fn errors_at_the_end_have_no_found_token() {
+    let input = "[\n";
+    let err = error(input);
+    assert_eq!(err.found, None);
+    assert_eq!((err.start, err.end), (2, 2));
+    assert_eq!((err.line, err.column), (2, 1));
+    assert!(err.to_string().contains("знайдено кінець файлу"), "{err}");
+}
+
+#[test]
+fn errors_compare_and_clone() {
+    assert_eq!(error("[1 2]"), error("[1 2]").clone());
+    assert_ne!(error("[1 2]"), error("[1 3]"));
+    assert_ne!(error("[1 2]"), error("[1  2]"));
+}
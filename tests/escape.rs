@@ -0,0 +1,131 @@
+use std::borrow::Cow;
+
+use mavka_did::{
+    parser::{
+        self,
+        ast::{Did, NodeContext},
+        escape::{self, EscapeErrorKind},
+    },
+    Reason,
+};
+
+/// `parser::parse` returns the wasm tree with the `wasm` feature, so this runs against
+/// both trees when the tests are run with and without it.
+#[test]
+fn parse_and_parse_did_validate_escapes_alike() {
+    let cases = [
+        (
+            r#""\uD800""#,
+            Some((1, 7, EscapeErrorKind::UnpairedSurrogate(0xD800))),
+        ),
+        (
+            r#""\uDC00\uD800""#,
+            Some((1, 7, EscapeErrorKind::UnpairedSurrogate(0xDC00))),
+        ),
+        (
+            r#"["а", "x\uD83D"]"#,
+            Some((9, 15, EscapeErrorKind::UnpairedSurrogate(0xD83D))),
+        ),
+        (
+            r#"("\uD800"=1)"#,
+            Some((2, 8, EscapeErrorKind::UnpairedSurrogate(0xD800))),
+        ),
+        (r#""\uD83D\uDE00""#, None),
+        (r#""\"\\\b\f\n\r\tA""#, None),
+    ];
+    for (input, expected) in cases {
+        let native = parser::parse_did(input).map(drop);
+        let default = parser::parse(input).map(drop);
+        let expected = expected.map(|(start, end, kind)| (start, end, Some(Reason::Escape(kind))));
+        for result in [native, default] {
+            let result = result.map_err(|err| (err.start, err.end, err.reason));
+            assert_eq!(result.err(), expected, "{input}");
+        }
+    }
+}
+
+#[test]
+fn recovering_parser_reports_invalid_escapes() {
+    let (_, errors) = parser::parse_recovering(r#"["\uD800", "\uDFFF"]"#);
+    let errors: Vec<_> = errors
+        .iter()
+        .map(|err| (err.start, err.reason.clone()))
+        .collect();
+    assert_eq!(
+        errors,
+        [
+            (
+                2,
+                Some(Reason::Escape(EscapeErrorKind::UnpairedSurrogate(0xD800)))
+            ),
+            (
+                12,
+                Some(Reason::Escape(EscapeErrorKind::UnpairedSurrogate(0xDFFF)))
+            ),
+        ]
+    );
+}
+
+#[test]
+fn surrogate_pairs_decode_to_one_char() {
+    let did = parser::parse_did(r#""\uD83D\uDE00""#).unwrap();
+    let Did::Text(text) = did else {
+        panic!("not a text: {did:?}");
+    };
+    assert_eq!(text.unescape().unwrap(), "😀");
+}
+
+#[test]
+fn texts_without_escapes_are_borrowed() {
+    let decoded = escape::unescape("текст без послідовностей", NodeContext::default()).unwrap();
+    assert!(matches!(decoded, Cow::Borrowed("текст без послідовностей")));
+}
+
+#[test]
+fn every_escape_sequence_is_decoded() {
+    let decoded = escape::unescape(r#"\"\\\b\f\n\r\t\u0456і"#, NodeContext::default());
+    assert_eq!(decoded.unwrap(), "\"\\\u{8}\u{c}\n\r\tіі");
+}
+
+#[test]
+fn bad_escapes_point_at_the_backslash() {
+    let cases = [
+        (r"ab\q", EscapeErrorKind::UnknownEscape('q'), (1, 4, 3)),
+        (r"ї\", EscapeErrorKind::TrailingBackslash, (1, 3, 3)),
+        (r"\u12G4", EscapeErrorKind::InvalidUnicodeEscape, (1, 2, 1)),
+        (r"\u12", EscapeErrorKind::InvalidUnicodeEscape, (1, 2, 1)),
+        (
+            "а\n  \\uDC00",
+            EscapeErrorKind::UnpairedSurrogate(0xDC00),
+            (2, 3, 6),
+        ),
+        (
+            r"\uD800\n",
+            EscapeErrorKind::UnpairedSurrogate(0xD800),
+            (1, 2, 1),
+        ),
+    ];
+    for (raw, kind, (line, column, index)) in cases {
+        let err = escape::unescape(raw, NodeContext::default()).unwrap_err();
+        assert_eq!(err.kind, kind, "{raw}");
+        let context = err.context;
+        assert_eq!(
+            (context.line, context.column, context.index),
+            (line, column, index),
+            "{raw}"
+        );
+    }
+}
+
+#[test]
+fn long_texts_decode_in_linear_time() {
+    let raw = "\\n".repeat(100_000) + "\\q";
+    assert_eq!(
+        escape::unescape(&raw[..200_000], NodeContext::default())
+            .unwrap()
+            .len(),
+        100_000
+    );
+    let err = escape::unescape(&raw, NodeContext::default()).unwrap_err();
+    assert_eq!(err.context.index, 200_001);
+}
@@ -0,0 +1,50 @@
+use mavka_did::{parser, ErrorKind};
+
+#[test]
+fn errors_list_every_expected_alternative() {
+    use ErrorKind::*;
+
+    let cases: &[(&str, &[ErrorKind], usize)] = &[
+        ("", &[AstNode], 0),
+        ("@", &[AstNode], 0),
+        ("[", &[AstNode, RightBracket], 1),
+        ("[1 2]", &[RightBracket, Comma], 3),
+        ("[1] 2", &[Eof], 4),
+        ("Об", &[LeftParenthesis], 4),
+        ("(а 1)", &[EqualsSign], 4),
+        ("(а=)", &[EntryValue], 4),
+        ("(1=2,", &[DictionaryEntryNode, RightParenthesis], 5),
+        ("(\"а\"=1 2)", &[RightParenthesis, Comma], 8),
+        ("Об(а=1", &[RightParenthesis, Comma], 9),
+        ("Об(1=2)", &[Identifier, RightParenthesis], 5),
+        ("Об(а=1,,)", &[Identifier, RightParenthesis], 10),
+    ];
+    for &(input, expected, start) in cases {
+        let err = parser::parse_did(input).unwrap_err();
+        assert_eq!(err.kind, expected[0], "{input}");
+        assert_eq!(err.expected, expected, "{input}");
+        assert_eq!(err.start, start, "{input}");
+        assert_eq!(err.reason, None, "{input}");
+    }
+}
+
+#[test]
+fn messages_name_the_alternatives_and_the_found_text() {
+    let err = parser::parse_did("[1 2]").unwrap_err();
+    assert_eq!(err.found.as_deref(), Some("2"));
+    assert_eq!(
+        err.to_string(),
+        "Очікувалася права квадратна дужка `]` або кома `,`, знайдено `2` (рядок: 1, стовпчик: 4)"
+    );
+
+    let err = parser::parse_did("Об(а=1").unwrap_err();
+    assert_eq!(err.found, None);
+    assert_eq!(
+        err.to_string(),
+        "Очікувалася права кругла дужка `)` або кома `,`, знайдено кінець файлу (рядок: 1, стовпчик: 7)"
+    );
+
+    let err = parser::parse_did("ні так").unwrap_err();
+    assert_eq!(err.found.as_deref(), Some("так"));
+    assert_eq!(err.start..err.end, 5..11);
+}
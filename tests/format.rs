@@ -0,0 +1,153 @@
+use std::{
+    io::Write,
+    process::{Command, Output, Stdio},
+};
+
+use mavka_did::writer::{self, Style, TrailingComma};
+
+/// 26 characters when written on one line.
+const INPUT: &str = "(а=[1, 2, 3], б=Об(в=так))";
+
+fn format(style: Style) -> String {
+    writer::format(INPUT, &style).unwrap()
+}
+
+#[test]
+fn entries_are_wrapped_only_when_they_do_not_fit() {
+    let flat = "(а=[1, 2, 3], б=Об(в=так))\n";
+    assert_eq!(format(Style::default()), flat);
+    assert_eq!(
+        format(Style {
+            max_width: 26,
+            ..Style::default()
+        }),
+        flat
+    );
+    assert_eq!(
+        format(Style {
+            max_width: 25,
+            ..Style::default()
+        }),
+        "(\n  а=[1, 2, 3],\n  б=Об(в=так)\n)\n"
+    );
+    assert_eq!(
+        format(Style::pretty()),
+        "(\n  а=[\n    1,\n    2,\n    3\n  ],\n  б=Об(\n    в=так\n  )\n)\n"
+    );
+}
+
+#[test]
+fn trailing_commas_and_spaces_follow_the_style() {
+    assert_eq!(
+        format(Style {
+            trailing_comma: TrailingComma::Always,
+            space_after_comma: false,
+            ..Style::default()
+        }),
+        "(а=[1,2,3,],б=Об(в=так,),)\n"
+    );
+    assert_eq!(
+        format(Style {
+            max_width: 20,
+            trailing_comma: TrailingComma::Vertical,
+            spaces_around_equals: true,
+            ..Style::default()
+        }),
+        "(\n  а = [1, 2, 3],\n  б = Об(в = так),\n)\n"
+    );
+}
+
+#[test]
+fn tabs_take_tab_width_columns() {
+    // `[1, 2, 3]` starts at column 2 + 2 with a tab width of 2 and just fits into 13.
+    let style = Style {
+        max_width: 13,
+        hard_tabs: true,
+        tab_width: 2,
+        ..Style::default()
+    };
+    assert_eq!(format(style), "(\n\tа=[1, 2, 3],\n\tб=Об(в=так)\n)\n");
+    assert_eq!(
+        format(Style {
+            tab_width: 3,
+            ..style
+        }),
+        "(\n\tа=[\n\t\t1,\n\t\t2,\n\t\t3\n\t],\n\tб=Об(\n\t\tв=так\n\t)\n)\n"
+    );
+}
+
+#[test]
+fn formatting_is_stable() {
+    let styles = [
+        Style::default(),
+        Style::pretty(),
+        Style::compact(),
+        Style {
+            max_width: 10,
+            hard_tabs: true,
+            trailing_comma: TrailingComma::Vertical,
+            ..Style::default()
+        },
+    ];
+    for style in styles {
+        let once = format(style);
+        assert_eq!(writer::format(&once, &style).unwrap(), once, "{style:?}");
+    }
+}
+
+#[test]
+fn invalid_input_is_not_formatted() {
+    let err = writer::format("[1, 2", &Style::default()).unwrap_err();
+    let writer::FormatError::Parse(err) = err;
+    assert_eq!((err.line, err.column), (1, 6));
+}
+
+fn didfmt(args: &[&str], stdin: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_didfmt"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(stdin.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+#[test]
+fn didfmt_formats_standard_input() {
+    let output = didfmt(&["--max-width", "25", "--spaces-around-equals"], INPUT);
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "(\n  а = [1, 2, 3],\n  б = Об(в = так)\n)\n"
+    );
+}
+
+#[test]
+fn didfmt_checks_without_writing() {
+    assert_eq!(didfmt(&["--check"], "[1, 2]\n").status.code(), Some(0));
+    let output = didfmt(&["--check"], "[1,2]");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(output.stdout.is_empty());
+}
+
+#[test]
+fn didfmt_rejects_bad_options_and_input() {
+    assert_eq!(didfmt(&["--indent", "-1"], "").status.code(), Some(2));
+    assert_eq!(
+        didfmt(&["--trailing-comma", "sometimes"], "").status.code(),
+        Some(2)
+    );
+    assert_eq!(didfmt(&["--unknown"], "").status.code(), Some(2));
+    let output = didfmt(&[], "[1,");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .starts_with("<stdin>: "));
+}
@@ -0,0 +1,137 @@
+use mavka_did::{
+    parser::{self, ParseOptions},
+    Reason,
+};
+
+/// Start, end and reason of an error.
+type Failure = (usize, usize, Option<Reason>);
+
+/// The error of the strict parser and the errors of the recovering one.
+fn errors(input: &str, options: &ParseOptions) -> (Option<Failure>, Vec<Failure>) {
+    let strict = parser::parse_did_with(input, options)
+        .err()
+        .map(|err| (err.start, err.end, err.reason));
+    let (_, recovered) = parser::parse_recovering_with(input, options);
+    let recovered = recovered
+        .into_iter()
+        .map(|err| (err.start, err.end, err.reason))
+        .collect();
+    (strict, recovered)
+}
+
+#[test]
+fn values_over_the_limit_are_counted_without_keys() {
+    let options = ParseOptions {
+        max_nodes: 3,
+        ..ParseOptions::default()
+    };
+    assert_eq!(errors("(а=1, б=2)", &options), (None, vec![]));
+    let options = ParseOptions {
+        max_nodes: 2,
+        ..ParseOptions::default()
+    };
+    let expected = (10, 11, Some(Reason::TooManyNodes(2)));
+    assert_eq!(
+        errors("(а=1, б=2)", &options),
+        (Some(expected.clone()), vec![expected])
+    );
+    let expected = (9, 10, Some(Reason::TooManyNodes(2)));
+    assert_eq!(
+        errors("Об(а=[1])", &options),
+        (Some(expected.clone()), vec![expected])
+    );
+}
+
+#[test]
+fn texts_are_measured_in_bytes_as_written() {
+    let options = ParseOptions {
+        max_text_len: 6,
+        ..ParseOptions::default()
+    };
+    assert_eq!(errors(r#"["абв", "\n\n\n"]"#, &options), (None, vec![]));
+
+    let options = ParseOptions {
+        max_text_len: 5,
+        ..ParseOptions::default()
+    };
+    let first = (1, 9, Some(Reason::TextTooLong(5)));
+    assert_eq!(
+        errors(r#"["абв", "\n\n\n"]"#, &options),
+        (
+            Some(first.clone()),
+            vec![first, (11, 19, Some(Reason::TextTooLong(5)))]
+        )
+    );
+    // Keys are texts too.
+    let options = ParseOptions {
+        max_text_len: 3,
+        ..ParseOptions::default()
+    };
+    let expected = (1, 15, Some(Reason::TextTooLong(3)));
+    assert_eq!(
+        errors(r#"("довгий"=1)"#, &options),
+        (Some(expected.clone()), vec![expected])
+    );
+}
+
+#[test]
+fn entries_over_the_limit_are_reported_at_the_first_excess() {
+    let options = ParseOptions {
+        max_entries: 2,
+        ..ParseOptions::default()
+    };
+    assert_eq!(errors("[1, 2]", &options), (None, vec![]));
+    for (input, start, end) in [("[1, 2, 3, 4]", 7, 8), ("[1, 2, [3, 4, 5]]", 7, 8)] {
+        let expected = (start, end, Some(Reason::TooManyEntries(2)));
+        assert_eq!(
+            errors(input, &options),
+            (Some(expected.clone()), vec![expected]),
+            "{input}"
+        );
+    }
+
+    let options = ParseOptions {
+        max_entries: 1,
+        ..ParseOptions::default()
+    };
+    for (input, start, end) in [("Об(а=1, б=2)", 11, 13), ("(1=1, 2=2)", 6, 7)] {
+        let expected = (start, end, Some(Reason::TooManyEntries(1)));
+        assert_eq!(
+            errors(input, &options),
+            (Some(expected.clone()), vec![expected]),
+            "{input}"
+        );
+    }
+}
+
+#[test]
+fn the_first_excess_in_the_input_is_the_error() {
+    let options = ParseOptions {
+        max_entries: 2,
+        max_text_len: 1,
+        ..ParseOptions::default()
+    };
+    let entries = (8, 9, Some(Reason::TooManyEntries(2)));
+    let text = (12, 26, Some(Reason::TextTooLong(1)));
+    assert_eq!(
+        errors(r#"[[1, 2, 3], "довгий"]"#, &options),
+        (Some(entries.clone()), vec![entries, text])
+    );
+    let text = (1, 15, Some(Reason::TextTooLong(1)));
+    let entries = (24, 25, Some(Reason::TooManyEntries(2)));
+    assert_eq!(
+        errors(r#"["довгий", [1, 2, 3]]"#, &options),
+        (Some(text.clone()), vec![text, entries])
+    );
+}
+
+#[test]
+fn limits_apply_to_syntax_trees() {
+    let options = ParseOptions {
+        max_nodes: 1,
+        ..ParseOptions::default()
+    };
+    assert!(parser::parse_cst_with("1", &options).is_ok());
+    let err = parser::parse_cst_with("[1]", &options).unwrap_err();
+    assert_eq!(err.reason, Some(Reason::TooManyNodes(1)));
+}
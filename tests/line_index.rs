@@ -0,0 +1,94 @@
+use mavka_did::line_index::{Encoding, LineIndex};
+
+const ENCODINGS: [Encoding; 3] = [Encoding::Utf8, Encoding::Utf16, Encoding::Utf32];
+
+#[test]
+fn lines_end_with_lf_crlf_or_a_lone_cr() {
+    let index = LineIndex::new("а\nб\r\nв\rг\n");
+    assert_eq!(index.line_count(), 5);
+    let lines: Vec<_> = (1..=5).map(|line| index.line(line)).collect();
+    assert_eq!(
+        lines,
+        [Some("а"), Some("б"), Some("в"), Some("г"), Some("")]
+    );
+    assert_eq!(index.line(0), None);
+    assert_eq!(index.line(6), None);
+
+    assert_eq!(LineIndex::new("").line_count(), 1);
+    assert_eq!(LineIndex::new("").line(1), Some(""));
+}
+
+#[test]
+fn offsets_convert_to_lines_and_columns_and_back() {
+    let input = "😀ї\r\n\tx\r𝄞\n";
+    let index = LineIndex::new(input);
+    let boundaries = input
+        .char_indices()
+        .map(|(offset, _)| offset)
+        .chain([input.len()]);
+    for offset in boundaries {
+        // The `\n` of a `\r\n` is no position of its own.
+        if input[..offset].ends_with('\r') && input[offset..].starts_with('\n') {
+            continue;
+        }
+        for encoding in ENCODINGS {
+            let (line, column) = index.line_col(offset, encoding).unwrap();
+            assert_eq!(
+                index.offset(line, column, encoding),
+                Some(offset),
+                "{offset} {encoding:?}"
+            );
+            let encoded = index.encode(offset, encoding).unwrap();
+            assert_eq!(index.decode(encoded, encoding), Some(offset));
+        }
+    }
+
+    let x = input.find('x').unwrap();
+    assert_eq!(index.line_col(x, Encoding::Utf8), Some((2, 2)));
+    assert_eq!(index.encode(x, Encoding::Utf8), Some(9));
+    assert_eq!(index.encode(x, Encoding::Utf16), Some(6));
+    assert_eq!(index.encode(x, Encoding::Utf32), Some(5));
+    let clef = input.find('𝄞').unwrap();
+    assert_eq!(index.line_col(clef, Encoding::Utf16), Some((3, 1)));
+    assert_eq!(index.line_col(input.len(), Encoding::Utf16), Some((4, 1)));
+}
+
+#[test]
+fn positions_outside_the_input_or_inside_characters_are_none() {
+    let input = "ї😀\nа";
+    let index = LineIndex::new(input);
+    assert_eq!(index.line_col(1, Encoding::Utf8), None);
+    assert_eq!(index.line_col(input.len() + 1, Encoding::Utf8), None);
+    assert_eq!(index.encode(3, Encoding::Utf16), None);
+    // The second unit of the surrogate pair of `😀`.
+    assert_eq!(index.decode(2, Encoding::Utf16), None);
+    assert_eq!(index.decode(3, Encoding::Utf16), Some(6));
+    assert_eq!(index.decode(6, Encoding::Utf16), None);
+
+    assert_eq!(index.offset(1, 3, Encoding::Utf32), Some(6));
+    assert_eq!(index.offset(1, 4, Encoding::Utf32), None);
+    assert_eq!(index.offset(1, 3, Encoding::Utf16), None);
+    assert_eq!(index.offset(0, 1, Encoding::Utf8), None);
+    assert_eq!(index.offset(3, 1, Encoding::Utf8), None);
+    assert_eq!(index.offset(2, 0, Encoding::Utf8), None);
+}
+
+#[test]
+fn contexts_match_the_parsed_ones() {
+    let input = "[\r\n  \"ї\", 1]";
+    let index = LineIndex::new(input);
+    let context = index.context(10).unwrap();
+    assert_eq!(
+        (
+            context.line,
+            context.column,
+            context.byte_column,
+            context.utf16_column
+        ),
+        (2, 7, 8, 7)
+    );
+    assert_eq!((context.char_index, context.utf16_index), (9, 9));
+    assert_eq!(context.range(), 10..10);
+    assert_eq!(index.input(), input);
+    assert_eq!(index.context(input.len() + 1), None);
+}
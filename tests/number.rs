@@ -0,0 +1,156 @@
+use mavka_did::parser::{
+    self,
+    ast::{Did, NumberNode},
+    number::{Decimal, NumberError, NumberErrorKind},
+};
+
+fn number(input: &str) -> NumberNode<'_> {
+    match parser::parse_did(input).unwrap() {
+        Did::Number(node) => node,
+        did => panic!("not a number: {did:?}"),
+    }
+}
+
+fn kind<T: std::fmt::Debug>(result: Result<T, NumberError>) -> NumberErrorKind {
+    result.unwrap_err().kind
+}
+
+fn decimal(input: &str) -> Decimal {
+    number(input).as_decimal().unwrap()
+}
+
+#[test]
+fn integers_convert_within_their_range() {
+    assert_eq!(number("42").as_i64(), Ok(42));
+    assert_eq!(number("-9223372036854775808").as_i64(), Ok(i64::MIN));
+    assert_eq!(number("18446744073709551615").as_u64(), Ok(u64::MAX));
+    assert_eq!(
+        number("-170141183460469231731687303715884105728").as_i128(),
+        Ok(i128::MIN)
+    );
+    assert_eq!(number("1.000").as_i64(), Ok(1));
+    assert_eq!(number("255").parse::<u8>(), Ok(255));
+
+    assert_eq!(kind(number("-1").as_u64()), NumberErrorKind::Overflow);
+    assert_eq!(kind(number("256").parse::<u8>()), NumberErrorKind::Overflow);
+    assert_eq!(
+        kind(number("170141183460469231731687303715884105728").as_i128()),
+        NumberErrorKind::Overflow
+    );
+    assert_eq!(kind(number("1.5").as_i64()), NumberErrorKind::Fractional);
+}
+
+#[test]
+fn floats_are_the_nearest_value() {
+    assert_eq!(number("0.1").as_f64(), Ok(0.1));
+    assert_eq!(number("-2.5").parse::<f32>(), Ok(-2.5));
+    assert_eq!(
+        number(&format!("17976931348623157{}", "0".repeat(292))).as_f64(),
+        Ok(f64::MAX)
+    );
+    let err = number(&format!("1{}", "0".repeat(400)))
+        .as_f64()
+        .unwrap_err();
+    assert_eq!(err.kind, NumberErrorKind::Overflow);
+}
+
+#[test]
+fn decimals_are_exact_and_normalized() {
+    let big = "123456789012345678901234567890.123456789012345678901234567890";
+    assert_eq!(decimal(big).to_string(), big.trim_end_matches('0'));
+    assert_eq!(decimal("001500.000").to_string(), "1500");
+    assert_eq!(decimal("0.00015").to_string(), "0.00015");
+    assert_eq!(
+        decimal(&format!("0.{}15", "0".repeat(25))).to_string(),
+        "1.5e-26"
+    );
+    assert_eq!(decimal("0.10"), decimal("0.1"));
+    assert_eq!(decimal("-0.0"), decimal("0"));
+    assert!(!decimal("-0").is_negative());
+    assert_eq!(decimal("-12.50").digits(), "125");
+    assert_eq!(decimal("-12.50").exponent(), -1);
+    assert!(decimal("1500").is_integer());
+    assert!(!decimal("0.5").is_integer());
+}
+
+#[test]
+fn decimals_are_ordered_by_value() {
+    let ordered = [
+        "-20", "-2", "-1.5", "0", "0.001", "0.01", "1", "9.99", "10", "100.5",
+    ];
+    for pair in ordered.windows(2) {
+        assert!(decimal(pair[0]) < decimal(pair[1]), "{pair:?}");
+    }
+}
+
+#[test]
+fn errors_point_at_the_number() {
+    let Did::List(list) = parser::parse_did("[1,\n  2.5]").unwrap() else {
+        panic!("not a list");
+    };
+    let Did::Number(node) = &list.entries[1] else {
+        panic!("not a number");
+    };
+    let err = node.as_i64().unwrap_err();
+    assert_eq!((err.context.line, err.context.column), (2, 3));
+    assert!(
+        err.to_string().ends_with("(рядок: 2, стовпчик: 3)"),
+        "{err}"
+    );
+}
+
+#[test]
+fn radix_prefixes_exponents_and_separators_are_numbers() {
+    let cases = [
+        ("0xFF", 16, "255"),
+        ("-0xff_ff", 16, "-65535"),
+        ("0b1010", 2, "10"),
+        ("+0o17", 8, "15"),
+        ("1_000_000", 10, "1000000"),
+        ("1e3", 10, "1000"),
+        ("+1.5E-3", 10, "0.0015"),
+        ("2.5e+2", 10, "250"),
+        ("1_0.0_1e0_1", 10, "100.1"),
+    ];
+    for (input, radix, normalized) in cases {
+        let node = number(input);
+        assert_eq!(node.radix(), radix, "{input}");
+        assert_eq!(node.normalized().as_deref(), Ok(normalized), "{input}");
+    }
+    assert_eq!(number("0x7fff_ffff_ffff_ffff").as_i64(), Ok(i64::MAX));
+    assert_eq!(number("0xffffffffffffffff").as_u64(), Ok(u64::MAX));
+    assert_eq!(number("-0b1").as_i64(), Ok(-1));
+    assert_eq!(number("1e3").as_i64(), Ok(1000));
+    assert_eq!(kind(number("1e-3").as_i64()), NumberErrorKind::Fractional);
+    assert_eq!(number("0x10").as_f64(), Ok(16.0));
+    assert_eq!(decimal("0x10"), decimal("1.6e1"));
+}
+
+#[test]
+fn misplaced_separators_and_empty_digits_are_not_numbers() {
+    for input in [
+        "1__0", "_1", "1_", "1._5", "1_.5", "0x", "0x_f", "0xg", "0b2", "0o8", "1e", "1e_1", "1.",
+        ".5", "0X1",
+    ] {
+        assert!(
+            !matches!(parser::parse_did(input), Ok(Did::Number(_))),
+            "{input}"
+        );
+    }
+}
+
+#[test]
+fn huge_literals_overflow_instead_of_hanging() {
+    let hex = format!("0x{}", "f".repeat(1024));
+    assert_eq!(decimal(&hex).digits().len(), 1234);
+    let hex = format!("0x1{}", "0".repeat(1024));
+    assert_eq!(kind(number(&hex).as_decimal()), NumberErrorKind::Overflow);
+    assert_eq!(
+        kind(number("1e99999999999999999999").as_decimal()),
+        NumberErrorKind::Overflow
+    );
+    assert_eq!(
+        kind(number("1e9223372036854775807").as_f64()),
+        NumberErrorKind::Overflow
+    );
+}
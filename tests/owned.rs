@@ -0,0 +1,67 @@
+use std::borrow::Cow;
+
+use mavka_did::parser::{
+    self,
+    ast::{DictionaryEntryKey, Did, ListNode, ObjectEntryNode, ObjectNode, OwnedDid},
+};
+
+fn owned(input: String) -> OwnedDid {
+    parser::parse_did(&input).unwrap().into_owned()
+}
+
+#[test]
+fn owned_trees_outlive_the_input() {
+    let input = r#"Об(а=[1, "б", (2="в", "г"=ні)], д=пусто, е=Порожній())"#;
+    let expected = format!("{:?}", parser::parse_did(input).unwrap());
+    let did = owned(input.to_owned());
+    assert_eq!(format!("{did:?}"), expected);
+}
+
+#[test]
+fn owned_trees_borrow_nothing() {
+    let did = owned(r#"[1, "а", (2=3, "б"=4), Об(в="г")]"#.to_owned());
+    let Did::List(list) = &did else {
+        panic!("not a list: {did:?}");
+    };
+    let [Did::Number(number), Did::Text(text), Did::Dictionary(dictionary), Did::Object(object)] =
+        list.entries.as_slice()
+    else {
+        panic!("unexpected entries: {list:?}");
+    };
+    assert!(matches!(number.value, Cow::Owned(_)));
+    assert!(matches!(text.value, Cow::Owned(_)));
+    for entry in &dictionary.entries {
+        let key = match &entry.key {
+            DictionaryEntryKey::Number(key) => &key.value,
+            DictionaryEntryKey::Text(key) => &key.value,
+        };
+        assert!(matches!(key, Cow::Owned(_)));
+    }
+    assert!(matches!(object.name.value, Cow::Owned(_)));
+    assert!(matches!(object.entries[0].key.value, Cow::Owned(_)));
+}
+
+#[test]
+fn nodes_can_be_taken_apart() {
+    let Did::Object(object) = owned("Об(а=[1, 2])".to_owned()) else {
+        panic!("not an object");
+    };
+    let ObjectNode { name, entries, .. } = object;
+    assert_eq!(name.value, "Об");
+    let ObjectEntryNode { value, .. } = entries.into_iter().next().unwrap();
+    let Did::List(ListNode { entries, .. }) = value else {
+        panic!("not a list");
+    };
+    assert_eq!(entries.len(), 2);
+}
+
+#[test]
+fn recovered_trees_can_be_owned() {
+    let input = "[1, @, 2]".to_owned();
+    let (did, errors) = parser::parse_recovering(&input);
+    let expected = format!("{did:?}");
+    let did = did.into_owned();
+    drop(input);
+    assert_eq!(errors.len(), 1);
+    assert_eq!(format!("{did:?}"), expected);
+}
@@ -0,0 +1,117 @@
+use mavka_did::{
+    convert::ErrorKind,
+    parser::{
+        self,
+        ast::{Did, LogicalNode},
+    },
+    path::Key,
+    writer,
+};
+
+const INPUT: &str = r#"Людина(
+  параметри=(висота=175, 1=1, 1.0="один", "а/б~в"=так, "\u0433"=ні),
+  оцінки=[5, [4, 3]],
+  порожній=Порожній(),
+)"#;
+
+fn did() -> Did<'static> {
+    parser::parse_did(INPUT).unwrap()
+}
+
+/// The source text of the value at `pointer`.
+fn at(pointer: &str) -> Option<&'static str> {
+    did()
+        .pointer(pointer)
+        .map(|did| &INPUT[did.context().range()])
+}
+
+#[test]
+fn paths_find_entries_keys_and_indexes() {
+    let did = did();
+    let text = |path: &[Key<'_>]| did.get_path(path).map(|did| &INPUT[did.context().range()]);
+    assert_eq!(text(&[]), Some(INPUT));
+    assert_eq!(
+        text(&[Key::from("параметри"), Key::from("висота")]),
+        Some("175")
+    );
+    // Number keys are compared by value, and the last of the equal keys is found.
+    assert_eq!(
+        text(&[Key::from("параметри"), Key::from(1)]),
+        Some("\"один\"")
+    );
+    assert_eq!(
+        text(&[Key::from("параметри"), Key::from("1.00")]),
+        Some("\"один\"")
+    );
+    assert_eq!(text(&[Key::from("параметри"), Key::from("г")]), Some("ні"));
+    assert_eq!(
+        text(&[Key::from("оцінки"), Key::from(1), Key::from(0)]),
+        Some("4")
+    );
+
+    assert_eq!(text(&[Key::from("оцінки"), Key::from(2)]), None);
+    assert_eq!(text(&[Key::from("оцінки"), Key::from("0")]), None);
+    assert_eq!(text(&[Key::from(0)]), None);
+    assert_eq!(text(&[Key::from("порожній"), Key::from("а")]), None);
+    assert_eq!(
+        text(&[Key::from("параметри"), Key::from("висота"), Key::from(0)]),
+        None
+    );
+}
+
+#[test]
+fn pointers_are_split_and_unescaped() {
+    assert_eq!(at(""), Some(INPUT));
+    assert_eq!(at("/параметри/висота"), Some("175"));
+    assert_eq!(at("/параметри/а~1б~0в"), Some("так"));
+    assert_eq!(at("/параметри/1"), Some("\"один\""));
+    assert_eq!(at("/оцінки/1/1"), Some("3"));
+    assert_eq!(at("/порожній"), Some("Порожній()"));
+
+    // Indexes have no leading zeros or signs.
+    assert_eq!(at("/оцінки/01"), None);
+    assert_eq!(at("/оцінки/-1"), None);
+    assert_eq!(at("/оцінки/"), None);
+    // Invalid pointers find nothing.
+    assert_eq!(at("параметри"), None);
+    assert_eq!(at("/параметри/а~2"), None);
+    assert_eq!(at("/параметри/а~"), None);
+    assert_eq!(at("/параметри/а/б~0в"), None);
+}
+
+#[test]
+fn values_are_changed_in_place() {
+    let mut did = did();
+    let Some(Did::Number(height)) = did.pointer_mut("/параметри/висота") else {
+        panic!("not a number");
+    };
+    height.value = "180".into();
+    let score = did
+        .get_path_mut(&[Key::from("оцінки"), Key::from(0)])
+        .unwrap();
+    *score = Did::Logical(LogicalNode {
+        value: true,
+        context: score.context(),
+    });
+    assert!(did.pointer_mut("/оцінки/5").is_none());
+    assert!(did.get_path_mut(&[Key::from("немає")]).is_none());
+    assert_eq!(
+        writer::to_string(&did),
+        "Людина(параметри=(висота=180,1=1,1.0=\"один\",\"а/б~в\"=так,\"\\u0433\"=ні),\
+         оцінки=[так,[4,3]],порожній=Порожній())"
+    );
+}
+
+#[test]
+fn values_are_read_as_rust_types() {
+    let did = did();
+    assert_eq!(did.pointer_as::<u32>("/параметри/висота"), Some(Ok(175)));
+    assert_eq!(
+        did.get_path_as::<Vec<u8>>(&[Key::from("оцінки"), Key::from(1)]),
+        Some(Ok(vec![4, 3]))
+    );
+    assert_eq!(did.pointer_as::<String>("/параметри/д"), None);
+    let err = did.pointer_as::<String>("/оцінки/0").unwrap().unwrap_err();
+    assert!(matches!(err.kind, ErrorKind::InvalidType { .. }), "{err}");
+    assert_eq!((err.context.line, err.context.column), (3, 11));
+}
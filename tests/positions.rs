@@ -0,0 +1,75 @@
+use mavka_did::{
+    line_index::LineIndex,
+    parser::{
+        self,
+        ast::{Did, NodeContext},
+        cst::Node,
+        ParseOptions,
+    },
+    Reason,
+};
+
+/// The start of `context` in every unit.
+fn start(context: NodeContext) -> [u64; 7] {
+    [
+        context.line,
+        context.column,
+        context.byte_column,
+        context.utf16_column,
+        context.index,
+        context.char_index,
+        context.utf16_index,
+    ]
+}
+
+fn check_tokens(lines: &LineIndex<'_>, node: &Node<'_>) {
+    for token in node.tokens() {
+        let context = token.context;
+        let at = |index: u64| lines.context(index as usize).unwrap();
+        assert_eq!(start(context), start(at(context.index)), "{token:?}");
+        assert_eq!(context.end(), at(context.end_index), "{token:?}");
+    }
+    for child in node.nodes() {
+        check_tokens(lines, child);
+    }
+}
+
+#[test]
+fn positions_are_counted_in_bytes_characters_and_utf16() {
+    let input = ";; 😀\r\n(\"ключ\"=[\"👍🏽\", 1],\r\t\"ї\"=\n Об(ґ=ні)) ;;; 𝄞\n;;;";
+    let document = parser::parse_cst(input).unwrap();
+    check_tokens(&LineIndex::new(input), document.root());
+
+    let Did::Dictionary(dictionary) = document.into_did() else {
+        panic!("not a dictionary");
+    };
+    let Did::Object(object) = &dictionary.entries[1].value else {
+        panic!("not an object");
+    };
+    let key = object.entries[0].key.context;
+    // After the lone `\r`, `\t"ї"=\n` and ` Об(`.
+    assert_eq!(start(key), [4, 5, 7, 5, 51, 35, 38]);
+    assert_eq!(&input[key.range()], "ґ");
+    assert_eq!(
+        (key.end_column, key.end_byte_column, key.end_utf16_column),
+        (6, 9, 6)
+    );
+}
+
+#[test]
+fn inputs_longer_than_the_limit_are_rejected() {
+    let options = ParseOptions {
+        max_input_len: 4,
+        ..ParseOptions::default()
+    };
+    assert!(parser::parse_did_with("[12]", &options).is_ok());
+    // The limit falls inside `ї`, so the error starts at it.
+    let err = parser::parse_did_with("[1,ї]", &options).unwrap_err();
+    assert_eq!(err.reason, Some(Reason::InputTooLong(4)));
+    assert_eq!((err.start, err.line, err.column), (3, 1, 4));
+
+    let (_, errors) = parser::parse_recovering_with("[1,ї]", &options);
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].reason, Some(Reason::InputTooLong(4)));
+    assert!(parser::parse_cst_with("[1,ї]", &options).is_err());
+}
@@ -0,0 +1,165 @@
+use mavka_did::{
+    parser,
+    query::{Query, QueryError, QueryErrorKind},
+};
+
+const PEOPLE: &str = r#"(
+  люди=[
+    Людина(імʼя="Леся", вік=20, параметри=(висота=160), мови=["uk", "en"], друг=Людина(імʼя="Тарас", вік=17)),
+    Людина(імʼя="Іван", вік=18.0, активний=ні, параметри=(висота=180)),
+    Кіт(імʼя="Мурчик", вік=3, активний=так),
+  ],
+  1="один",
+  "ключ з пробілом"=пусто,
+)"#;
+
+/// The source text of every node `query` finds in `input`.
+fn select<'inp>(input: &'inp str, query: &str) -> Vec<&'inp str> {
+    parser::parse_did(input)
+        .unwrap()
+        .select(query)
+        .unwrap_or_else(|err| panic!("{query}: {err}"))
+        .iter()
+        .map(|did| &input[did.context().range()])
+        .collect()
+}
+
+fn people(query: &str) -> Vec<&'static str> {
+    select(PEOPLE, query)
+}
+
+fn error(query: &str) -> (QueryErrorKind, usize) {
+    let QueryError { kind, column } = Query::parse(query).unwrap_err();
+    (kind, column)
+}
+
+#[test]
+fn steps_go_into_entries_and_elements() {
+    assert_eq!(people(".люди[2].імʼя"), ["\"Мурчик\""]);
+    assert_eq!(people(".\"ключ з пробілом\""), ["пусто"]);
+    assert_eq!(people("[1]"), ["\"один\""]);
+    assert_eq!(people(".\"1\""), ["\"один\""]);
+    assert_eq!(people(".люди[*].вік"), ["20", "18.0", "3"]);
+    assert_eq!(people(".люди.*.вік"), ["20", "18.0", "3"]);
+    assert_eq!(people(".люди[0].мови[*]"), ["\"uk\"", "\"en\""]);
+    assert_eq!(people(".люди[3]"), Vec::<&str>::new());
+    assert_eq!(people(".немає.вік"), Vec::<&str>::new());
+}
+
+#[test]
+fn descendants_and_names_search_the_whole_tree() {
+    assert_eq!(
+        people("..Людина.імʼя"),
+        ["\"Леся\"", "\"Тарас\"", "\"Іван\""]
+    );
+    assert_eq!(people("...висота"), ["160", "180"]);
+    assert_eq!(people("..Кіт.імʼя"), ["\"Мурчик\""]);
+    assert_eq!(people(".люди[*]Кіт.вік"), ["3"]);
+    assert_eq!(
+        select("[[1], [[2]]]", ".."),
+        ["[[1], [[2]]]", "[1]", "1", "[[2]]", "[2]", "2"]
+    );
+}
+
+#[test]
+fn predicates_filter_the_found_nodes() {
+    assert_eq!(
+        people("..Людина[?вік >= 18].імʼя"),
+        ["\"Леся\"", "\"Іван\""]
+    );
+    // Numbers are compared by value.
+    assert_eq!(people("..Людина[?вік == 18].імʼя"), ["\"Іван\""]);
+    assert_eq!(
+        people("..[?імʼя == \"Тарас\" || параметри.висота > 170].імʼя"),
+        ["\"Тарас\"", "\"Іван\""]
+    );
+    assert_eq!(
+        people("..Людина[?вік > 17 && !активний].імʼя"),
+        ["\"Леся\"", "\"Іван\""]
+    );
+    assert_eq!(people("..[?активний].імʼя"), ["\"Мурчик\""]);
+    assert_eq!(
+        people("..[?(вік < 18 || вік > 19) && імʼя != \"Мурчик\"].вік"),
+        ["20", "17"]
+    );
+    // Any of the found values can match.
+    assert_eq!(people("..[?мови[*] == \"en\"].імʼя"), ["\"Леся\""]);
+    // Values of different types are never equal.
+    assert_eq!(people("..[?вік == \"20\"]"), Vec::<&str>::new());
+    assert_eq!(people("..[?вік != \"20\"].вік"), ["20", "17", "18.0", "3"]);
+    assert_eq!(people(".*[?@ == пусто]"), ["пусто"]);
+    assert_eq!(people(".люди[0].мови[*][?@ < \"en~\"]"), ["\"en\""]);
+}
+
+#[test]
+fn projections_take_entries_in_the_order_of_the_keys() {
+    assert_eq!(people(".люди[0]{вік, імʼя, немає}"), ["20", "\"Леся\""]);
+    assert_eq!(
+        people(".люди[*].{ \"імʼя\" ,параметри }"),
+        [
+            "\"Леся\"",
+            "(висота=160)",
+            "\"Іван\"",
+            "(висота=180)",
+            "\"Мурчик\""
+        ]
+    );
+}
+
+#[test]
+fn invalid_queries_point_at_the_error() {
+    assert_eq!(error(".люди["), (QueryErrorKind::UnexpectedEnd, 7));
+    assert_eq!(error(".люди]"), (QueryErrorKind::UnexpectedChar(']'), 6));
+    assert_eq!(error("[?вік > ]"), (QueryErrorKind::UnexpectedChar(']'), 9));
+    assert_eq!(
+        error("[99999999999999999999999]"),
+        (QueryErrorKind::InvalidNumber, 2)
+    );
+    assert!(matches!(
+        error(".\"\\q\"").0,
+        QueryErrorKind::InvalidEscape(_)
+    ));
+    let (kind, column) = error(&format!("[?{}вік]", "(".repeat(200)));
+    assert_eq!(kind, QueryErrorKind::TooDeep(128));
+    // At the 129th parenthesis.
+    assert_eq!(column, 3 + 128);
+    assert!(Query::parse(&format!("[?{}вік{}]", "(".repeat(127), ")".repeat(127))).is_ok());
+    assert!(matches!(
+        error(&format!("[?{}вік]", "!".repeat(200))).0,
+        QueryErrorKind::TooDeep(128)
+    ));
+    assert_eq!(
+        Query::parse("[").unwrap_err().to_string(),
+        "Неочікуваний кінець запиту (стовпчик: 2)"
+    );
+}
+
+#[test]
+fn long_chains_of_conditions_do_not_recurse() {
+    // A small stack, since chains are not limited by the depth of conditions.
+    std::thread::Builder::new()
+        .stack_size(256 * 1024)
+        .spawn(|| {
+            let terms = vec!["вік > 99"; 100_000];
+            let or = format!("..Людина[?{}].імʼя", terms.join(" || "));
+            assert!(people(&or).is_empty());
+            let and = format!("..Людина[?{}].імʼя", vec!["вік"; 100_000].join(" && "));
+            assert_eq!(people(&and), ["\"Леся\"", "\"Тарас\"", "\"Іван\""]);
+        })
+        .unwrap()
+        .join()
+        .unwrap();
+}
+
+#[test]
+fn parsed_queries_can_be_reused() {
+    let query: Query = "..Людина[?вік < 18].імʼя".parse().unwrap();
+    let did = parser::parse_did(PEOPLE).unwrap();
+    let names: Vec<_> = query
+        .select(&did)
+        .iter()
+        .map(|did| &PEOPLE[did.context().range()])
+        .collect();
+    assert_eq!(names, ["\"Тарас\""]);
+    assert_eq!(query, Query::parse("..Людина[?вік < 18].імʼя").unwrap());
+}
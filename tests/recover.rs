@@ -0,0 +1,196 @@
+use mavka_did::{
+    parser::{self, ast::Did, DuplicateKeys, ParseOptions, Warning},
+    writer, ErrorKind, Reason,
+};
+
+const VALID: &[&str] = &[
+    "пусто",
+    "так",
+    "ні",
+    "-0xff_ff",
+    "1.5e-3",
+    r#""текст \"у лапках\" \u0041""#,
+    "()",
+    "[]",
+    "Порожній()",
+    "[1, 2, 3,]",
+    r#"(1=так, "ключ"=ні, ключ=пусто)"#,
+    "Людина(імʼя=\"Іван\", вік=30, друзі=[Людина(імʼя=\"Петро\")])",
+    // Identifiers that start with a keyword.
+    "ніч()",
+    "такий(а=1)",
+    "[пустота(), ні, такт(такі=так)]",
+    "(ніхто=1, пустий=[так])",
+    "Обʼєкт(нічого=ні, так_ні=так)",
+    ";; коментар\n[\n  1, ;;; блок ;;; 2\n] ;; кінець\n",
+    "  \t\r\n( а = 1 ,\n б = [ ] )  ",
+];
+
+#[test]
+fn recovering_parser_agrees_with_parse_did_on_valid_input() {
+    for input in VALID {
+        let expected = parser::parse_did(input).unwrap_or_else(|err| panic!("{input}: {err}"));
+        let (did, errors) = parser::parse_recovering(input);
+        assert!(errors.is_empty(), "{input}: {errors:?}");
+        assert_eq!(format!("{did:?}"), format!("{expected:?}"), "{input}");
+    }
+}
+
+#[test]
+fn keywords_are_not_identifiers() {
+    for input in ["пусто()", "так(а=1)", "(ні=1)"] {
+        assert!(parser::parse_did(input).is_err(), "{input}");
+        assert!(!parser::parse_recovering(input).1.is_empty(), "{input}");
+    }
+}
+
+#[test]
+fn every_error_is_reported_and_replaced() {
+    let input = "[1, @, (а=), Об(б=2, 3), \"\\uD800\"]";
+    let (did, errors) = parser::parse_recovering(input);
+    let positions: Vec<_> = errors.iter().map(|err| (err.line, err.column)).collect();
+    assert_eq!(positions, [(1, 5), (1, 11), (1, 22), (1, 27)]);
+    let Did::List(list) = did else {
+        panic!("not a list: {did:?}");
+    };
+    assert_eq!(list.entries.len(), 5);
+    assert!(list.entries[1].is_error());
+}
+
+#[test]
+fn error_lines_and_columns_count_characters() {
+    let input = "[\n  \"ї\", @,\r\n  ;; коментар\n  #\n]";
+    let (_, errors) = parser::parse_recovering(input);
+    let positions: Vec<_> = errors.iter().map(|err| (err.line, err.column)).collect();
+    assert_eq!(positions, [(2, 8), (4, 3)]);
+}
+
+#[test]
+fn unterminated_block_comments_are_reported_alike() {
+    for (input, start) in [(";;; x", 0), ("[1, ;;; x ]", 4), ("\";;;\" ;;; x", 6)] {
+        let err = parser::parse_did(input).unwrap_err();
+        let (_, errors) = parser::parse_recovering(input);
+        let expected = (start, input.len(), Some(Reason::UnterminatedComment));
+        assert_eq!((err.start, err.end, err.reason), expected, "{input}");
+        let first = &errors[0];
+        assert_eq!(
+            (first.start, first.end, first.reason.clone()),
+            expected,
+            "{input}"
+        );
+    }
+}
+
+/// The written tree, with error nodes written as the text they replace, and the start
+/// and the last expected alternative of every error.
+fn recovered(input: &str) -> (String, Vec<(usize, ErrorKind)>) {
+    let (did, errors) = parser::parse_recovering(input);
+    let errors = errors
+        .iter()
+        .map(|err| {
+            assert!(err.expected.contains(&err.kind), "{input}: {err:?}");
+            (err.start, *err.expected.last().unwrap())
+        })
+        .collect();
+    (writer::to_string(&did), errors)
+}
+
+#[test]
+fn parsing_resumes_at_the_next_comma_or_closing_bracket() {
+    use ErrorKind::*;
+
+    assert_eq!(
+        recovered("[1 2 3]"),
+        ("[1]".to_owned(), vec![(3, RightBracket)])
+    );
+    assert_eq!(
+        recovered("[1,,2]"),
+        ("[1,,2]".to_owned(), vec![(3, AstNode)])
+    );
+    assert_eq!(
+        recovered("(а=1 б=2, в=3)"),
+        ("(а=1,в=3)".to_owned(), vec![(6, RightParenthesis)])
+    );
+    assert_eq!(
+        recovered("Об(а=1, 2=3, б=@@ @, в=так)"),
+        (
+            "Об(а=1,б=@@ @,в=так)".to_owned(),
+            vec![(11, Identifier), (19, EntryValue)]
+        )
+    );
+    assert_eq!(
+        recovered("[1, 2)], 3]"),
+        ("[1,2]".to_owned(), vec![(5, RightBracket), (7, Eof)])
+    );
+    assert_eq!(
+        recovered("[1, (а=2"),
+        ("[1,(а=2)]".to_owned(), vec![(9, RightParenthesis)])
+    );
+    assert_eq!(recovered(")"), (String::new(), vec![(0, AstNode)]));
+}
+
+#[test]
+fn limits_are_reported_once_and_parsing_goes_on() {
+    let options = ParseOptions {
+        max_depth: 2,
+        ..ParseOptions::default()
+    };
+    let (did, errors) = parser::parse_recovering_with("[[[1]], [[2]], 3]", &options);
+    let reasons: Vec<_> = errors
+        .iter()
+        .map(|err| (err.start, err.reason.clone()))
+        .collect();
+    assert_eq!(
+        reasons,
+        [(2, Some(Reason::TooDeep(2))), (9, Some(Reason::TooDeep(2)))]
+    );
+    assert_eq!(writer::to_string(&did), "[[[1]],[[2]],3]");
+
+    let options = ParseOptions {
+        max_nodes: 3,
+        ..ParseOptions::default()
+    };
+    let (_, errors) = parser::parse_recovering_with("[1, 2, 3, 4]", &options);
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].reason, Some(Reason::TooManyNodes(3)));
+    assert_eq!(errors[0].start, 7);
+}
+
+#[test]
+fn duplicate_keys_follow_the_options() {
+    let input = "(а=1, б=2, а=3, б=[4], @)";
+    let options = ParseOptions {
+        duplicate_keys: DuplicateKeys::Reject,
+        ..ParseOptions::default()
+    };
+    let (_, errors) = parser::parse_recovering_with(input, &options);
+    let reasons: Vec<_> = errors
+        .iter()
+        .map(|err| (err.start, err.reason.clone()))
+        .collect();
+    assert_eq!(
+        reasons,
+        [
+            (13, Some(Reason::DuplicateKey { first: 1..3 })),
+            (19, Some(Reason::DuplicateKey { first: 7..9 })),
+            (27, None)
+        ]
+    );
+
+    let options = ParseOptions {
+        duplicate_keys: DuplicateKeys::LastWins,
+        ..ParseOptions::default()
+    };
+    let (did, errors, warnings) = parser::parse_recovering_with_warnings(input, &options);
+    assert_eq!(errors.len(), 1);
+    assert_eq!(writer::to_string(&did), "(а=3,б=[4])");
+    let keys: Vec<_> = warnings
+        .iter()
+        .map(|Warning::DuplicateKey { first, key }| (first.index, key.index))
+        .collect();
+    assert_eq!(keys, [(1, 13), (7, 19)]);
+
+    let (did, errors) = parser::parse_recovering_with(input, &options);
+    assert_eq!(errors.len(), 1);
+    assert_eq!(writer::to_string(&did), "(а=3,б=[4])");
+}
@@ -0,0 +1,97 @@
+use std::collections::BTreeMap;
+
+use mavka_did::{from_str, to_string, to_string_pretty};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Людина {
+    імʼя: String,
+    вік: u8,
+    зріст: f64,
+    друг: Option<Box<Людина>>,
+    зацікавлення: Vec<String>,
+}
+
+fn леся() -> Людина {
+    Людина {
+        імʼя: "Леся \"Українка\"\n".to_owned(),
+        вік: 42,
+        зріст: 1.6,
+        друг: None,
+        зацікавлення: vec!["поезія".to_owned()],
+    }
+}
+
+#[test]
+fn structs_serialize_into_objects() {
+    assert_eq!(
+        to_string(&леся()).unwrap(),
+        r#"Людина(імʼя="Леся \"Українка\"\n",вік=42,зріст=1.6,друг=пусто,зацікавлення=["поезія"])"#
+    );
+    assert_eq!(
+        to_string_pretty(&леся()).unwrap(),
+        "Людина(\n  імʼя=\"Леся \\\"Українка\\\"\\n\",\n  вік=42,\n  зріст=1.6,\n  друг=пусто,\n  \
+         зацікавлення=[\n    \"поезія\"\n  ]\n)"
+    );
+}
+
+#[test]
+fn serialized_values_deserialize_back() {
+    let людина = Людина {
+        друг: Some(Box::new(леся())),
+        ..леся()
+    };
+    let written = to_string(&людина).unwrap();
+    assert_eq!(from_str::<Людина>(&written).unwrap(), людина);
+
+    let map = BTreeMap::from([(1, -1.5), (20, 0.0)]);
+    let written = to_string(&map).unwrap();
+    assert_eq!(written, "(1=-1.5,20=0)");
+    assert_eq!(from_str::<BTreeMap<u8, f64>>(&written).unwrap(), map);
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+enum Фігура {
+    Точка,
+    Коло { радіус: u32 },
+    Квадрат(u32),
+    Відрізок(u32, u32),
+}
+
+#[test]
+fn enum_variants_serialize_into_objects() {
+    let cases = [
+        (Фігура::Точка, "Точка()"),
+        (Фігура::Коло { радіус: 1 }, "Коло(радіус=1)"),
+        (Фігура::Квадрат(2), "Квадрат(значення=2)"),
+        (Фігура::Відрізок(3, 4), "Відрізок(значення=[3,4])"),
+    ];
+    for (value, expected) in cases {
+        let written = to_string(&value).unwrap();
+        assert_eq!(written, expected);
+        assert_eq!(from_str::<Фігура>(&written).unwrap(), value);
+    }
+}
+
+#[test]
+fn map_keys_are_quoted_unless_they_are_identifiers() {
+    let map = BTreeMap::from([("ключ", ()), ("ні", ()), ("з пробілом", ())]);
+    assert_eq!(
+        to_string(&map).unwrap(),
+        r#"("з пробілом"=пусто,ключ=пусто,"ні"=пусто)"#
+    );
+}
+
+#[derive(Serialize)]
+struct Поле {
+    #[serde(rename = "з пробілом")]
+    поле: u8,
+}
+
+#[test]
+fn values_that_cannot_be_written_are_errors() {
+    assert!(to_string(&f64::NAN).is_err());
+    assert!(to_string(&[f32::INFINITY]).is_err());
+    assert!(to_string(&Поле { поле: 1 }).is_err());
+    assert!(to_string(&BTreeMap::from([(vec![1], 1)])).is_err());
+}
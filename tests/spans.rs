@@ -0,0 +1,101 @@
+use mavka_did::parser::{
+    self,
+    ast::{DictionaryEntryKey, Did, NodeContext},
+};
+
+/// The source text of every node, key and object name of `did`, in the order they are
+/// written.
+fn spans<'inp>(input: &'inp str, did: &Did<'_>) -> Vec<&'inp str> {
+    let text = |context: NodeContext| &input[context.range()];
+    let mut found = vec![text(did.context())];
+    match did {
+        Did::Dictionary(node) => {
+            for entry in &node.entries {
+                found.push(text(entry.context));
+                found.push(text(match &entry.key {
+                    DictionaryEntryKey::Number(key) => key.context,
+                    DictionaryEntryKey::Text(key) => key.context,
+                }));
+                found.extend(spans(input, &entry.value));
+            }
+        }
+        Did::Object(node) => {
+            found.push(text(node.name.context));
+            for entry in &node.entries {
+                found.push(text(entry.context));
+                found.push(text(entry.key.context));
+                found.extend(spans(input, &entry.value));
+            }
+        }
+        Did::List(node) => {
+            for entry in &node.entries {
+                found.extend(spans(input, entry));
+            }
+        }
+        _ => {}
+    }
+    found
+}
+
+#[test]
+fn every_node_spans_its_source_text() {
+    let input = " Об( ключ = [1 , \"т\" ] ,інший=(\"к\"=пусто, -1.5e3=так) ) ;; кінець";
+    let did = parser::parse_did(input).unwrap();
+    assert_eq!(
+        spans(input, &did),
+        [
+            "Об( ключ = [1 , \"т\" ] ,інший=(\"к\"=пусто, -1.5e3=так) )",
+            "Об",
+            "ключ = [1 , \"т\" ]",
+            "ключ",
+            "[1 , \"т\" ]",
+            "1",
+            "\"т\"",
+            "інший=(\"к\"=пусто, -1.5e3=так)",
+            "інший",
+            "(\"к\"=пусто, -1.5e3=так)",
+            "\"к\"=пусто",
+            "\"к\"",
+            "пусто",
+            "-1.5e3=так",
+            "-1.5e3",
+            "так",
+        ]
+    );
+}
+
+#[test]
+fn ends_are_counted_in_every_unit() {
+    let input = "[\n  \"ї😀\",\r\n  (а=\n  1)]";
+    let Did::List(list) = parser::parse_did(input).unwrap() else {
+        panic!("not a list");
+    };
+    let text = list.entries[0].context();
+    assert_eq!((text.line, text.column), (2, 3));
+    assert_eq!((text.end_line, text.end_column), (2, 7));
+    assert_eq!(text.end_byte_column, 3 + 8);
+    assert_eq!(text.end_utf16_column, 3 + 5);
+    assert_eq!(text.end_index as usize, input.find(',').unwrap());
+    assert_eq!(text.end_char_index, 8);
+    assert_eq!(text.end_utf16_index, 9);
+
+    let dictionary = list.entries[1].context();
+    assert_eq!((dictionary.line, dictionary.column), (3, 3));
+    assert_eq!((dictionary.end_line, dictionary.end_column), (4, 5));
+    assert_eq!(dictionary.end_index as usize, input.len() - 1);
+
+    assert_eq!(list.context.range(), 0..input.len());
+    assert_eq!((list.context.end_line, list.context.end_column), (4, 6));
+    assert_eq!(list.context.end(), NodeContext::at(input, input.len()));
+    assert_eq!(NodeContext::default().range(), 0..0);
+}
+
+#[test]
+fn error_nodes_span_the_skipped_text() {
+    let input = "[1, @ #, 2]";
+    let (did, _) = parser::parse_recovering(input);
+    let Did::List(list) = did else {
+        panic!("not a list");
+    };
+    assert_eq!(&input[list.entries[1].context().range()], "@ #");
+}
@@ -0,0 +1,148 @@
+use std::borrow::Cow;
+
+use mavka_did::{
+    parser::{
+        self,
+        ast::{
+            DictionaryEntryNode, Did, EmptyNode, ErrorNode, ListNode, LogicalNode, NumberNode,
+            ObjectNode, TextNode,
+        },
+    },
+    visit::{self, Fold, Visitor, VisitorMut},
+    writer,
+};
+
+const INPUT: &str = r#"Об(а=[1, "б"], в=(2="г", "д"=пусто), е=Вкладений(ж=так))"#;
+
+/// Every node the visitor meets, in order.
+#[derive(Default)]
+struct Trace<'ast>(Vec<String>, Vec<&'ast ErrorNode<'ast>>);
+
+impl<'ast> Visitor<'ast> for Trace<'ast> {
+    fn visit_empty(&mut self, _node: &'ast EmptyNode) {
+        self.0.push("пусто".to_owned());
+    }
+
+    fn visit_logical(&mut self, node: &'ast LogicalNode) {
+        self.0.push(node.value.to_string());
+    }
+
+    fn visit_number(&mut self, node: &'ast NumberNode<'ast>) {
+        self.0.push(format!("число {}", node.value));
+    }
+
+    fn visit_text(&mut self, node: &'ast TextNode<'ast>) {
+        self.0.push(format!("текст {}", node.value));
+    }
+
+    fn visit_object(&mut self, node: &'ast ObjectNode<'ast>) {
+        self.0.push("(".to_owned());
+        visit::walk_object(self, node);
+        self.0.push(")".to_owned());
+    }
+
+    fn visit_object_name(&mut self, name: &'ast TextNode<'ast>) {
+        self.0.push(format!("назва {}", name.value));
+    }
+
+    fn visit_error(&mut self, node: &'ast ErrorNode<'ast>) {
+        self.1.push(node);
+    }
+}
+
+#[test]
+fn visitors_meet_every_node_in_order() {
+    let did = parser::parse_did(INPUT).unwrap();
+    let mut trace = Trace::default();
+    trace.visit_did(&did);
+    assert_eq!(
+        trace.0,
+        [
+            "(",
+            "назва Об",
+            "текст а",
+            "число 1",
+            "текст б",
+            "текст в",
+            "число 2",
+            "текст г",
+            "текст д",
+            "пусто",
+            "текст е",
+            "(",
+            "назва Вкладений",
+            "текст ж",
+            "true",
+            ")",
+            ")"
+        ]
+    );
+}
+
+#[test]
+fn visitors_can_keep_references_to_the_nodes() {
+    let (did, errors) = parser::parse_recovering("[1, @, (а=#)]");
+    assert_eq!(errors.len(), 2);
+    let mut trace = Trace::default();
+    trace.visit_did(&did);
+    let skipped: Vec<_> = trace.1.iter().map(|node| &*node.text).collect();
+    assert_eq!(skipped, ["@", "#"]);
+}
+
+/// Negates numbers and makes every key upper case.
+struct Shout;
+
+impl<'inp> VisitorMut<'inp> for Shout {
+    fn visit_number_mut(&mut self, node: &mut NumberNode<'inp>) {
+        node.value = Cow::Owned(format!("-{}", node.value));
+    }
+
+    fn visit_dictionary_entry_mut(&mut self, entry: &mut DictionaryEntryNode<'inp>) {
+        // Entry values only; the keys stay as they are.
+        self.visit_did_mut(&mut entry.value);
+    }
+
+    fn visit_object_key_mut(&mut self, key: &mut TextNode<'inp>) {
+        key.value = Cow::Owned(key.value.to_uppercase());
+    }
+}
+
+#[test]
+fn mutable_visitors_change_the_tree_in_place() {
+    let mut did = parser::parse_did(INPUT).unwrap();
+    Shout.visit_did_mut(&mut did);
+    assert_eq!(
+        writer::to_string(&did),
+        r#"Об(А=[-1,"б"],В=(2="г",д=пусто),Е=Вкладений(Ж=так))"#
+    );
+}
+
+/// Drops empty values from lists and renames objects.
+struct Tidy;
+
+impl<'inp> Fold<'inp> for Tidy {
+    fn fold_list(&mut self, mut node: ListNode<'inp>) -> ListNode<'inp> {
+        node.entries.retain(|did| !matches!(did, Did::Empty(_)));
+        visit::fold_list(self, node)
+    }
+
+    fn fold_object_name(&mut self, mut name: TextNode<'inp>) -> TextNode<'inp> {
+        name.value = Cow::Owned(format!("Новий{}", name.value));
+        name
+    }
+}
+
+#[test]
+fn folds_build_a_new_tree() {
+    let did = parser::parse_did("[пусто, 1, [пусто], Об(а=[пусто, так])]").unwrap();
+    assert_eq!(
+        writer::to_string(&Tidy.fold_did(did)),
+        "[1,[],НовийОб(а=[так])]"
+    );
+    // The default methods give the same tree back.
+    struct Identity;
+    impl Fold<'_> for Identity {}
+    let did = parser::parse_did(INPUT).unwrap();
+    let expected = format!("{did:?}");
+    assert_eq!(format!("{:?}", Identity.fold_did(did)), expected);
+}
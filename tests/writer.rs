@@ -0,0 +1,73 @@
+use mavka_did::{
+    parser::{
+        self,
+        ast::{Did, NodeContext, TextNode},
+    },
+    writer,
+};
+
+#[test]
+fn written_text_parses_back_to_the_same_tree() {
+    let cases = [
+        ("пусто", "пусто"),
+        ("  так ", "так"),
+        ("-12.5", "-12.5"),
+        (r#""а\"бA""#, r#""а\"бA""#),
+        ("[ 1 , 2 , ]", "[1,2]"),
+        (
+            r#"( "так" = 1, ключ = 2, 3 = ні, "з пробілом" = [] )"#,
+            r#"("так"=1,ключ=2,3=ні,"з пробілом"=[])"#,
+        ),
+        (
+            "Людина( імʼя = \"Леся\", друзі = [ Людина() ] )",
+            "Людина(імʼя=\"Леся\",друзі=[Людина()])",
+        ),
+        // Names that start with a keyword are identifiers.
+        (
+            "[ніч( такий = пусто ), пустота()]",
+            "[ніч(такий=пусто),пустота()]",
+        ),
+    ];
+    for (input, expected) in cases {
+        let written = writer::to_string(&parser::parse_did(input).unwrap());
+        assert_eq!(written, expected, "{input}");
+        let again = writer::to_string(&parser::parse_did(&written).unwrap());
+        assert_eq!(again, written, "{input}");
+    }
+}
+
+#[test]
+fn pretty_text_puts_every_entry_on_its_own_line() {
+    let did = parser::parse_did("[1, (а=[]), Об(б=Пусто())]").unwrap();
+    assert_eq!(
+        writer::to_string_pretty(&did),
+        "[\n  1,\n  (\n    а=[]\n  ),\n  Об(\n    б=Пусто()\n  )\n]"
+    );
+}
+
+#[test]
+fn texts_are_escaped_where_needed() {
+    assert_eq!(writer::escape("просто"), "просто");
+    assert_eq!(writer::escape("\"\\\n\t\u{1}"), r#"\"\\\n\t\u0001"#);
+
+    // Valid escapes of a hand-built node are kept, anything else is escaped.
+    let did = Did::Text(TextNode {
+        value: "\\n \\q \" \n".into(),
+        context: NodeContext::default(),
+    });
+    let written = writer::to_string(&did);
+    assert_eq!(written, r#""\n \\q \" \n""#);
+    let Did::Text(text) = parser::parse_did(&written).unwrap() else {
+        panic!("not a text: {written}");
+    };
+    assert_eq!(text.unescape().unwrap(), "\n \\q \" \n");
+}
+
+#[test]
+fn dictionary_keys_are_quoted_unless_they_are_identifiers() {
+    let did = parser::parse_did(r#"("ключ"=1, "ніч"=2, "ні"=3, "1а"=4, "а б"=5)"#).unwrap();
+    assert_eq!(
+        writer::to_string(&did),
+        r#"(ключ=1,ніч=2,"ні"=3,"1а"=4,"а б"=5)"#
+    );
+}